@@ -0,0 +1,95 @@
+use std::path::Path;
+
+use chrono::{Datelike, Local, NaiveDate};
+use times::config::Config;
+use times::edit::{insert_span, InsertError};
+use times::{Date, Granularity, Topic};
+
+use crate::{
+    convert_options, hooks, lock, month_file, read_document, timesheets_dir, write_validated, Error,
+};
+
+/// Reads the calendar at `input`, which may be an http(s) or webcal URL with the `caldav`
+/// feature. `CALDAV_USER` and `CALDAV_PASSWORD` are sent as basic authentication.
+fn read(input: &Path) -> Result<String, Error> {
+    #[cfg(feature = "caldav")]
+    if let Some(url) = input.to_str().filter(|s| s.contains("://")) {
+        use base64::Engine;
+
+        let url = url.replacen("webcal://", "https://", 1);
+        let mut request = ureq::get(&url);
+        if let Ok(user) = std::env::var("CALDAV_USER") {
+            let password = std::env::var("CALDAV_PASSWORD").unwrap_or_default();
+            let credentials =
+                base64::engine::general_purpose::STANDARD.encode(format!("{user}:{password}"));
+            request = request.set("Authorization", &format!("Basic {credentials}"));
+        }
+        return request
+            .call()
+            .map_err(|e| Error::Fetch(Box::new(e)))?
+            .into_string()
+            .map_err(Error::InputFile);
+    }
+    fs_err::read_to_string(input).map_err(Error::InputFile)
+}
+
+/// Inserts the meetings of the calendar at `input` that already took place into the days of
+/// their files, continuing the interrupted entry after each of them.
+pub fn import(
+    config: &Config,
+    input: &Path,
+    since: Option<NaiveDate>,
+    dry_run: bool,
+    granularity: Option<Granularity>,
+) -> Result<(), Error> {
+    let options = convert_options(config, granularity);
+    let text = read(input)?;
+    let calendar_options = times::calendar::Options {
+        email: config.import.email.clone(),
+        since,
+        until: Local::now().naive_local(),
+    };
+    let events = times::calendar::events(text.as_bytes(), &calendar_options)
+        .map_err(|e| Error::Calendar(input.to_owned(), e))?;
+    let identifier = config.import.meetings.as_deref().unwrap_or("Meeting");
+    let minutes = options.granularity.minutes();
+    let dir = timesheets_dir(config);
+    for events in
+        events.chunk_by(|a, b| (a.date.year(), a.date.month()) == (b.date.year(), b.date.month()))
+    {
+        let path = month_file(&dir, events[0].date);
+        let lock = lock(&path)?;
+        let mut document = read_document(&path)?;
+        let mut added = 0;
+        for event in events {
+            let (start, end) = (event.start.rounded(minutes), event.end.rounded(minutes));
+            if end <= start {
+                continue;
+            }
+            let date = Date::new(event.date);
+            let topic = Topic::Project {
+                identifier: identifier.to_owned(),
+                comment: event.summary.clone(),
+            };
+            match insert_span(&mut document, date, start, end, &topic) {
+                Ok(()) => {
+                    println!("{date} {start}-{end} {topic}");
+                    added += 1;
+                }
+                Err(e @ (InsertError::EntryExists(..) | InsertError::Overlaps(..))) => {
+                    println!("Skipped {topic}: {e}");
+                }
+                Err(e) => return Err(e.into()),
+            }
+        }
+        if added > 0 && !dry_run {
+            write_validated(config, &path, &document, &options)?;
+            drop(lock);
+            hooks::changed(config, &path, None);
+        }
+    }
+    if dry_run {
+        println!("Dry run, no file was changed");
+    }
+    Ok(())
+}
@@ -0,0 +1,379 @@
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::time::Duration;
+
+use clap::ValueEnum;
+use notify::{EventKind, RecursiveMode, Watcher};
+use serde_json::{json, Value};
+use times::catalog::Catalog;
+use times::convert::{Day, Incremental};
+use times::data::Data;
+use times::diagnostic::{Diagnostic, Severity};
+use times::i18n::{Language, Message};
+use times::lint::{unterminated_days, Warning};
+use times::parse::parse_month;
+
+use crate::{month_of, read_month, Error};
+
+#[derive(ValueEnum, Copy, Clone, Default, Eq, PartialEq)]
+pub enum CheckFormat {
+    #[default]
+    Text,
+    Json,
+    Sarif,
+}
+
+pub fn read_catalog(path: Option<&Path>) -> Result<Option<Catalog>, Error> {
+    path.map(|path| {
+        Ok(fs_err::read_to_string(path)
+            .map_err(Error::InputFile)?
+            .parse()
+            .unwrap())
+    })
+    .transpose()
+}
+
+pub fn warnings(
+    days: &[times::convert::Day],
+    catalog: Option<&Catalog>,
+    options: &times::lint::Options,
+) -> Vec<times::lint::Warning> {
+    let mut warnings = times::lint::lint(days, options);
+    if let Some(catalog) = catalog {
+        warnings.extend(times::lint::unknown_identifiers(days, catalog));
+    }
+    warnings
+}
+
+pub fn print_warnings(
+    days: &[times::convert::Day],
+    catalog: Option<&Path>,
+    options: &times::lint::Options,
+    language: Language,
+) -> Result<(), Error> {
+    let catalog = read_catalog(catalog)?;
+    for warning in warnings(days, catalog.as_ref(), options) {
+        eprintln!(
+            "{}: {}",
+            language.tr(Message::Warning),
+            language.tr(Message::Lint(&warning))
+        );
+    }
+    Ok(())
+}
+
+pub fn watch(
+    path: &Path,
+    convert_options: &times::convert::Options,
+    catalog: Option<&Path>,
+    options: &times::lint::Options,
+    language: Language,
+) -> Result<(), Error> {
+    const DEBOUNCE: Duration = Duration::from_millis(100);
+
+    let catalog = read_catalog(catalog)?;
+    // Only the days that changed since the last check are converted again.
+    let mut incremental = Incremental::new(convert_options.clone());
+    let mut check = || match reload(path, &mut incremental) {
+        Ok(month) => {
+            let warnings = warnings(&month.days, catalog.as_ref(), options);
+            if warnings.is_empty() {
+                println!("{}", language.tr(Message::NoProblems));
+            }
+            for warning in warnings {
+                eprintln!(
+                    "{}: {}",
+                    language.tr(Message::Warning),
+                    language.tr(Message::Lint(&warning))
+                );
+            }
+        }
+        Err(e) => e.print(language),
+    };
+
+    let (tx, rx) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(tx)?;
+    // Editors often replace the file instead of writing to it, so watch the whole directory.
+    let dir = path
+        .parent()
+        .filter(|p| !p.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    watcher.watch(dir, RecursiveMode::NonRecursive)?;
+    check();
+    for event in &rx {
+        let event = event?;
+        if matches!(event.kind, EventKind::Access(_))
+            || !event
+                .paths
+                .iter()
+                .any(|p| p.file_name() == path.file_name())
+        {
+            continue;
+        }
+        while rx.recv_timeout(DEBOUNCE).is_ok() {}
+        println!("\n{} changed", path.display());
+        check();
+    }
+    Ok(())
+}
+
+fn reload(path: &Path, incremental: &mut Incremental) -> Result<times::convert::Month, Error> {
+    let file = fs_err::File::open(path).map_err(Error::InputFile)?;
+    let located = |e: times::Error| Error::Times(e.with_path(path));
+    let timesheet =
+        parse_month(&mut BufReader::new(file), month_of(path)).map_err(|e| located(e.into()))?;
+    incremental
+        .convert(timesheet)
+        .map_err(|e| located(e.into()))
+}
+
+pub fn check_all(
+    dir: &Path,
+    convert_options: &times::convert::Options,
+    catalog: Option<&Path>,
+    options: &times::lint::Options,
+    language: Language,
+) -> Result<(), Error> {
+    let catalog = read_catalog(catalog)?;
+    let data = Data::from_dir(dir)?;
+    let mut failed = 0;
+    for file in &data.files {
+        match file.load(convert_options) {
+            Ok(month) => {
+                for warning in warnings(&month.days, catalog.as_ref(), options) {
+                    eprintln!(
+                        "{}: {}: {}",
+                        language.tr(Message::Warning),
+                        file.path.display(),
+                        language.tr(Message::Lint(&warning))
+                    );
+                }
+            }
+            Err(e) => {
+                Error::Data(e).print(language);
+                failed += 1;
+            }
+        }
+    }
+    if failed == 0 {
+        Ok(())
+    } else {
+        Err(Error::Failed(failed, data.files.len()))
+    }
+}
+
+fn diagnostics(
+    path: &Path,
+    convert_options: &times::convert::Options,
+    catalog: Option<&Catalog>,
+    options: &times::lint::Options,
+) -> Vec<Diagnostic> {
+    match read_month(path, convert_options) {
+        Ok(month) => warnings(&month.days, catalog, options)
+            .iter()
+            .map(Diagnostic::from)
+            .collect(),
+        Err(e) => e.diagnostics(),
+    }
+}
+
+fn json(files: &[(PathBuf, Vec<Diagnostic>)]) -> Value {
+    let diagnostics = files
+        .iter()
+        .flat_map(|(path, diagnostics)| {
+            diagnostics.iter().map(move |d| {
+                json!({
+                    "file": path,
+                    "line": d.line,
+                    "severity": d.severity.as_str(),
+                    "code": d.code,
+                    "message": d.message,
+                })
+            })
+        })
+        .collect();
+    Value::Array(diagnostics)
+}
+
+/// Code review tools resolve locations relative to the repository.
+fn relative(path: &Path) -> &Path {
+    std::env::current_dir()
+        .ok()
+        .and_then(|cd| path.strip_prefix(cd).ok())
+        .unwrap_or(path)
+}
+
+fn sarif(files: &[(PathBuf, Vec<Diagnostic>)]) -> Value {
+    let mut rules: Vec<_> = files
+        .iter()
+        .flat_map(|(_, diagnostics)| diagnostics.iter().map(|d| d.code.as_str()))
+        .collect();
+    rules.sort_unstable();
+    rules.dedup();
+    let results: Vec<_> = files
+        .iter()
+        .flat_map(|(path, diagnostics)| {
+            diagnostics.iter().map(move |d| {
+                let mut location = json!({ "artifactLocation": { "uri": relative(path) } });
+                if let Some(line) = d.line {
+                    location["region"] = json!({ "startLine": line });
+                }
+                json!({
+                    "ruleId": d.code,
+                    "level": d.severity.as_str(),
+                    "message": { "text": d.message },
+                    "locations": [{ "physicalLocation": location }],
+                })
+            })
+        })
+        .collect();
+    json!({
+        "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": env!("CARGO_PKG_NAME"),
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "rules": rules.iter().map(|id| json!({ "id": id })).collect::<Vec<_>>(),
+                },
+            },
+            "results": results,
+        }],
+    })
+}
+
+/// Prints the problems of all `paths` in a machine-readable `format`.
+pub fn check_structured(
+    paths: Vec<PathBuf>,
+    format: CheckFormat,
+    convert_options: &times::convert::Options,
+    catalog: Option<&Path>,
+    options: &times::lint::Options,
+) -> Result<(), Error> {
+    let catalog = read_catalog(catalog)?;
+    let files: Vec<_> = paths
+        .into_iter()
+        .map(|path| {
+            let diagnostics = diagnostics(&path, convert_options, catalog.as_ref(), options);
+            (path, diagnostics)
+        })
+        .collect();
+    print_diagnostics(&files, format)
+}
+
+/// Problems of a file with the levels of the lint `options`. Days that are not terminated are
+/// reported by their rule instead of failing the whole file.
+fn lint_diagnostics(
+    path: &Path,
+    convert_options: &times::convert::Options,
+    catalog: Option<&Catalog>,
+    options: &times::lint::Options,
+) -> Vec<Diagnostic> {
+    let month = match fs_err::File::open(path) {
+        Ok(file) => parse_month(BufReader::new(file), month_of(path)),
+        Err(e) => return vec![Diagnostic::io(&e)],
+    };
+    let month = match month {
+        Ok(month) => month,
+        Err(e) => return Diagnostic::from_parse_error(&e),
+    };
+    let mut warnings = unterminated_days(&month.days, options);
+    let mut diagnostics = Vec::new();
+    let mut days = Vec::new();
+    for day in month.days {
+        let line = day.date.line;
+        match Day::convert(day, convert_options) {
+            Ok(day) => days.push(day),
+            Err(times::convert::Error::NotTerminated(_))
+                if warnings.contains(&Warning::UnterminatedDay(line)) => {}
+            Err(e) => diagnostics.push(Diagnostic::from(&e)),
+        }
+    }
+    warnings.extend(self::warnings(&days, catalog, options));
+    diagnostics.extend(warnings.iter().filter_map(|w| {
+        Some(Diagnostic {
+            severity: options.severity(w)?,
+            ..Diagnostic::from(w)
+        })
+    }));
+    diagnostics.sort_by_key(|d| d.line);
+    diagnostics
+}
+
+/// Checks all `paths` with the configured severities of the lint rules.
+pub fn lint(
+    paths: Vec<PathBuf>,
+    format: CheckFormat,
+    convert_options: &times::convert::Options,
+    catalog: Option<&Path>,
+    options: &times::lint::Options,
+) -> Result<(), Error> {
+    let catalog = read_catalog(catalog)?;
+    let files: Vec<_> = paths
+        .into_iter()
+        .map(|path| {
+            let diagnostics = lint_diagnostics(&path, convert_options, catalog.as_ref(), options);
+            (path, diagnostics)
+        })
+        .collect();
+    print_diagnostics(&files, format)
+}
+
+/// Fails if any file has an error.
+fn print_diagnostics(
+    files: &[(PathBuf, Vec<Diagnostic>)],
+    format: CheckFormat,
+) -> Result<(), Error> {
+    match format {
+        CheckFormat::Text => {
+            for (path, diagnostics) in files {
+                for d in diagnostics {
+                    let line = d.line.map(|l| format!(":{l}")).unwrap_or_default();
+                    println!(
+                        "{}{line}: {}[{}]: {}",
+                        path.display(),
+                        d.severity.as_str(),
+                        d.code,
+                        d.message
+                    );
+                }
+            }
+        }
+        CheckFormat::Json => println!("{:#}", json(files)),
+        CheckFormat::Sarif => println!("{:#}", sarif(files)),
+    }
+    let failed = files
+        .iter()
+        .filter(|(_, d)| d.iter().any(|d| d.severity == Severity::Error))
+        .count();
+    if failed == 0 {
+        Ok(())
+    } else {
+        Err(Error::Failed(failed, files.len()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_structured_invalid() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("2024-04.tsh");
+        fs_err::write(&path, "* Mo. 15.04.\n10:17 AA\n12:00\n").unwrap();
+        let convert_options = times::convert::Options::default();
+        let options = times::lint::Options::default();
+        let found = diagnostics(&path, &convert_options, None, &options);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].line, Some(2));
+        assert_eq!(found[0].severity, Severity::Error);
+        for format in [CheckFormat::Json, CheckFormat::Sarif] {
+            let result =
+                check_structured(vec![path.clone()], format, &convert_options, None, &options);
+            assert!(matches!(result, Err(Error::Failed(1, 1))));
+        }
+    }
+}
@@ -0,0 +1,88 @@
+use std::path::Path;
+
+use times::config::Config;
+use times::Date;
+
+use crate::shell::shell;
+
+/// The events with a command in `[hooks]` of the config.
+#[derive(Debug, Copy, Clone)]
+pub enum Hook {
+    Add,
+    Change,
+    CheckFail,
+}
+
+impl Hook {
+    fn name(self) -> &'static str {
+        match self {
+            Hook::Add => "on_add",
+            Hook::Change => "on_change",
+            Hook::CheckFail => "on_check_fail",
+        }
+    }
+
+    fn command(self, config: &Config) -> Option<&str> {
+        let hooks = &config.hooks;
+        match self {
+            Hook::Add => hooks.on_add.as_deref(),
+            Hook::Change => hooks.on_change.as_deref(),
+            Hook::CheckFail => hooks.on_check_fail.as_deref(),
+        }
+    }
+}
+
+/// Runs the command of `hook` with `path` and `date` as arguments. Failures are only printed,
+/// the event they follow has already happened.
+pub fn run(config: &Config, hook: Hook, path: &Path, date: Option<Date>) {
+    let Some(command) = hook.command(config) else {
+        return;
+    };
+    let mut shell = shell(command);
+    shell.arg(path);
+    if let Some(date) = date {
+        shell.arg(date.into_inner().to_string());
+    }
+    match shell.status() {
+        Ok(status) if status.success() => {}
+        Ok(status) => eprintln!("Hook {} failed: {status}", hook.name()),
+        Err(e) => eprintln!("Failed to run hook {}: {e}", hook.name()),
+    }
+}
+
+/// Runs the `on_change` hook after `path` was written, `date` is the changed day if it was the
+/// only one.
+pub fn changed(config: &Config, path: &Path, date: Option<Date>) {
+    run(config, Hook::Change, path, date);
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use chrono::NaiveDate;
+    use times::config::Hooks;
+
+    use super::*;
+
+    #[test]
+    fn test_run() {
+        let dir = tempfile::tempdir().unwrap();
+        let output = dir.path().join("output");
+        let config = Config {
+            hooks: Hooks {
+                on_add: Some(format!("printf '%s|' >> '{}'", output.display())),
+                on_check_fail: Some("exit 1".to_owned()),
+                ..Hooks::default()
+            },
+            ..Config::default()
+        };
+        let date = Date::new(NaiveDate::from_ymd_opt(2024, 4, 15).unwrap());
+        run(&config, Hook::Add, Path::new("2024-04.tsh"), Some(date));
+        run(&config, Hook::Add, Path::new("2024-05.tsh"), None);
+        changed(&config, Path::new("2024-04.tsh"), Some(date));
+        run(&config, Hook::CheckFail, Path::new("2024-04.tsh"), None);
+        assert_eq!(
+            std::fs::read_to_string(output).unwrap(),
+            "2024-04.tsh|2024-04-15|2024-05.tsh|"
+        );
+    }
+}
@@ -0,0 +1,138 @@
+use std::collections::HashMap;
+
+use base64::Engine;
+use chrono::{Local, TimeZone};
+use serde_json::{json, Value};
+use times::config::{Config, Jira};
+use times::jira::Worklog;
+
+use crate::{convert_options, load, timesheet_path, Args, Error};
+
+fn request_error(error: impl Into<Box<dyn std::error::Error + Send + Sync>>) -> Error {
+    Error::Jira(error.into())
+}
+
+/// Version 2 of the REST API, whose worklog comments are plain text.
+struct Client {
+    agent: ureq::Agent,
+    url: String,
+    authorization: String,
+    /// The comments of the worklogs of every issue requested so far.
+    comments: HashMap<String, Vec<String>>,
+}
+
+impl Client {
+    fn new(config: &Jira, token: &str) -> Result<Self, Error> {
+        let url = config.url.as_deref().ok_or(Error::Missing("jira.url"))?;
+        let user = config.user.as_deref().ok_or(Error::Missing("jira.user"))?;
+        let credentials =
+            base64::engine::general_purpose::STANDARD.encode(format!("{user}:{token}"));
+        Ok(Self {
+            agent: ureq::agent(),
+            url: url.trim_end_matches('/').to_owned(),
+            authorization: format!("Basic {credentials}"),
+            comments: HashMap::new(),
+        })
+    }
+
+    fn worklog_url(&self, issue: &str) -> String {
+        format!("{}/rest/api/2/issue/{issue}/worklog", self.url)
+    }
+
+    fn comments(&mut self, issue: &str) -> Result<&mut Vec<String>, Error> {
+        if !self.comments.contains_key(issue) {
+            let response: Value = self
+                .agent
+                .get(&self.worklog_url(issue))
+                .set("Authorization", &self.authorization)
+                .call()
+                .map_err(request_error)?
+                .into_json()
+                .map_err(request_error)?;
+            let comments = response["worklogs"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(|w| w["comment"].as_str())
+                .map(str::to_owned)
+                .collect();
+            self.comments.insert(issue.to_owned(), comments);
+        }
+        Ok(self.comments.get_mut(issue).unwrap())
+    }
+
+    fn is_pushed(&mut self, worklog: &Worklog) -> Result<bool, Error> {
+        let marker = worklog.marker();
+        Ok(self
+            .comments(&worklog.issue)?
+            .iter()
+            .any(|c| c.contains(&marker)))
+    }
+
+    fn push(&mut self, worklog: &Worklog) -> Result<(), Error> {
+        let started = worklog.started();
+        // A start in the gap of a daylight saving change is taken as UTC.
+        let started = Local
+            .from_local_datetime(&started)
+            .earliest()
+            .unwrap_or_else(|| Local.from_utc_datetime(&started));
+        let text = worklog.text();
+        self.agent
+            .post(&self.worklog_url(&worklog.issue))
+            .set("Authorization", &self.authorization)
+            .send_json(json!({
+                "started": started.format("%Y-%m-%dT%H:%M:%S%.3f%z").to_string(),
+                "timeSpentSeconds": worklog.duration.into_inner() * 60,
+                "comment": text,
+            }))
+            .map_err(request_error)?;
+        self.comments(&worklog.issue)?.push(text);
+        Ok(())
+    }
+}
+
+/// Pushes the entries of the file of `args` as worklogs, skipping those with a marker in a
+/// worklog of their issue. A dry run without a token lists every entry without asking Jira.
+pub fn sync(config: &Config, args: &Args, token: Option<&str>, dry_run: bool) -> Result<(), Error> {
+    let path = timesheet_path(args, config)?;
+    let month = load(&path, &convert_options(config, args.granularity))?;
+    let mut client = match token {
+        Some(token) => Some(Client::new(&config.jira, token)?),
+        None if dry_run => None,
+        None => return Err(Error::Missing("--token or JIRA_API_TOKEN")),
+    };
+    let (mut pushed, mut skipped) = (0, 0);
+    for worklog in times::jira::worklogs(&month, &config.jira.issues) {
+        let worklog = match worklog {
+            Ok(worklog) => worklog,
+            Err(unmapped) => {
+                eprintln!("Warning: {unmapped}");
+                continue;
+            }
+        };
+        let summary = format!(
+            "{} {} {} {}",
+            worklog.started().format("%Y-%m-%d %H:%M"),
+            worklog.duration.into_duration(),
+            worklog.issue,
+            worklog.comment.as_deref().unwrap_or_default()
+        );
+        if let Some(client) = &mut client {
+            if client.is_pushed(&worklog)? {
+                skipped += 1;
+                continue;
+            }
+            if !dry_run {
+                client.push(&worklog)?;
+                println!("Pushed {}", summary.trim_end());
+                pushed += 1;
+                continue;
+            }
+        }
+        println!("Would push {}", summary.trim_end());
+        pushed += 1;
+    }
+    let verb = if dry_run { "Would push" } else { "Pushed" };
+    println!("{verb} {pushed} worklogs, skipped {skipped} pushed before");
+    Ok(())
+}
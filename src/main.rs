@@ -4,25 +4,57 @@
 #![allow(clippy::missing_errors_doc)]
 #![allow(clippy::missing_panics_doc)]
 
-use std::borrow::Cow;
-use std::fs::OpenOptions;
-use std::io::{stdout, BufReader, BufWriter, Write};
+mod calendar;
+mod check;
+mod hooks;
+#[cfg(feature = "jira")]
+mod jira;
+#[cfg(feature = "plugins")]
+mod plugin;
+mod remind;
+#[cfg(feature = "serve")]
+mod serve;
+mod shell;
+mod terminal;
+
+use std::collections::HashSet;
+use std::io::{stdin, stdout, BufReader, Write};
 use std::path::{Path, PathBuf};
 use std::process::ExitCode;
+use std::time::Duration;
 
-use chrono::Datelike;
-use clap::{Parser, ValueEnum};
+use chrono::{Datelike, NaiveDate, Timelike};
+use clap::{Parser, Subcommand, ValueEnum};
 use fs_err::File;
+use regex::RegexBuilder;
 use thiserror::Error;
 
+use crate::check::{check_all, check_structured, print_warnings, watch, CheckFormat};
+use crate::hooks::Hook;
+
+use times::ast::Document;
+use times::config::Config;
+use times::data::Data;
+use times::edit::Fix;
+use times::error::Kind;
+use times::export::Locale;
 use times::generate::Template;
-use times::parse::{from_stem, parse};
+use times::holidays::Region;
+use times::i18n::{Language, Message};
+use times::import::Importer;
+use times::lock::Lock;
+use times::parse::{from_stem, parse, parse_month};
+use times::{Date, Granularity, Minutes, Time, Topic};
 
 #[derive(Parser)]
 struct Args {
-    /// Input path timesheet
+    /// Input path timesheet, report and xlsx exports accept several and globs like
+    /// timesheets/2024-0[1-3].tsh
     #[clap(short, long)]
-    file: Option<PathBuf>,
+    file: Vec<PathBuf>,
+    /// Minutes every time has to be a multiple of, defaults to the configured granularity or 3
+    #[clap(long)]
+    granularity: Option<Granularity>,
 }
 
 #[derive(ValueEnum, Copy, Clone)]
@@ -30,6 +62,7 @@ pub enum TemplateName {
     Empty,
     TechDay,
     Holiday,
+    PublicHoliday,
     Normal,
     Ill,
 }
@@ -40,28 +73,363 @@ impl From<TemplateName> for Template {
             TemplateName::Empty => Template::Empty,
             TemplateName::TechDay => Template::TechDay,
             TemplateName::Holiday => Template::Holiday,
+            TemplateName::PublicHoliday => Template::PublicHoliday,
             TemplateName::Normal => Template::Normal,
             TemplateName::Ill => Template::Ill,
         }
     }
 }
 
+#[derive(ValueEnum, Copy, Clone, Default)]
+pub enum LocaleName {
+    #[default]
+    En,
+    De,
+}
+
+impl From<LocaleName> for Locale {
+    fn from(value: LocaleName) -> Self {
+        match value {
+            LocaleName::En => Locale::En,
+            LocaleName::De => Locale::De,
+        }
+    }
+}
+
+#[derive(ValueEnum, Copy, Clone, Default)]
+pub enum ExportFormat {
+    #[default]
+    Csv,
+    /// A spreadsheet with a sheet per file, written to stdout
+    #[cfg(feature = "xlsx")]
+    Xlsx,
+}
+
+#[derive(ValueEnum, Copy, Clone)]
+pub enum ImportFormat {
+    Clockify,
+    Harvest,
+    /// Meetings of an iCalendar file, inserted into the days of their files, which are created
+    /// if they are missing
+    Ics,
+}
+
+impl ImportFormat {
+    /// `None` for calendars, which are imported by [`calendar::import`].
+    fn importer(self) -> Option<Box<dyn Importer>> {
+        match self {
+            ImportFormat::Clockify => Some(Box::new(times::import::Clockify)),
+            ImportFormat::Harvest => Some(Box::new(times::import::Harvest)),
+            ImportFormat::Ics => None,
+        }
+    }
+}
+
+fn parse_date_arg(s: &str) -> Result<NaiveDate, String> {
+    if s == "today" {
+        return Ok(chrono::offset::Local::now().date_naive());
+    }
+    NaiveDate::parse_from_str(s, "%Y-%m-%d")
+        .map_err(|_| "expected a date like 2024-04-12 or today".to_owned())
+}
+
+#[derive(Copy, Clone)]
+enum WeekArg {
+    Current,
+    Number(u32),
+}
+
+fn parse_week_arg(s: &str) -> Result<WeekArg, String> {
+    if s == "current" {
+        return Ok(WeekArg::Current);
+    }
+    s.parse()
+        .map(WeekArg::Number)
+        .map_err(|_| "expected a week number or current".to_owned())
+}
+
+#[derive(Subcommand)]
+enum FixCommand {
+    /// Move all times of a day by a number of minutes
+    Shift {
+        #[clap(allow_negative_numbers = true)]
+        minutes: isize,
+        /// Day to shift, e.g. 2024-04-12 or today, defaults to the last day in the file
+        #[clap(long, value_parser = parse_date_arg)]
+        date: Option<NaiveDate>,
+        #[clap(flatten)]
+        args: Args,
+    },
+}
+
+#[cfg(feature = "jira")]
+#[derive(Subcommand)]
+enum SyncTarget {
+    /// Log the entries as work on the Jira issue of the first ticket key in their comment or of
+    /// their identifier in [jira.issues], entries pushed before are skipped
+    Jira {
+        /// API token, or the password of a Jira Server account
+        #[clap(long, env = "JIRA_API_TOKEN", hide_env_values = true)]
+        token: Option<String>,
+        /// Only list the worklogs that would be pushed, without a token every entry is listed
+        #[clap(long)]
+        dry_run: bool,
+        #[clap(flatten)]
+        args: Args,
+    },
+}
+
 #[derive(Parser)]
-enum Cli {
+struct Cli {
+    /// Directory with the timesheet files, defaults to the configured directory or ./timesheets
+    #[clap(short = 'C', long, global = true, env = "TIMESHEET_DIR")]
+    directory: Option<PathBuf>,
+    /// Append a debug log of file access, parsing and converting to this file, RUST_LOG
+    /// overrides the filter
+    #[allow(clippy::doc_markdown)]
+    #[cfg(feature = "tracing")]
+    #[clap(long, global = true, value_name = "PATH")]
+    log_file: Option<PathBuf>,
+    #[clap(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
     Check {
         #[clap(flatten)]
         args: Args,
+        /// Apply suggested fixes to the file
+        #[clap(long)]
+        fix: bool,
+        /// Check every file in the timesheets directory, or in the directory given with --file
+        #[clap(long, conflicts_with = "fix")]
+        all: bool,
+        /// Check again whenever the file changes
+        #[clap(long, conflicts_with_all = ["fix", "all"])]
+        watch: bool,
+        #[clap(long, value_enum, default_value_t, conflicts_with = "watch")]
+        format: CheckFormat,
+        /// File with the known identifiers, one per line
+        #[clap(long)]
+        catalog: Option<PathBuf>,
+        /// Warn about days with more work time than this, e.g. 9h30m, defaults to the configured
+        /// time or 10h
+        #[clap(long)]
+        max_daily_work: Option<Minutes>,
+    },
+    /// Report problems with the levels of the lint rules in the config
+    Lint {
+        #[clap(flatten)]
+        args: Args,
+        /// Lint every file in the timesheets directory, or in the directory given with --file
+        #[clap(long)]
+        all: bool,
+        #[clap(long, value_enum, default_value_t)]
+        format: CheckFormat,
+        /// File with the known identifiers, one per line
+        #[clap(long)]
+        catalog: Option<PathBuf>,
     },
     Report {
         #[clap(flatten)]
         args: Args,
+        /// Show the duration and share of the day of each entry
+        #[clap(long)]
+        durations: bool,
+        /// Show the month-to-date balance after each day
+        #[clap(long)]
+        balance: bool,
+        /// Only show the entries of this identifier grouped by comment
+        #[clap(long, value_name = "IDENTIFIER")]
+        detail: Option<String>,
+        /// Only show one ISO week of the year of the file, including days of adjacent months
+        #[clap(long, value_name = "N|current", value_parser = parse_week_arg, conflicts_with = "detail")]
+        week: Option<WeekArg>,
+        /// Print the report directly even if it does not fit on the terminal
+        #[clap(long)]
+        no_pager: bool,
     },
     Output {
         #[clap(flatten)]
         args: Args,
     },
+    Fix {
+        #[clap(subcommand)]
+        command: FixCommand,
+    },
+    /// Start working on something now
+    Start {
+        identifier: String,
+        comment: Vec<String>,
+        #[clap(flatten)]
+        args: Args,
+    },
+    /// Stop working now
+    Stop {
+        #[clap(flatten)]
+        args: Args,
+    },
+    /// Remove the day appended last by add
+    Undo {
+        /// Do not ask for confirmation
+        #[clap(short, long)]
+        yes: bool,
+        #[clap(flatten)]
+        args: Args,
+    },
+    /// List the snapshots taken before the file was changed, or restore one of them
+    Restore {
+        /// Number of the snapshot in the list, 1 is the newest, or its file name
+        snapshot: Option<String>,
+        /// Do not ask for confirmation
+        #[clap(short, long)]
+        yes: bool,
+        #[clap(flatten)]
+        args: Args,
+    },
+    /// Change the last entry of the file
+    Amend {
+        /// New start time, e.g. 9:03
+        #[clap(long, value_parser = Time::parse_flexible)]
+        time: Option<Time>,
+        /// New identifier
+        #[clap(long)]
+        identifier: Option<String>,
+        /// New comment
+        #[clap(long, conflicts_with = "no_comment")]
+        comment: Option<String>,
+        /// Remove the comment
+        #[clap(long)]
+        no_comment: bool,
+        #[clap(flatten)]
+        args: Args,
+    },
+    /// Print and show a desktop notification if nothing was logged today by the configured time
+    /// or the last entry has been running for too long, e.g. from a timer
+    Remind {
+        /// Keep running and check again after this long, e.g. 15m
+        #[clap(long, value_name = "DURATION")]
+        every: Option<Minutes>,
+    },
+    /// Create the timesheets directory with a file for the current month
+    Init {
+        /// Defaults to ./timesheets
+        dir: Option<PathBuf>,
+    },
+    /// Show totals across all files in the timesheets directory
+    Stats {
+        /// Only include the months of this year
+        #[clap(long)]
+        year: Option<i32>,
+        #[clap(long)]
+        granularity: Option<Granularity>,
+    },
+    /// List every identifier of the files in the timesheets directory with its first and last use
+    Projects {
+        /// Only include the months of this year
+        #[clap(long)]
+        year: Option<i32>,
+        /// Print a JSON array instead of a table
+        #[clap(long)]
+        json: bool,
+        #[clap(long)]
+        granularity: Option<Granularity>,
+    },
+    /// Print the identifiers starting with PREFIX in the timesheets directory, most used first,
+    /// e.g. for shell completion
+    Identifiers {
+        prefix: Option<String>,
+        /// Print the comments used with this identifier instead
+        #[clap(long, value_name = "IDENTIFIER")]
+        comments: Option<String>,
+    },
+    /// Serve read-only JSON of the timesheets directory at /months, /months/<YYYY-MM>,
+    /// /report/<YYYY-MM> and /stats
+    #[cfg(feature = "serve")]
+    Serve {
+        /// Address to listen on
+        #[clap(long, default_value = "127.0.0.1:8080")]
+        addr: String,
+    },
+    /// Push the entries of a file to another system
+    #[cfg(feature = "jira")]
+    Sync {
+        #[clap(subcommand)]
+        target: SyncTarget,
+    },
+    /// Add the public holidays of a region to the files of their months
+    Holidays {
+        /// German state, e.g. BY or NW
+        #[clap(long)]
+        region: Region,
+        /// Defaults to the current year
+        #[clap(long, value_parser = clap::value_parser!(i32).range(1583..=9999))]
+        year: Option<i32>,
+        /// Only show the holidays that would be added without changing any file
+        #[clap(long)]
+        dry_run: bool,
+    },
+    /// Add the days of an export of another time tracker to the files of their months, projects
+    /// are mapped to identifiers by [import.identifiers] of the config
+    Import {
+        #[clap(long, value_enum)]
+        format: ImportFormat,
+        /// The exported CSV or iCalendar file, with the caldav feature also an http(s) or webcal
+        /// URL of a calendar
+        input: PathBuf,
+        /// Only import activities on or after this day, e.g. 2024-04-01
+        #[clap(long, value_parser = parse_date_arg)]
+        since: Option<NaiveDate>,
+        /// Only show the days that would be added without changing any file
+        #[clap(long)]
+        dry_run: bool,
+        #[clap(long)]
+        granularity: Option<Granularity>,
+    },
+    /// Search identifiers and comments of all files in the timesheets directory
+    Grep {
+        /// Regular expression to search for
+        pattern: String,
+        /// Match case-insensitively
+        #[clap(short, long)]
+        ignore_case: bool,
+    },
+    /// Show the changed entries between two files of the same month
+    Diff {
+        old: PathBuf,
+        new: PathBuf,
+        #[clap(long)]
+        granularity: Option<Granularity>,
+    },
+    Fmt {
+        #[clap(flatten)]
+        args: Args,
+        /// Join consecutive entries with the same identifier and comment
+        #[clap(long)]
+        merge: bool,
+        /// Only check that the file is formatted, without writing it
+        #[clap(long)]
+        check: bool,
+    },
+    Export {
+        #[clap(flatten)]
+        args: Args,
+        #[clap(long, value_enum, default_value_t)]
+        format: ExportFormat,
+        /// Locale used for numbers and dates, defaults to the configured locale of the format or en
+        #[clap(long, value_enum)]
+        locale: Option<LocaleName>,
+    },
     Add {
         template: TemplateName,
+        /// Day to add, e.g. 2024-04-12 or today, defaults to the next weekday after the last day.
+        /// Selects the file of its month unless --file is given
+        #[clap(long, value_parser = parse_date_arg)]
+        date: Option<NaiveDate>,
+        /// Only show and validate the new day without changing the file
+        #[clap(long)]
+        dry_run: bool,
         #[clap(flatten)]
         args: Args,
         template_args: Vec<String>,
@@ -78,87 +446,1418 @@ enum Error {
     Validate(#[from] times::convert::Error),
     #[error("Error running template: {0}")]
     Template(#[from] times::generate::Error),
+    #[error("Failed to amend entry: {0}")]
+    Amend(#[from] times::edit::AmendError),
+    #[error("Failed to shift times: {0}")]
+    Shift(#[from] times::transform::ShiftError),
+    #[error("{0}")]
+    Data(#[from] times::data::Error),
+    #[error("{0} of {1} files failed the check")]
+    Failed(usize, usize),
+    #[error("{} is not the file of the current month", .0.display())]
+    NotCurrentMonth(PathBuf),
+    #[error("Failed to watch file: {0}")]
+    Watch(#[from] notify::Error),
+    #[error("{0} is not in the month of {path}", path = .1.display())]
+    OtherMonth(NaiveDate, PathBuf),
+    #[error("Failed to add day: {0}")]
+    Insert(#[from] times::edit::InsertError),
+    #[error("{0}")]
+    Journal(#[from] times::journal::Error),
+    #[error("{0}")]
+    Backup(#[from] times::backup::Error),
+    #[error("{0}")]
+    Lock(#[from] times::lock::Error),
+    #[error("There is no week {0} in {1}")]
+    InvalidWeek(u32, i32),
+    #[error("Invalid pattern: {0}")]
+    Pattern(#[from] regex::Error),
+    #[error("{0}")]
+    Config(#[from] times::config::Error),
+    #[error("No file matches {}", .0.display())]
+    NoMatch(PathBuf),
+    #[error("Only report and xlsx exports accept multiple files")]
+    MultipleFiles,
+    #[error("{} is not formatted", .0.display())]
+    Unformatted(PathBuf),
+    #[error("Failed to import {path}: {1}", path = .0.display())]
+    Import(PathBuf, times::import::Error),
+    #[error("Failed to import {path}: {1}", path = .0.display())]
+    Calendar(PathBuf, times::calendar::Error),
+    #[cfg(feature = "caldav")]
+    #[error("Failed to fetch calendar: {0}")]
+    Fetch(Box<ureq::Error>),
+    #[error("{0}")]
+    Times(#[from] times::Error),
+    #[cfg(feature = "serve")]
+    #[error("Failed to start server: {0}")]
+    Serve(Box<dyn std::error::Error + Send + Sync>),
+    #[cfg(feature = "xlsx")]
+    #[error("Failed to write spreadsheet: {0}")]
+    Xlsx(Box<dyn std::error::Error + Send + Sync>),
+    #[cfg(feature = "xlsx")]
+    #[error("Refusing to write a spreadsheet to a terminal, redirect stdout to a file")]
+    Terminal,
+    #[cfg(feature = "jira")]
+    #[error("Jira request failed: {0}")]
+    Jira(Box<dyn std::error::Error + Send + Sync>),
+    #[cfg(feature = "jira")]
+    #[error("Missing {0}")]
+    Missing(&'static str),
+    #[cfg(feature = "plugins")]
+    #[error("Failed to load lint plugin {path}: {1}", path = .0.display())]
+    Plugin(PathBuf, String),
+    #[cfg(not(feature = "plugins"))]
+    #[error("Lint plugins are configured, but this build does not have the plugins feature")]
+    NoPlugins,
 }
 
-fn run(cli: &Cli) -> Result<(), Error> {
-    let path = match cli {
-        Cli::Check { args, .. }
-        | Cli::Report { args, .. }
-        | Cli::Output { args, .. }
-        | Cli::Add { args, .. } => args.file.as_deref(),
-    };
-    let path = path.map_or_else(
-        || {
-            let mut cd = std::env::current_dir().unwrap();
-            cd.push("timesheets");
-            let now = chrono::offset::Local::now();
-            let year = now.year();
-            let month = now.month();
-            cd.push(format!("{year}-{month:0>2}.tsh"));
-            Cow::Owned(cd)
-        },
-        Cow::Borrowed,
+/// Exit codes for the failure categories, any other failure exits with 1.
+mod exit_code {
+    /// A file could not be read or written.
+    pub const IO: u8 = 2;
+    /// A file is not a syntactically valid timesheet.
+    pub const PARSE: u8 = 3;
+    /// The times of a file are inconsistent, e.g. overlapping entries.
+    pub const VALIDATE: u8 = 4;
+    /// A template could not be executed with the given arguments.
+    pub const TEMPLATE: u8 = 5;
+}
+
+impl Error {
+    fn exit_code(&self) -> ExitCode {
+        let code = match self {
+            Error::InputFile(_)
+            | Error::Parse(times::parse::Error::Io(_))
+            | Error::Data(times::data::Error::Io(..))
+            | Error::Config(times::config::Error::Io(..))
+            | Error::Journal(times::journal::Error::Io(..))
+            | Error::Backup(times::backup::Error::Io(..))
+            | Error::Lock(times::lock::Error::Io(..))
+            | Error::Watch(_) => exit_code::IO,
+            #[cfg(feature = "serve")]
+            Error::Serve(_) => exit_code::IO,
+            #[cfg(feature = "jira")]
+            Error::Jira(_) => exit_code::IO,
+            #[cfg(feature = "caldav")]
+            Error::Fetch(_) => exit_code::IO,
+            #[cfg(feature = "jira")]
+            Error::Missing(_) => return ExitCode::FAILURE,
+            #[cfg(feature = "plugins")]
+            Error::Plugin(..) => return ExitCode::FAILURE,
+            #[cfg(not(feature = "plugins"))]
+            Error::NoPlugins => return ExitCode::FAILURE,
+            #[cfg(feature = "xlsx")]
+            Error::Xlsx(_) | Error::Terminal => return ExitCode::FAILURE,
+            Error::Parse(_) | Error::Data(times::data::Error::Parse(..)) => exit_code::PARSE,
+            Error::Validate(_) | Error::Data(times::data::Error::Convert(..)) => {
+                exit_code::VALIDATE
+            }
+            Error::Template(_) => exit_code::TEMPLATE,
+            Error::Times(e) => match &*e.kind {
+                Kind::Io(_) | Kind::Parse(times::parse::Error::Io(_)) => exit_code::IO,
+                Kind::Parse(_) => exit_code::PARSE,
+                Kind::Convert(_) => exit_code::VALIDATE,
+                Kind::Template(_) => exit_code::TEMPLATE,
+                _ => return ExitCode::FAILURE,
+            },
+            Error::Amend(_)
+            | Error::Shift(_)
+            | Error::Failed(..)
+            | Error::NotCurrentMonth(_)
+            | Error::OtherMonth(..)
+            | Error::Insert(_)
+            | Error::Journal(_)
+            | Error::Backup(_)
+            | Error::Lock(_)
+            | Error::InvalidWeek(..)
+            | Error::Pattern(_)
+            | Error::Unformatted(_)
+            | Error::NoMatch(_)
+            | Error::MultipleFiles
+            | Error::Import(..)
+            | Error::Calendar(..)
+            | Error::Config(times::config::Error::Toml(..)) => {
+                return ExitCode::FAILURE;
+            }
+        };
+        ExitCode::from(code)
+    }
+
+    /// Prints this error, errors in files in `language`.
+    fn print(self, language: Language) {
+        let error = match self {
+            Error::Times(e) => e,
+            Error::Data(e) => e.into(),
+            e => return eprintln!("{e}"),
+        };
+        eprintln!("{}", language.tr(Message::Error(&error)));
+    }
+}
+
+fn timesheets_dir(config: &Config) -> PathBuf {
+    let dir = config
+        .timesheets_dir
+        .as_deref()
+        .unwrap_or(Path::new("timesheets"));
+    std::env::current_dir().unwrap().join(dir)
+}
+
+fn month_file(dir: &Path, date: NaiveDate) -> PathBuf {
+    dir.join(format!("{}-{:0>2}.tsh", date.year(), date.month()))
+}
+
+fn current_month_file(dir: &Path) -> PathBuf {
+    month_file(dir, chrono::offset::Local::now().date_naive())
+}
+
+/// Expands the glob patterns in `path`, other paths are returned as they are.
+fn expand_glob(path: &Path) -> Result<Vec<PathBuf>, Error> {
+    let Some(pattern) = path.to_str().filter(|p| p.contains(['*', '?', '['])) else {
+        return Ok(vec![path.to_owned()]);
+    };
+    let Ok(paths) = glob::glob(pattern) else {
+        return Ok(vec![path.to_owned()]);
+    };
+    let paths: Vec<_> = paths.filter_map(Result::ok).collect();
+    if paths.is_empty() {
+        return Err(Error::NoMatch(path.to_owned()));
+    }
+    Ok(paths)
+}
+
+impl Args {
+    /// The files given with --file, with globs expanded and each file only once.
+    fn files(&self) -> Result<Vec<PathBuf>, Error> {
+        let mut files = Vec::new();
+        for file in &self.file {
+            for file in expand_glob(file)? {
+                if !files.contains(&file) {
+                    files.push(file);
+                }
+            }
+        }
+        Ok(files)
+    }
+}
+
+fn timesheet_path(args: &Args, config: &Config) -> Result<PathBuf, Error> {
+    let mut files = args.files()?;
+    match files.len() {
+        0 => Ok(current_month_file(&timesheets_dir(config))),
+        1 => Ok(files.remove(0)),
+        _ => Err(Error::MultipleFiles),
+    }
+}
+
+fn punch(args: &Args, config: &Config, topic: Topic) -> Result<(), Error> {
+    let now = chrono::offset::Local::now();
+    let date = Date::new(now.date_naive());
+    let path = timesheet_path(args, config)?;
+    if month_of(&path) != Date::new(now.date_naive().with_day(1).unwrap()) {
+        return Err(Error::NotCurrentMonth(path));
+    }
+    let time = Time::new(
+        u8::try_from(now.hour()).unwrap(),
+        u8::try_from(now.minute()).unwrap(),
+    )
+    .unwrap()
+    .rounded(
+        convert_options(config, args.granularity)
+            .granularity
+            .minutes(),
     );
-    let path = path.as_ref();
+    let lock = lock(&path)?;
+    if path.exists() {
+        snapshot(config, &path)?;
+    } else {
+        fs_err::write(&path, "").map_err(Error::InputFile)?;
+    }
+    let entry = times::Entry { time, topic };
+    times::edit::insert_entry(&path, date, &entry).map_err(Error::InputFile)?;
+    println!("+ {entry}");
+    drop(lock);
+    hooks::changed(config, &path, Some(date));
+    Ok(())
+}
+
+fn init(dir: &Path) -> Result<(), Error> {
+    fs_err::create_dir_all(dir).map_err(Error::InputFile)?;
+    // Next to the directory, so the config is found when running in the directory's parent.
+    let config = dir
+        .parent()
+        .unwrap_or(Path::new(""))
+        .join(times::config::LOCAL_FILE);
+    if !config.exists() {
+        fs_err::write(&config, times::config::TEMPLATE).map_err(Error::InputFile)?;
+        println!("Created {}", config.display());
+    }
+    let path = current_month_file(dir);
+    if path.exists() {
+        println!("{} already exists", path.display());
+        return Ok(());
+    }
+    fs_err::write(&path, "#! hours_per_week: 40\n").map_err(Error::InputFile)?;
+    println!("Created {}", path.display());
+    Ok(())
+}
+
+impl Command {
+    fn args(&self) -> &Args {
+        match self {
+            Command::Check { args, .. }
+            | Command::Lint { args, .. }
+            | Command::Report { args, .. }
+            | Command::Output { args, .. }
+            | Command::Fmt { args, .. }
+            | Command::Fix {
+                command: FixCommand::Shift { args, .. },
+            }
+            | Command::Export { args, .. }
+            | Command::Add { args, .. }
+            | Command::Start { args, .. }
+            | Command::Stop { args }
+            | Command::Undo { args, .. }
+            | Command::Restore { args, .. }
+            | Command::Amend { args, .. } => args,
+            Command::Diff { .. }
+            | Command::Stats { .. }
+            | Command::Projects { .. }
+            | Command::Identifiers { .. }
+            | Command::Grep { .. }
+            | Command::Holidays { .. }
+            | Command::Import { .. }
+            | Command::Remind { .. }
+            | Command::Init { .. } => {
+                unreachable!("no single input file")
+            }
+            #[cfg(feature = "serve")]
+            Command::Serve { .. } => unreachable!("no single input file"),
+            #[cfg(feature = "jira")]
+            Command::Sync {
+                target: SyncTarget::Jira { args, .. },
+            } => args,
+        }
+    }
+}
+
+fn month_of(path: &Path) -> Date {
     let stem = path
         .file_stem()
         .expect("need a file with a name")
         .to_str()
         .unwrap();
-    let month = from_stem(stem).unwrap_or_else(|| {
+    from_stem(stem).unwrap_or_else(|| {
         panic!("failed to parse month from input file stem {stem:?}, expected format YYYY-MM")
-    });
-    let file = File::open(path).map_err(Error::InputFile)?;
-    let days = parse(&mut BufReader::new(file), month)?;
-    let days = days
-        .into_iter()
-        .map(times::convert::Day::try_from)
+    })
+}
+
+/// Reads and converts the month file at `path`, with every failure as a [`times::Error`].
+fn read_month(
+    path: &Path,
+    options: &times::convert::Options,
+) -> Result<times::convert::Month, times::Error> {
+    let load = || -> Result<_, times::Error> {
+        let file = File::open(path)?;
+        let timesheet = parse_month(&mut BufReader::new(file), month_of(path))?;
+        Ok(times::convert::Month::convert(timesheet, options)?)
+    };
+    load().map_err(|e| e.with_path(path))
+}
+
+fn load(path: &Path, options: &times::convert::Options) -> Result<times::convert::Month, Error> {
+    read_month(path, options).map_err(|e| match *e.kind {
+        Kind::Io(e) => Error::InputFile(e),
+        _ => Error::Times(e),
+    })
+}
+
+fn convert_options(config: &Config, granularity: Option<Granularity>) -> times::convert::Options {
+    let options = config.convert_options();
+    times::convert::Options {
+        granularity: granularity.unwrap_or(options.granularity),
+        ..options
+    }
+}
+
+/// The lint options of the config with the rules of its plugins.
+fn lint_options(
+    config: &Config,
+    max_daily_work: Option<Minutes>,
+) -> Result<times::lint::Options, Error> {
+    let options = config.lint_options();
+    #[cfg(feature = "plugins")]
+    let custom = plugin::load(&config.lint.plugins)?;
+    #[cfg(not(feature = "plugins"))]
+    let custom = if config.lint.plugins.is_empty() {
+        options.custom
+    } else {
+        return Err(Error::NoPlugins);
+    };
+    Ok(times::lint::Options {
+        max_daily_work: max_daily_work.unwrap_or(options.max_daily_work),
+        custom,
+        ..options
+    })
+}
+
+fn diff(
+    old: &Path,
+    new: &Path,
+    config: &Config,
+    granularity: Option<Granularity>,
+) -> Result<(), Error> {
+    let options = convert_options(config, granularity);
+    let (old, new) = (load(old, &options)?, load(new, &options)?);
+    println!("{}", times::diff::Diff::new(&old, &new));
+    Ok(())
+}
+
+/// The files of the timesheets directory, only of `year` if given, using the cache if it is
+/// enabled.
+fn data(config: &Config, year: Option<i32>) -> Result<Data, Error> {
+    let dir = timesheets_dir(config);
+    let mut data = Data::from_dir(&dir)?;
+    if let Some(year) = year {
+        data = data.year(year);
+    }
+    if config.cache == Some(true) {
+        data = data.with_disk_cache(&dir);
+    }
+    Ok(data)
+}
+
+/// Loads all month files, or only the ones of `year`.
+fn load_all(
+    config: &Config,
+    year: Option<i32>,
+    granularity: Option<Granularity>,
+) -> Result<Vec<times::convert::Month>, Error> {
+    Ok(data(config, year)?.load_all(&convert_options(config, granularity))?)
+}
+
+fn identifiers(config: &Config, prefix: Option<&str>, comments: Option<&str>) -> Result<(), Error> {
+    let index = data(config, None)?.index(&convert_options(config, None))?;
+    let prefix = prefix.unwrap_or_default();
+    let candidates = match comments {
+        Some(identifier) => index.comments(identifier, prefix),
+        None => index.identifiers(prefix),
+    };
+    for candidate in candidates {
+        println!("{candidate}");
+    }
+    Ok(())
+}
+
+fn stats(
+    config: &Config,
+    year: Option<i32>,
+    granularity: Option<Granularity>,
+) -> Result<(), Error> {
+    let months = load_all(config, year, granularity)?;
+    print!("{}", times::stats::Stats::new(&months));
+    Ok(())
+}
+
+fn projects(
+    config: &Config,
+    year: Option<i32>,
+    json: bool,
+    granularity: Option<Granularity>,
+) -> Result<(), Error> {
+    let months = load_all(config, year, granularity)?;
+    let projects = times::stats::projects(&months);
+    if json {
+        let projects: Vec<_> = projects
+            .iter()
+            .map(|p| {
+                serde_json::json!({
+                    "identifier": p.identifier.as_str(),
+                    "first": p.first.into_inner().to_string(),
+                    "last": p.last.into_inner().to_string(),
+                    "entries": p.entries,
+                    "minutes": p.total.into_inner(),
+                })
+            })
+            .collect();
+        println!("{}", serde_json::Value::Array(projects));
+        return Ok(());
+    }
+    for p in &projects {
+        println!(
+            "{:<12} {} - {}  {}  ({} entries)",
+            p.identifier,
+            p.first.into_inner(),
+            p.last.into_inner(),
+            p.total.into_duration(),
+            p.entries
+        );
+    }
+    Ok(())
+}
+
+fn report_week(
+    args: &Args,
+    config: &Config,
+    week: WeekArg,
+    options: times::report::Options,
+    no_pager: bool,
+) -> Result<(), Error> {
+    let path = timesheet_path(args, config)?;
+    let (year, week) = match week {
+        WeekArg::Current => {
+            let week = chrono::offset::Local::now().date_naive().iso_week();
+            (week.year(), week.week())
+        }
+        WeekArg::Number(week) => (month_of(&path).year(), week),
+    };
+    let days = Date::days_of_week(year, week).ok_or(Error::InvalidWeek(week, year))?;
+    let dir = path.parent().unwrap_or(Path::new(""));
+    let mut months = Vec::new();
+    for date in [days[0], days[6]] {
+        let file = month_file(dir, date.into_inner());
+        if file.exists() && !months.contains(&file) {
+            months.push(file);
+        }
+    }
+    let files = terminal::link_targets(&months);
+    let months = months
+        .iter()
+        .map(|file| load(file, &convert_options(config, args.granularity)))
         .collect::<Result<Vec<_>, _>>()?;
+    let output = times::report::Week::new(&months, year, week, options)
+        .ok_or(Error::InvalidWeek(week, year))?
+        .with_files(&files);
+    terminal::page(&output.to_string(), no_pager);
+    Ok(())
+}
 
-    match cli {
-        Cli::Check { .. } => {}
-        Cli::Report { .. } => {
-            let output = times::report::Output(&days);
-            write!(&mut stdout(), "{output}").expect("format output");
+fn grep(config: &Config, pattern: &str, ignore_case: bool) -> Result<(), Error> {
+    let regex = RegexBuilder::new(pattern)
+        .case_insensitive(ignore_case)
+        .build()?;
+    let mut stdout = stdout().lock();
+    for file in Data::from_dir(&timesheets_dir(config))?.files {
+        let month = file.parse()?;
+        for found in times::search::search(&month.days, &regex) {
+            writeln!(
+                stdout,
+                "{}:{}: {} {}",
+                file.path.display(),
+                found.entry.line,
+                found.date,
+                found.entry.value
+            )
+            .expect("write output");
         }
-        Cli::Output { .. } => {
-            let output = times::format::Output(&days);
-            write!(&mut stdout(), "{output}").expect("format output");
+    }
+    Ok(())
+}
+
+/// The document of the file at `path`, empty if it does not exist yet.
+fn read_document(path: &Path) -> Result<Document, Error> {
+    let month = month_of(path);
+    if !path.exists() {
+        return Ok(Document::parse("", month));
+    }
+    let file = File::open(path).map_err(Error::InputFile)?;
+    Document::read(file, month).map_err(Error::InputFile)
+}
+
+/// Inserts the rendered days ordered by date into the files of their months, creating missing
+/// files and skipping days that already exist.
+fn insert_days(
+    config: &Config,
+    days: &[(Date, String)],
+    options: &times::convert::Options,
+    dry_run: bool,
+) -> Result<(), Error> {
+    let dir = timesheets_dir(config);
+    for days in days.chunk_by(|(a, _), (b, _)| (a.year(), a.month()) == (b.year(), b.month())) {
+        let path = month_file(&dir, days[0].0.into_inner());
+        let lock = lock(&path)?;
+        let mut document = read_document(&path)?;
+        let mut added = 0;
+        for (date, rendered) in days {
+            match times::edit::insert_day(&mut document, *date, rendered) {
+                Ok(_) => {
+                    println!("{}", indent(rendered));
+                    added += 1;
+                }
+                Err(times::edit::InsertError::DayExists(_)) => {
+                    println!("{date} already exists in {}", path.display());
+                }
+                Err(e) => return Err(e.into()),
+            }
+        }
+        if added > 0 && !dry_run {
+            write_validated(config, &path, &document, options)?;
+            drop(lock);
+            hooks::changed(config, &path, None);
+        }
+    }
+    if dry_run {
+        println!("Dry run, no file was changed");
+    }
+    Ok(())
+}
+
+fn holidays(
+    config: &Config,
+    region: Region,
+    year: Option<i32>,
+    dry_run: bool,
+) -> Result<(), Error> {
+    let year = year.unwrap_or_else(|| chrono::offset::Local::now().year());
+    let mut days = Vec::new();
+    for holiday in times::holidays::holidays(region, year) {
+        if !holiday.is_weekday() {
+            println!("{} {} is on a weekend", holiday.date, holiday.name);
+            continue;
+        }
+        let rendered = Template::PublicHoliday.execute(
+            holiday.date,
+            &[holiday.name.to_owned()],
+            config.template_language(),
+        )?;
+        days.push((holiday.date, rendered));
+    }
+    insert_days(config, &days, &convert_options(config, None), dry_run)
+}
+
+fn import(
+    config: &Config,
+    format: ImportFormat,
+    input: &Path,
+    since: Option<NaiveDate>,
+    dry_run: bool,
+    granularity: Option<Granularity>,
+) -> Result<(), Error> {
+    let Some(importer) = format.importer() else {
+        return calendar::import(config, input, since, dry_run, granularity);
+    };
+    let options = convert_options(config, granularity);
+    let mut file = File::open(input).map_err(Error::InputFile)?;
+    let days = importer
+        .activities(&mut file)
+        .and_then(|mut activities| {
+            activities.retain(|a| since.is_none_or(|s| a.date >= s));
+            times::import::render(&activities, &config.import.identifiers, options.granularity)
+        })
+        .map_err(|e| Error::Import(input.to_owned(), e))?;
+    insert_days(config, &days, &options, dry_run)
+}
+
+/// Writes the spreadsheet of all files of `args` to stdout, unless it is a terminal.
+#[cfg(feature = "xlsx")]
+fn export_xlsx(args: &Args, config: &Config, locale: Option<LocaleName>) -> Result<(), Error> {
+    use std::io::IsTerminal;
+
+    if stdout().is_terminal() {
+        return Err(Error::Terminal);
+    }
+    let options = convert_options(config, args.granularity);
+    let mut paths = args.files()?;
+    if paths.is_empty() {
+        paths.push(current_month_file(&timesheets_dir(config)));
+    }
+    let months = paths
+        .iter()
+        .map(|p| load(p, &options))
+        .collect::<Result<Vec<_>, _>>()?;
+    let locale = locale.map_or(config.xlsx_locale(), Into::into);
+    let bytes = times::export::Xlsx::new(&months, locale)
+        .to_bytes()
+        .map_err(|e| Error::Xlsx(Box::new(e)))?;
+    stdout().write_all(&bytes).map_err(Error::InputFile)
+}
+
+/// Writes the CSV export of `timesheet` to stdout.
+fn export_csv(timesheet: &times::convert::Month, config: &Config, locale: Option<LocaleName>) {
+    let locale = locale.map_or(config.csv_locale(), Into::into);
+    let output = times::export::Csv::new(timesheet, locale);
+    write!(&mut stdout(), "{output}").expect("format output");
+}
+
+/// Runs report for several files, `None` for a single file and all other commands.
+fn report_files(command: &Command, config: &Config) -> Option<Result<(), Error>> {
+    let Command::Report {
+        args,
+        week: None,
+        detail: None,
+        durations,
+        balance,
+        no_pager,
+    } = command
+    else {
+        return None;
+    };
+    match args.files() {
+        Ok(paths) if paths.len() > 1 => Some(report_combined(
+            &paths,
+            &convert_options(config, args.granularity),
+            report_options(config, *durations, *balance),
+            *no_pager,
+        )),
+        Ok(_) => None,
+        Err(e) => Some(Err(e)),
+    }
+}
+
+fn report_combined(
+    paths: &[PathBuf],
+    options: &times::convert::Options,
+    report_options: times::report::Options,
+    no_pager: bool,
+) -> Result<(), Error> {
+    let months = paths
+        .iter()
+        .map(|p| load(p, options))
+        .collect::<Result<Vec<_>, _>>()?;
+    let months = paths
+        .iter()
+        .map(|p| p.display().to_string())
+        .zip(&months)
+        .collect();
+    let files = terminal::link_targets(paths);
+    let output = times::report::Combined::new(months, report_options).with_files(&files);
+    terminal::page(&output.to_string(), no_pager);
+    Ok(())
+}
+
+/// The files to check, after applying the fixes if requested.
+fn check_paths(args: &Args, config: &Config, all: bool, fix: bool) -> Result<Vec<PathBuf>, Error> {
+    if all {
+        let dir = args
+            .file
+            .first()
+            .cloned()
+            .unwrap_or_else(|| timesheets_dir(config));
+        return Ok(Data::from_dir(&dir)?
+            .files
+            .into_iter()
+            .map(|f| f.path)
+            .collect());
+    }
+    let path = timesheet_path(args, config)?;
+    if fix {
+        apply_fixes(
+            config,
+            &path,
+            month_of(&path),
+            &convert_options(config, args.granularity),
+        )?;
+    }
+    Ok(vec![path])
+}
+
+/// Runs the checks that are not limited to a single file, `None` for all other commands.
+fn run_checks(command: &Command, config: &Config) -> Option<Result<(), Error>> {
+    let result = match command {
+        Command::Check {
+            format: format @ (CheckFormat::Json | CheckFormat::Sarif),
+            all,
+            fix,
+            args,
+            catalog,
+            max_daily_work,
+            ..
+        } => check_paths(args, config, *all, *fix).and_then(|paths| {
+            check_structured(
+                paths,
+                *format,
+                &convert_options(config, args.granularity),
+                catalog.as_deref(),
+                &lint_options(config, *max_daily_work)?,
+            )
+        }),
+        Command::Lint {
+            args,
+            all,
+            format,
+            catalog,
+        } => check_paths(args, config, *all, false).and_then(|paths| {
+            check::lint(
+                paths,
+                *format,
+                &convert_options(config, args.granularity),
+                catalog.as_deref(),
+                &lint_options(config, None)?,
+            )
+        }),
+        Command::Check {
+            watch: true,
+            args,
+            catalog,
+            max_daily_work,
+            ..
+        } => timesheet_path(args, config).and_then(|path| {
+            watch(
+                &path,
+                &convert_options(config, args.granularity),
+                catalog.as_deref(),
+                &lint_options(config, *max_daily_work)?,
+                config.language.unwrap_or_default(),
+            )
+        }),
+        Command::Check {
+            all: true,
+            args,
+            catalog,
+            max_daily_work,
+            ..
+        } => lint_options(config, *max_daily_work).and_then(|options| {
+            check_all(
+                &args
+                    .file
+                    .first()
+                    .cloned()
+                    .unwrap_or_else(|| timesheets_dir(config)),
+                &convert_options(config, args.granularity),
+                catalog.as_deref(),
+                &options,
+                config.language.unwrap_or_default(),
+            )
+        }),
+        _ => return None,
+    };
+    Some(result)
+}
+
+fn run(command: &Command, config: &Config) -> Result<(), Error> {
+    if let Some(result) = run_checks(command, config) {
+        return result;
+    }
+    match command {
+        Command::Report {
+            week: Some(week),
+            args,
+            durations,
+            balance,
+            no_pager,
+            ..
+        } => report_week(
+            args,
+            config,
+            *week,
+            report_options(config, *durations, *balance),
+            *no_pager,
+        ),
+        Command::Diff {
+            old,
+            new,
+            granularity,
+        } => diff(old, new, config, *granularity),
+        Command::Stats { year, granularity } => stats(config, *year, *granularity),
+        Command::Projects {
+            year,
+            json,
+            granularity,
+        } => projects(config, *year, *json, *granularity),
+        Command::Identifiers { prefix, comments } => {
+            identifiers(config, prefix.as_deref(), comments.as_deref())
         }
-        Cli::Add {
+        #[cfg(feature = "serve")]
+        Command::Serve { addr } => serve::serve(config, addr),
+        #[cfg(feature = "xlsx")]
+        Command::Export {
+            args,
+            format: ExportFormat::Xlsx,
+            locale,
+        } => export_xlsx(args, config, *locale),
+        #[cfg(feature = "jira")]
+        Command::Sync {
+            target:
+                SyncTarget::Jira {
+                    token,
+                    dry_run,
+                    args,
+                },
+        } => jira::sync(config, args, token.as_deref(), *dry_run),
+        Command::Grep {
+            pattern,
+            ignore_case,
+        } => grep(config, pattern, *ignore_case),
+        Command::Holidays {
+            region,
+            year,
+            dry_run,
+        } => holidays(config, *region, *year, *dry_run),
+        Command::Import {
+            format,
+            input,
+            since,
+            dry_run,
+            granularity,
+        } => import(config, *format, input, *since, *dry_run, *granularity),
+        Command::Remind { every } => remind::remind(config, *every),
+        Command::Init { dir } => init(&dir.clone().unwrap_or_else(|| timesheets_dir(config))),
+        Command::Start {
+            identifier,
+            comment,
+            args,
+        } => punch(
+            args,
+            config,
+            Topic::Project {
+                identifier: identifier.clone(),
+                comment: (!comment.is_empty()).then(|| comment.join(" ")),
+            },
+        ),
+        Command::Stop { args } => punch(args, config, Topic::Break),
+        Command::Restore {
+            snapshot,
+            yes,
+            args,
+        } => restore(args, config, snapshot.as_deref(), *yes),
+        _ => run_file(command, config),
+    }
+}
+
+/// Runs the commands that change the file itself, `None` for all others.
+fn edit_file(
+    command: &Command,
+    config: &Config,
+    path: &Path,
+    month: Date,
+    options: &times::convert::Options,
+) -> Option<Result<(), Error>> {
+    match command {
+        Command::Fmt { merge, check, .. } => Some(format_file(config, path, month, *merge, *check)),
+        Command::Undo { yes, .. } => Some(undo(config, path, *yes)),
+        Command::Add {
             template,
+            date,
+            dry_run,
             template_args,
             ..
+        } => Some(add(
+            config,
+            path,
+            &(*template).into(),
+            *date,
+            template_args,
+            options,
+            *dry_run,
+        )),
+        Command::Fix {
+            command: FixCommand::Shift { minutes, date, .. },
+        } => Some(shift_day(
+            config,
+            path,
+            month,
+            date.map(Date::new),
+            *minutes,
+            options,
+        )),
+        Command::Amend {
+            time,
+            identifier,
+            comment,
+            no_comment,
+            ..
         } => {
-            let template: Template = (*template).into();
-            let date = days
-                .last()
-                .and_then(|d| d.date.value.following_day_in_month())
-                .unwrap_or(month)
-                .next_weekday_in_month()
-                .expect("last day in the month");
-            let rendered = template.execute(date, template_args)?;
-            println!("{}", indent(&rendered));
-            append_to_file(path, &rendered).map_err(Error::InputFile)?;
+            let amendment = times::edit::Amendment {
+                time: *time,
+                identifier: identifier.clone(),
+                comment: if *no_comment {
+                    Some(None)
+                } else {
+                    comment.clone().map(Some)
+                },
+            };
+            Some(amend(config, path, month, amendment, options))
         }
+        _ => None,
+    }
+}
+
+fn report_options(config: &Config, durations: bool, balance: bool) -> times::report::Options {
+    times::report::Options {
+        entry_durations: durations,
+        running_balance: balance,
+        language: config.language.unwrap_or_default(),
+    }
+}
+
+fn run_file(command: &Command, config: &Config) -> Result<(), Error> {
+    let args = command.args();
+    let options = convert_options(config, args.granularity);
+    if let Some(result) = report_files(command, config) {
+        return result;
+    }
+    let path = match command {
+        Command::Add {
+            date: Some(date), ..
+        } if args.file.is_empty() => month_file(&timesheets_dir(config), *date),
+        _ => timesheet_path(args, config)?,
+    };
+    let path = path.as_path();
+    let month = month_of(path);
+    if let Command::Check { fix: true, .. } = command {
+        apply_fixes(config, path, month, &options)?;
+    }
+    if let Some(result) = edit_file(command, config, path, month, &options) {
+        return result;
+    }
+    let timesheet = load(path, &options)?;
+    let days = &timesheet.days;
+
+    match command {
+        Command::Check {
+            catalog,
+            max_daily_work,
+            ..
+        } => print_warnings(
+            days,
+            catalog.as_deref(),
+            &lint_options(config, *max_daily_work)?,
+            config.language.unwrap_or_default(),
+        )?,
+        Command::Report {
+            detail: Some(identifier),
+            no_pager,
+            ..
+        } => {
+            let output =
+                times::report::Detail::new(days, identifier, config.language.unwrap_or_default());
+            terminal::page(&output.to_string(), *no_pager);
+        }
+        Command::Report {
+            durations,
+            balance,
+            no_pager,
+            ..
+        } => {
+            let options = report_options(config, *durations, *balance);
+            let file = terminal::link_target(path);
+            let mut output = times::report::Output::with_options(&timesheet, options);
+            if let Some(file) = &file {
+                output = output.with_file(file);
+            }
+            terminal::page(&output.to_string(), *no_pager);
+        }
+        Command::Output { .. } => {
+            let output = times::format::Output(&timesheet);
+            write!(&mut stdout(), "{output}").expect("format output");
+        }
+        Command::Fmt { .. }
+        | Command::Lint { .. }
+        | Command::Fix { .. }
+        | Command::Diff { .. }
+        | Command::Stats { .. }
+        | Command::Projects { .. }
+        | Command::Identifiers { .. }
+        | Command::Grep { .. }
+        | Command::Holidays { .. }
+        | Command::Import { .. }
+        | Command::Init { .. }
+        | Command::Start { .. }
+        | Command::Stop { .. }
+        | Command::Undo { .. }
+        | Command::Amend { .. }
+        | Command::Remind { .. }
+        | Command::Restore { .. }
+        | Command::Add { .. } => unreachable!("handled before parsing"),
+        #[cfg(feature = "serve")]
+        Command::Serve { .. } => unreachable!("handled before parsing"),
+        #[cfg(feature = "jira")]
+        Command::Sync { .. } => unreachable!("handled before parsing"),
+        #[cfg(feature = "xlsx")]
+        Command::Export {
+            format: ExportFormat::Xlsx,
+            ..
+        } => unreachable!("handled before parsing"),
+        Command::Export {
+            format: ExportFormat::Csv,
+            locale,
+            ..
+        } => export_csv(&timesheet, config, *locale),
     }
     Ok(())
 }
 
+/// Loads the config that applies to `directory`, which replaces the configured timesheets
+/// directory.
+fn load_config(directory: Option<PathBuf>) -> Result<Config, Error> {
+    let cd = std::env::current_dir().map_err(Error::InputFile)?;
+    let directory = directory.map(|d| cd.join(d));
+    let flags = Config {
+        timesheets_dir: directory.clone(),
+        ..Config::default()
+    };
+    Ok(Config::load_with(
+        directory.as_deref().unwrap_or(&cd),
+        flags,
+    )?)
+}
+
+#[cfg(feature = "tracing")]
+fn init_log(path: &Path) -> Result<(), Error> {
+    use tracing_subscriber::fmt::format::FmtSpan;
+    use tracing_subscriber::EnvFilter;
+
+    let file = fs_err::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(Error::InputFile)?;
+    let filter = EnvFilter::try_from_default_env()
+        .unwrap_or_else(|_| EnvFilter::new("timesheet=debug,times=debug"));
+    tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_writer(std::sync::Mutex::new(file))
+        .with_span_events(FmtSpan::CLOSE)
+        .with_ansi(false)
+        .init();
+    tracing::info!(args = ?std::env::args().collect::<Vec<_>>(), "started");
+    Ok(())
+}
+
 fn main() -> ExitCode {
-    let command = Cli::parse();
-    match run(&command) {
+    let cli = Cli::parse();
+    #[cfg(feature = "tracing")]
+    if let Some(path) = &cli.log_file {
+        if let Err(e) = init_log(path) {
+            eprintln!("{e}");
+            return e.exit_code();
+        }
+    }
+    let mut language = Language::default();
+    let result = load_config(cli.directory).and_then(|config| {
+        language = config.language.unwrap_or_default();
+        run(&cli.command, &config).inspect_err(|_| check_failed(&cli.command, &config))
+    });
+    match result {
         Ok(()) => ExitCode::SUCCESS,
         Err(e) => {
-            eprintln!("{e}");
-            ExitCode::FAILURE
+            let code = e.exit_code();
+            e.print(language);
+            code
+        }
+    }
+}
+
+/// Runs the `on_check_fail` hook with the checked file, or directory with --all.
+fn check_failed(command: &Command, config: &Config) {
+    let Command::Check { args, all, .. } = command else {
+        return;
+    };
+    let path = if *all {
+        args.file
+            .first()
+            .cloned()
+            .unwrap_or_else(|| timesheets_dir(config))
+    } else if let Ok(path) = timesheet_path(args, config) {
+        path
+    } else {
+        return;
+    };
+    hooks::run(config, Hook::CheckFail, &path, None);
+}
+
+const MAX_FIX_PASSES: usize = 100;
+
+fn fixes(document: &Document, options: &times::convert::Options) -> Vec<Fix> {
+    let text = document.to_string();
+    match parse(text.as_bytes(), document.month()) {
+        Ok(days) => days
+            .into_iter()
+            .filter_map(|d| times::convert::Day::convert(d, options).err())
+            .filter_map(|e| e.fix(document))
+            .collect(),
+        Err(times::parse::Error::Many(errors)) => errors
+            .0
+            .iter()
+            .filter_map(|e| e.value.fix(e.line, document))
+            .collect(),
+        Err(_) => Vec::new(),
+    }
+}
+
+fn apply_fixes(
+    config: &Config,
+    path: &Path,
+    month: Date,
+    options: &times::convert::Options,
+) -> Result<(), Error> {
+    let lock = lock(path)?;
+    let mut changed = false;
+    for pass in 0..MAX_FIX_PASSES {
+        let file = File::open(path).map_err(Error::InputFile)?;
+        let mut document = Document::read(file, month).map_err(Error::InputFile)?;
+        let mut touched = HashSet::new();
+        let mut applied = false;
+        for fix in fixes(&document, options) {
+            if fix
+                .replacements
+                .iter()
+                .any(|(line, _)| touched.contains(line))
+            {
+                continue;
+            }
+            touched.extend(fix.replacements.iter().map(|(line, _)| *line));
+            println!("Fixed line {}: {}", fix.line, fix.description);
+            fix.apply(&mut document);
+            applied = true;
+        }
+        if !applied {
+            break;
+        }
+        if pass == 0 {
+            snapshot(config, path)?;
+        }
+        times::atomic::write(path, document.to_string()).map_err(Error::InputFile)?;
+        changed = true;
+    }
+    drop(lock);
+    if changed {
+        hooks::changed(config, path, None);
+    }
+    Ok(())
+}
+
+fn format_file(
+    config: &Config,
+    path: &Path,
+    month: Date,
+    merge: bool,
+    check: bool,
+) -> Result<(), Error> {
+    let lock = if check { None } else { Some(lock(path)?) };
+    let file = File::open(path).map_err(Error::InputFile)?;
+    let mut document = Document::read(file, month).map_err(Error::InputFile)?;
+    let merged = if merge {
+        times::transform::merge_adjacent_in(&mut document)
+    } else {
+        0
+    };
+    let normalized = times::transform::normalize(&mut document);
+    if check {
+        return if merged > 0 || normalized {
+            Err(Error::Unformatted(path.to_owned()))
+        } else {
+            Ok(())
+        };
+    }
+    if merged > 0 {
+        println!("Merged {merged} entries");
+    }
+    if merged > 0 || normalized {
+        snapshot(config, path)?;
+        times::atomic::write(path, document.to_string()).map_err(Error::InputFile)?;
+        drop(lock);
+        hooks::changed(config, path, None);
+    }
+    Ok(())
+}
+
+fn shift_day(
+    config: &Config,
+    path: &Path,
+    month: Date,
+    date: Option<Date>,
+    minutes: isize,
+    options: &times::convert::Options,
+) -> Result<(), Error> {
+    let lock = lock(path)?;
+    let file = File::open(path).map_err(Error::InputFile)?;
+    let mut document = Document::read(file, month).map_err(Error::InputFile)?;
+    let date = date
+        .or_else(|| {
+            let block = document.blocks().pop()?;
+            document.lines()[block.header].date()
+        })
+        .unwrap_or(month);
+    let shifted = times::transform::shift_in(&mut document, date, minutes.into())?;
+    write_validated(config, path, &document, options)?;
+    drop(lock);
+    hooks::changed(config, path, Some(date));
+    println!("Shifted {shifted} times of {date}");
+    Ok(())
+}
+
+/// The text of `document` if it is a valid timesheet.
+fn validate(document: &Document, options: &times::convert::Options) -> Result<String, Error> {
+    let text = document.to_string();
+    let timesheet = parse_month(text.as_bytes(), document.month())?;
+    times::convert::Month::convert(timesheet, options)?;
+    Ok(text)
+}
+
+/// How long to wait for another process that is changing the same file.
+const LOCK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Locks `path` while it is read and written again.
+fn lock(path: &Path) -> Result<Lock, Error> {
+    Ok(Lock::acquire(path, LOCK_TIMEOUT)?)
+}
+
+/// Takes a snapshot of `path` before it is changed, unless disabled in the config.
+fn snapshot(config: &Config, path: &Path) -> Result<(), Error> {
+    if let Some(options) = config.backup_options() {
+        times::backup::snapshot(path, chrono::offset::Local::now().naive_local(), &options)?;
+    }
+    Ok(())
+}
+
+/// Writes `document` to `path` if it is still a valid timesheet.
+fn write_validated(
+    config: &Config,
+    path: &Path,
+    document: &Document,
+    options: &times::convert::Options,
+) -> Result<(), Error> {
+    let text = validate(document, options)?;
+    snapshot(config, path)?;
+    times::atomic::write(path, text).map_err(Error::InputFile)
+}
+
+fn amend(
+    config: &Config,
+    path: &Path,
+    month: Date,
+    amendment: times::edit::Amendment,
+    options: &times::convert::Options,
+) -> Result<(), Error> {
+    let lock = lock(path)?;
+    let file = File::open(path).map_err(Error::InputFile)?;
+    let mut document = Document::read(file, month).map_err(Error::InputFile)?;
+    let (index, entry) = times::edit::amend_last(&mut document, amendment)?;
+    write_validated(config, path, &document, options)?;
+    drop(lock);
+    hooks::changed(config, path, None);
+    println!("Amended line {}: {entry}", index + 1);
+    Ok(())
+}
+
+/// Adds the day rendered from `template` at `date`, or appends it after the last day.
+fn add(
+    config: &Config,
+    path: &Path,
+    template: &Template,
+    date: Option<NaiveDate>,
+    args: &[String],
+    options: &times::convert::Options,
+    dry_run: bool,
+) -> Result<(), Error> {
+    let lock = if dry_run { None } else { Some(lock(path)?) };
+    let days = load(path, options)?.days;
+    if !dry_run {
+        snapshot(config, path)?;
+    }
+    let month = month_of(path);
+    let file = File::open(path).map_err(Error::InputFile)?;
+    let mut document = Document::read(file, month).map_err(Error::InputFile)?;
+    let (added, rendered) = if let Some(date) = date {
+        if date.with_day(1) != Some(month.into_inner()) {
+            return Err(Error::OtherMonth(date, path.to_owned()));
         }
+        let date = Date::new(date);
+        let rendered = template.execute(date, args, config.template_language())?;
+        times::edit::insert_day(&mut document, date, &rendered)?;
+        (date, rendered)
+    } else {
+        let date = days
+            .last()
+            .and_then(|d| d.date.value.following_day_in_month())
+            .unwrap_or(month)
+            .next_weekday_in_month()
+            .expect("last day in the month");
+        let rendered = template.execute(date, args, config.template_language())?;
+        document = Document::parse(&format!("{document}{rendered}"), month);
+        (date, rendered)
+    };
+    println!("{}", indent(&rendered));
+    let text = validate(&document, options)?;
+    if dry_run {
+        println!("Dry run, {} is unchanged", path.display());
+    } else if date.is_some() {
+        times::atomic::write(path, text).map_err(Error::InputFile)?;
+    } else {
+        let offset = times::atomic::append(path, &rendered).map_err(Error::InputFile)?;
+        times::journal::record(
+            path,
+            times::journal::Append {
+                offset,
+                text: rendered,
+            },
+        )?;
     }
+    drop(lock);
+    if !dry_run {
+        hooks::run(config, Hook::Add, path, Some(added));
+        hooks::changed(config, path, Some(added));
+    }
+    Ok(())
 }
 
-fn append_to_file(path: &Path, text: &str) -> Result<(), std::io::Error> {
-    let file = OpenOptions::new().append(true).open(path)?;
-    BufWriter::new(file).write_all(text.as_bytes())
+/// Asks `question` on stdout, true if it is answered with yes.
+fn confirm(question: &str) -> Result<bool, Error> {
+    print!("{question} [y/N] ");
+    stdout().flush().map_err(Error::InputFile)?;
+    let mut answer = String::new();
+    stdin().read_line(&mut answer).map_err(Error::InputFile)?;
+    Ok(matches!(answer.trim(), "y" | "Y" | "yes"))
+}
+
+fn undo(config: &Config, path: &Path, yes: bool) -> Result<(), Error> {
+    let lock = lock(path)?;
+    let append = times::journal::last(path)?;
+    for line in append.text.lines() {
+        println!("- {line}");
+    }
+    if !yes && !confirm(&format!("Remove these lines from {}?", path.display()))? {
+        return Ok(());
+    }
+    snapshot(config, path)?;
+    times::journal::undo(path)?;
+    drop(lock);
+    hooks::changed(config, path, None);
+    println!("Removed the last day added to {}", path.display());
+    Ok(())
+}
+
+/// Lists the snapshots of the file newest first, or replaces the file with the one selected by
+/// `selector`.
+fn restore(args: &Args, config: &Config, selector: Option<&str>, yes: bool) -> Result<(), Error> {
+    let path = timesheet_path(args, config)?;
+    let lock = lock(&path)?;
+    let Some(selector) = selector else {
+        let snapshots = times::backup::snapshots(&path)?;
+        if snapshots.is_empty() {
+            println!("There are no snapshots of {}", path.display());
+        }
+        for (i, snapshot) in snapshots.iter().rev().enumerate() {
+            let name = snapshot.path.file_name().unwrap_or_default();
+            println!(
+                "{:>3}  {}  {}",
+                i + 1,
+                snapshot.time.format("%Y-%m-%d %H:%M:%S"),
+                name.to_string_lossy()
+            );
+        }
+        return Ok(());
+    };
+    let snapshot = times::backup::find(&path, selector)?;
+    let question = format!(
+        "Replace {} with the snapshot from {}?",
+        path.display(),
+        snapshot.time.format("%Y-%m-%d %H:%M:%S")
+    );
+    if !yes && !confirm(&question)? {
+        return Ok(());
+    }
+    let options = config.backup_options().unwrap_or_default();
+    let now = chrono::offset::Local::now().naive_local();
+    times::backup::restore(&path, &snapshot, now, &options)?;
+    drop(lock);
+    hooks::changed(config, &path, None);
+    println!(
+        "Restored {}, the replaced contents are the newest snapshot",
+        path.display()
+    );
+    Ok(())
 }
 
 fn indent(s: &str) -> String {
@@ -0,0 +1,152 @@
+use std::ffi::{c_char, CStr, CString};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use libloading::{Library, Symbol};
+use serde_json::{json, Value};
+use times::convert::Day;
+use times::lint::{Custom, Level, Rule};
+
+use crate::Error;
+
+/// `TimesLintRule` of `times/include/times_lint.h`.
+#[repr(C)]
+struct RawRule {
+    code: *const c_char,
+    default_level: u8,
+    check: unsafe extern "C" fn(*const c_char) -> *mut c_char,
+    free_result: unsafe extern "C" fn(*mut c_char),
+}
+
+type Rules = unsafe extern "C" fn(*mut usize) -> *const RawRule;
+
+struct PluginRule {
+    code: String,
+    default_level: Level,
+    check: unsafe extern "C" fn(*const c_char) -> *mut c_char,
+    free_result: unsafe extern "C" fn(*mut c_char),
+    /// Keeps the functions loaded.
+    _library: Arc<Library>,
+}
+
+fn day_json(day: &Day) -> Value {
+    let entries: Vec<_> = day
+        .entries
+        .iter()
+        .map(|e| {
+            json!({
+                "line": e.line,
+                "start": e.value.start.value.to_string(),
+                "end": e.value.end.value.to_string(),
+                "identifier": e.value.identifier.as_str(),
+                "comment": e.value.comment,
+            })
+        })
+        .collect();
+    json!({
+        "date": day.date.value.into_inner().to_string(),
+        "line": day.date.line,
+        "entries": entries,
+    })
+}
+
+/// The problems of the JSON array `text`.
+fn problems(text: &str) -> Result<Vec<(usize, String)>, String> {
+    let value: Value = serde_json::from_str(text).map_err(|e| e.to_string())?;
+    let problems = value.as_array().ok_or("expected an array")?;
+    problems
+        .iter()
+        .map(|p| {
+            let line = p["line"].as_u64().and_then(|l| usize::try_from(l).ok());
+            let message = p["message"].as_str();
+            line.zip(message)
+                .map(|(line, message)| (line, message.to_owned()))
+                .ok_or_else(|| format!("expected a line and a message in {p}"))
+        })
+        .collect()
+}
+
+impl Rule for PluginRule {
+    fn code(&self) -> &str {
+        &self.code
+    }
+
+    fn default_level(&self) -> Level {
+        self.default_level
+    }
+
+    fn check(&self, day: &Day) -> Vec<(usize, String)> {
+        // JSON escapes NUL characters.
+        let input = CString::new(day_json(day).to_string()).expect("no NUL in JSON");
+        // SAFETY: The plugin follows times_lint.h, check returns NULL or a string that stays
+        // valid until it is passed to free_result.
+        let output = unsafe {
+            let output = (self.check)(input.as_ptr());
+            if output.is_null() {
+                return Vec::new();
+            }
+            let text = CStr::from_ptr(output).to_string_lossy().into_owned();
+            (self.free_result)(output);
+            text
+        };
+        problems(&output).unwrap_or_else(|e| {
+            vec![(
+                day.date.line,
+                format!("Invalid result of lint rule {}: {e}", self.code),
+            )]
+        })
+    }
+}
+
+fn load_library(path: &Path) -> Result<Vec<Arc<dyn Rule>>, String> {
+    // SAFETY: Loading runs the initializers of the library, which the config trusts.
+    let library = Arc::new(unsafe { Library::new(path) }.map_err(|e| e.to_string())?);
+    // SAFETY: The plugin follows times_lint.h, the rules stay valid while it is loaded and the
+    // fields are copied before `library` can be dropped.
+    let raw = unsafe {
+        let function: Symbol<'_, Rules> = library
+            .get(b"times_lint_rules\0")
+            .map_err(|e| e.to_string())?;
+        let mut count = 0;
+        let rules = function(&raw mut count);
+        if rules.is_null() {
+            &[]
+        } else {
+            std::slice::from_raw_parts(rules, count)
+        }
+    };
+    let mut rules: Vec<Arc<dyn Rule>> = Vec::new();
+    for rule in raw {
+        // SAFETY: The code is a NUL-terminated string that stays valid while the library is
+        // loaded.
+        let code = unsafe { CStr::from_ptr(rule.code) }
+            .to_str()
+            .map_err(|e| e.to_string())?;
+        if !code.contains('/') {
+            return Err(format!("rule {code} has no / in its code"));
+        }
+        let default_level = match rule.default_level {
+            0 => Level::Off,
+            1 => Level::Warning,
+            2 => Level::Error,
+            level => return Err(format!("rule {code} has an invalid level {level}")),
+        };
+        rules.push(Arc::new(PluginRule {
+            code: code.to_owned(),
+            default_level,
+            check: rule.check,
+            free_result: rule.free_result,
+            _library: library.clone(),
+        }));
+    }
+    Ok(rules)
+}
+
+/// The rules of the plugins at `paths`.
+pub fn load(paths: &[PathBuf]) -> Result<Custom, Error> {
+    let mut rules = Vec::new();
+    for path in paths {
+        rules.extend(load_library(path).map_err(|e| Error::Plugin(path.clone(), e))?);
+    }
+    Ok(Custom(rules))
+}
@@ -0,0 +1,81 @@
+use std::io::{BufReader, ErrorKind};
+use std::process::Command;
+use std::time::Duration;
+
+use chrono::{Local, Timelike};
+use times::config::Config;
+use times::parse::parse_month;
+use times::remind::{reminders, Reminder};
+use times::{Minutes, Time};
+
+use crate::{current_month_file, month_of, timesheets_dir, Error};
+
+/// `notify-send` on Linux and the BSDs, `osascript` on macOS.
+fn notifier(message: &str) -> Option<Command> {
+    let mut command = if cfg!(target_os = "macos") {
+        let mut command = Command::new("osascript");
+        command.arg("-e").arg(format!(
+            "display notification {message:?} with title \"timesheet\""
+        ));
+        command
+    } else if cfg!(unix) {
+        let mut command = Command::new("notify-send");
+        command.args(["--app-name=timesheet", "timesheet", message]);
+        command
+    } else {
+        return None;
+    };
+    command.stdout(std::process::Stdio::null());
+    Some(command)
+}
+
+/// Prints the reminder and shows it as a desktop notification where possible.
+fn notify(reminder: &Reminder) {
+    let message = reminder.to_string();
+    println!("{message}");
+    if let Some(mut command) = notifier(&message) {
+        // Without a notification daemon the printed message is all there is.
+        command.status().ok();
+    }
+}
+
+fn check(config: &Config) -> Result<Vec<Reminder>, Error> {
+    let now = Local::now().naive_local();
+    let path = current_month_file(&timesheets_dir(config));
+    let month = match fs_err::File::open(&path) {
+        Ok(file) => Some(
+            parse_month(&mut BufReader::new(file), month_of(&path))
+                .map_err(|e| times::Error::from(e).with_path(&path))?,
+        ),
+        Err(e) if e.kind() == ErrorKind::NotFound => None,
+        Err(e) => return Err(Error::InputFile(e)),
+    };
+    let time = Time::new(
+        u8::try_from(now.hour()).unwrap(),
+        u8::try_from(now.minute()).unwrap(),
+    )
+    .unwrap();
+    Ok(reminders(
+        month.as_ref(),
+        now.date(),
+        time,
+        &config.remind_options(),
+    ))
+}
+
+/// Checks the file of the current month once, or every `every` until stopped. Errors while
+/// running repeatedly are printed, the file might just be in the middle of an edit.
+pub fn remind(config: &Config, every: Option<Minutes>) -> Result<(), Error> {
+    loop {
+        match check(config) {
+            Ok(reminders) => reminders.iter().for_each(notify),
+            Err(e) if every.is_some() => eprintln!("{e}"),
+            Err(e) => return Err(e),
+        }
+        let Some(every) = every else {
+            return Ok(());
+        };
+        let minutes = u64::try_from(every.into_inner().max(1)).unwrap_or(u64::MAX);
+        std::thread::sleep(Duration::from_secs(minutes.saturating_mul(60)));
+    }
+}
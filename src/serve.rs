@@ -0,0 +1,217 @@
+use serde_json::{json, Map, Value};
+use times::config::Config;
+use times::convert::{AccumulatedTime, Month};
+use times::data::Data;
+use times::parse::from_stem;
+use times::stats::Stats;
+use tiny_http::{Header, Method, Response, Server};
+
+use crate::{load_all, timesheets_dir, Error};
+
+/// A failed request with its status code.
+#[derive(Debug)]
+struct Failure(u16, String);
+
+impl From<Error> for Failure {
+    fn from(error: Error) -> Self {
+        Self(500, error.to_string())
+    }
+}
+
+fn month_name(month: times::Date) -> String {
+    month.into_inner().format("%Y-%m").to_string()
+}
+
+fn projects(times: &AccumulatedTime) -> Value {
+    times
+        .by_project()
+        .iter()
+        .map(|(identifier, minutes)| (identifier.to_string(), minutes.into_inner().into()))
+        .collect::<Map<_, _>>()
+        .into()
+}
+
+fn months(config: &Config) -> Result<Value, Failure> {
+    let data = Data::from_dir(&timesheets_dir(config)).map_err(Error::from)?;
+    Ok(data
+        .files
+        .iter()
+        .map(|f| {
+            json!({
+                "month": month_name(f.month),
+                "file": f.path.file_name().map(|n| n.to_string_lossy()),
+            })
+        })
+        .collect())
+}
+
+fn load_month(config: &Config, name: &str) -> Result<Month, Failure> {
+    let not_found = || Failure(404, format!("No file for month {name}"));
+    let month = from_stem(name).ok_or_else(not_found)?;
+    let data = Data::from_dir(&timesheets_dir(config)).map_err(Error::from)?;
+    let file = data
+        .files
+        .iter()
+        .find(|f| f.month == month)
+        .ok_or_else(not_found)?;
+    Ok(file.load(&config.convert_options()).map_err(Error::from)?)
+}
+
+fn month(month: &Month) -> Value {
+    let days: Vec<_> = month
+        .days
+        .iter()
+        .map(|day| {
+            let entries: Vec<_> = day
+                .entries
+                .iter()
+                .map(|e| {
+                    let e = &e.value;
+                    json!({
+                        "start": e.start.value.to_string(),
+                        "end": e.end.value.to_string(),
+                        "identifier": e.identifier.as_str(),
+                        "comment": e.comment,
+                        "minutes": e.duration.into_inner(),
+                    })
+                })
+                .collect();
+            json!({
+                "date": day.date.value.into_inner().to_string(),
+                "comments": day.comments,
+                "entries": entries,
+            })
+        })
+        .collect();
+    json!({ "month": month_name(month.month), "days": days })
+}
+
+fn report(month: &Month) -> Value {
+    let days: Vec<_> = month
+        .days
+        .iter()
+        .map(|day| {
+            let times = &day.times;
+            json!({
+                "date": day.date.value.into_inner().to_string(),
+                "expected": month.expected_time(day).into_inner(),
+                "billable": times.billable_time().into_inner(),
+                "work": times.work_time().into_inner(),
+                "travel": times.travel_time().into_inner(),
+                "under_hours": times.under_hours().into_inner(),
+            })
+        })
+        .collect();
+    let times = month.times();
+    let expected = month
+        .days
+        .iter()
+        .map(|d| month.expected_time(d))
+        .sum::<times::Minutes>();
+    json!({
+        "month": month_name(month.month),
+        "expected": expected.into_inner(),
+        "billable": times.billable_time().into_inner(),
+        "balance": (times.billable_time() - expected).into_inner(),
+        "projects": projects(&times),
+        "days": days,
+    })
+}
+
+fn stats(config: &Config) -> Result<Value, Failure> {
+    let months = load_all(config, None, None)?;
+    let stats = Stats::new(&months);
+    let balances: Vec<_> = stats
+        .months
+        .iter()
+        .map(|m| {
+            json!({
+                "month": month_name(m.month),
+                "balance": m.balance.into_inner(),
+                "cumulative": m.cumulative.into_inner(),
+            })
+        })
+        .collect();
+    Ok(json!({
+        "billable": stats.times.billable_time().into_inner(),
+        "projects": projects(&stats.times),
+        "months": balances,
+    }))
+}
+
+fn route(config: &Config, method: &Method, url: &str) -> Result<Value, Failure> {
+    if *method != Method::Get {
+        return Err(Failure(405, "Only GET is supported".to_owned()));
+    }
+    let path = url.split(['?', '#']).next().unwrap_or_default();
+    let segments: Vec<_> = path.split('/').filter(|s| !s.is_empty()).collect();
+    match segments.as_slice() {
+        ["months"] => months(config),
+        ["months", name] => Ok(month(&load_month(config, name)?)),
+        ["report", name] => Ok(report(&load_month(config, name)?)),
+        ["stats"] => stats(config),
+        _ => Err(Failure(404, format!("Unknown path {path}"))),
+    }
+}
+
+/// Answers read-only JSON requests about the timesheets directory until the process is stopped.
+/// Times are in minutes.
+pub fn serve(config: &Config, addr: &str) -> Result<(), Error> {
+    let server = Server::http(addr).map_err(Error::Serve)?;
+    eprintln!(
+        "Serving {} on http://{}",
+        timesheets_dir(config).display(),
+        server.server_addr()
+    );
+    let content_type = Header::from_bytes("Content-Type", "application/json").unwrap();
+    for request in server.incoming_requests() {
+        let (status, body) = match route(config, request.method(), request.url()) {
+            Ok(body) => (200, body),
+            Err(Failure(status, message)) => (status, json!({ "error": message })),
+        };
+        let response = Response::from_string(body.to_string())
+            .with_status_code(status)
+            .with_header(content_type.clone());
+        // The client may have gone away already, which only concerns that request.
+        request.respond(response).ok();
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_route() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("2024-04.tsh"),
+            "* Mo. 15.04.\n09:00 AA Foo\n12:00\n",
+        )
+        .unwrap();
+        let config = Config {
+            timesheets_dir: Some(dir.path().to_owned()),
+            ..Config::default()
+        };
+        let status = |method, url| route(&config, &method, url).err().map(|Failure(s, _)| s);
+
+        let month = route(&config, &Method::Get, "/months/2024-04?pretty").unwrap();
+        assert_eq!(month["month"], "2024-04");
+        assert_eq!(
+            month["days"][0]["entries"][0],
+            json!({
+                "start": "09:00",
+                "end": "12:00",
+                "identifier": "AA",
+                "comment": "Foo",
+                "minutes": 180,
+            })
+        );
+        assert_eq!(status(Method::Get, "/months"), None);
+        assert_eq!(status(Method::Get, "/months/2024-05"), Some(404));
+        assert_eq!(status(Method::Get, "/months/april"), Some(404));
+        assert_eq!(status(Method::Get, "/unknown"), Some(404));
+        assert_eq!(status(Method::Post, "/months"), Some(405));
+    }
+}
@@ -0,0 +1,29 @@
+use std::process::Command;
+
+/// Runs `command` with the shell, the arguments follow the command like in a script.
+pub fn shell(command: &str) -> Command {
+    if cfg!(windows) {
+        let mut shell = Command::new("cmd");
+        shell.arg("/C").arg(command);
+        shell
+    } else {
+        let mut shell = Command::new("sh");
+        shell.arg("-c").arg(format!("{command} \"$@\"")).arg("sh");
+        shell
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shell() {
+        let output = shell("printf '%s|'")
+            .arg("2024-04.tsh")
+            .arg("a \"b\" $c")
+            .output()
+            .unwrap();
+        assert_eq!(output.stdout, b"2024-04.tsh|a \"b\" $c|");
+    }
+}
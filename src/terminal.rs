@@ -0,0 +1,100 @@
+use std::env;
+use std::io::{stdout, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+
+use crate::shell::shell;
+
+/// Whether the terminal on stdout shows OSC 8 hyperlinks, `FORCE_HYPERLINK=1` or `0` overrides
+/// the detection. Terminals ignoring them would print the sequences, so only known ones are used.
+pub fn hyperlinks() -> bool {
+    if let Some(force) = env::var_os("FORCE_HYPERLINK") {
+        return force != "0";
+    }
+    if !stdout().is_terminal() || env::var_os("CI").is_some() {
+        return false;
+    }
+    let var = |name| env::var(name).unwrap_or_default();
+    ["DOMTERM", "WT_SESSION", "KONSOLE_VERSION"]
+        .iter()
+        .any(|name| env::var_os(name).is_some())
+        || var("VTE_VERSION").parse::<u32>().is_ok_and(|v| v >= 5000)
+        || matches!(
+            var("TERM_PROGRAM").as_str(),
+            "Hyper" | "iTerm.app" | "terminology" | "WezTerm" | "vscode" | "ghostty"
+        )
+        || matches!(
+            var("TERM").as_str(),
+            "xterm-kitty" | "alacritty" | "alacritty-direct" | "foot" | "xterm-ghostty"
+        )
+}
+
+/// The absolute path the report links to for `file`, `None` without hyperlinks.
+pub fn link_target(file: &Path) -> Option<PathBuf> {
+    hyperlinks()
+        .then(|| std::path::absolute(file).ok())
+        .flatten()
+}
+
+/// The link targets of `files` in the same order, empty without hyperlinks.
+pub fn link_targets(files: &[PathBuf]) -> Vec<PathBuf> {
+    if !hyperlinks() {
+        return Vec::new();
+    }
+    files
+        .iter()
+        .map(|file| std::path::absolute(file).unwrap_or_else(|_| file.clone()))
+        .collect()
+}
+
+#[cfg(unix)]
+fn window_rows() -> Option<usize> {
+    let mut size = libc::winsize {
+        ws_row: 0,
+        ws_col: 0,
+        ws_xpixel: 0,
+        ws_ypixel: 0,
+    };
+    // SAFETY: TIOCGWINSZ only writes a winsize to the pointer.
+    let result = unsafe { libc::ioctl(libc::STDOUT_FILENO, libc::TIOCGWINSZ, &raw mut size) };
+    (result == 0 && size.ws_row > 0).then_some(usize::from(size.ws_row))
+}
+
+#[cfg(not(unix))]
+fn window_rows() -> Option<usize> {
+    None
+}
+
+/// Number of lines of the terminal on stdout, `None` if it is not one.
+pub fn height() -> Option<usize> {
+    if !stdout().is_terminal() {
+        return None;
+    }
+    window_rows().or_else(|| env::var("LINES").ok()?.parse().ok())
+}
+
+/// Prints `text`, through `$PAGER` or `less` if it does not fit on the terminal like git does.
+/// `PAGER=cat` or an empty `PAGER` disables the pager.
+pub fn page(text: &str, no_pager: bool) {
+    let fits = height().is_none_or(|height| text.lines().count() < height);
+    let pager = env::var("PAGER").unwrap_or_else(|_| "less".to_owned());
+    if no_pager || fits || pager.is_empty() || pager == "cat" {
+        let mut stdout = stdout();
+        stdout.write_all(text.as_bytes()).expect("write output");
+        return;
+    }
+    let mut command = shell(&pager);
+    // Colors and hyperlinks need raw control characters.
+    if env::var_os("LESS").is_none() {
+        command.env("LESS", "FRX");
+    }
+    let Ok(mut child) = command.stdin(Stdio::piped()).spawn() else {
+        stdout().write_all(text.as_bytes()).expect("write output");
+        return;
+    };
+    if let Some(mut stdin) = child.stdin.take() {
+        // Quitting the pager early closes the pipe.
+        stdin.write_all(text.as_bytes()).ok();
+    }
+    child.wait().ok();
+}
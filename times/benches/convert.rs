@@ -0,0 +1,32 @@
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+use times::convert::{Incremental, Month, Options};
+use times::parse::parse_month;
+use times::testing::Generator;
+
+fn convert(c: &mut Criterion) {
+    let months = Generator::new(1).year(2024);
+    let (month, text) = &months[3];
+    let parse = || parse_month(text.as_bytes(), *month).unwrap();
+    let options = Options::default();
+    let mut group = c.benchmark_group("convert");
+    group.bench_function("month", |b| {
+        b.iter_batched(
+            parse,
+            |parsed| Month::convert(parsed, &options).unwrap(),
+            BatchSize::SmallInput,
+        );
+    });
+    let mut incremental = Incremental::new(options.clone());
+    incremental.convert(parse()).unwrap();
+    group.bench_function("incremental", |b| {
+        b.iter_batched(
+            parse,
+            |parsed| incremental.convert(parsed).unwrap(),
+            BatchSize::SmallInput,
+        );
+    });
+    group.finish();
+}
+
+criterion_group!(benches, convert);
+criterion_main!(benches);
@@ -0,0 +1,26 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use times::parse::parse_month;
+use times::testing::Generator;
+
+fn parse(c: &mut Criterion) {
+    let months = Generator::new(1).year(2024);
+    let (month, text) = &months[3];
+    let mut group = c.benchmark_group("parse");
+    group.throughput(Throughput::Bytes(text.len() as u64));
+    group.bench_function("month", |b| {
+        b.iter(|| parse_month(black_box(text.as_bytes()), *month).unwrap());
+    });
+    let bytes = months.iter().map(|(_, text)| text.len() as u64).sum();
+    group.throughput(Throughput::Bytes(bytes));
+    group.bench_function("year", |b| {
+        b.iter(|| {
+            for (month, text) in &months {
+                parse_month(black_box(text.as_bytes()), *month).unwrap();
+            }
+        });
+    });
+    group.finish();
+}
+
+criterion_group!(benches, parse);
+criterion_main!(benches);
@@ -0,0 +1,28 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use times::convert::{Month, Options};
+use times::parse::parse_month;
+use times::report::Output;
+use times::stats::Stats;
+use times::testing::Generator;
+
+fn report(c: &mut Criterion) {
+    let months: Vec<_> = Generator::new(1)
+        .year(2024)
+        .into_iter()
+        .map(|(month, text)| {
+            let parsed = parse_month(text.as_bytes(), month).unwrap();
+            Month::convert(parsed, &Options::default()).unwrap()
+        })
+        .collect();
+    let mut group = c.benchmark_group("report");
+    group.bench_function("month", |b| {
+        b.iter(|| Output::new(black_box(&months[3])).to_string());
+    });
+    group.bench_function("stats", |b| {
+        b.iter(|| Stats::new(black_box(&months)).to_string());
+    });
+    group.finish();
+}
+
+criterion_group!(benches, report);
+criterion_main!(benches);
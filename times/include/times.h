@@ -0,0 +1,49 @@
+/* C bindings of the times library, built with
+ * cargo rustc -p times --release --features capi --crate-type cdylib */
+#ifndef TIMES_H
+#define TIMES_H
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* The totals of a day in minutes. */
+typedef struct TimesDay {
+    int32_t year;
+    uint32_t month;
+    uint32_t day;
+    uint64_t expected;
+    uint64_t work;
+    uint64_t travel;
+    uint64_t billable;
+    uint64_t under_hours;
+} TimesDay;
+
+typedef struct TimesMonth TimesMonth;
+
+/* Parses and converts the file of month in year with the rules of the TOML config, or the
+ * defaults if it is NULL. Returns NULL for an invalid month, free the result with times_free. */
+TimesMonth *times_parse(const uint8_t *data, size_t len, int32_t year, uint32_t month,
+                        const char *config);
+void times_free(TimesMonth *month);
+
+/* Number of days, zero if there are errors. */
+size_t times_day_count(const TimesMonth *month);
+/* Writes the totals of the day at index to out, false if there is no such day. */
+bool times_day(const TimesMonth *month, size_t index, TimesDay *out);
+
+/* Number of errors, zero if the month could be converted. */
+size_t times_error_count(const TimesMonth *month);
+/* The message of the error at index or NULL, valid as long as month. Its line is written to
+ * line unless it is NULL, 0 for errors not tied to a line. */
+const char *times_error(const TimesMonth *month, size_t index, size_t *line);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
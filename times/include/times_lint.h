@@ -0,0 +1,42 @@
+/* Custom lint rules for the timesheet command, built with the plugins feature. A plugin is a
+ * dynamic library listed in plugins of the [lint] section of the config. */
+#ifndef TIMES_LINT_H
+#define TIMES_LINT_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* Level of a rule without a level in [lint.rules]. */
+enum {
+    TIMES_LINT_OFF = 0,
+    TIMES_LINT_WARNING = 1,
+    TIMES_LINT_ERROR = 2,
+};
+
+typedef struct TimesLintRule {
+    /* Code of the warnings, containing a '/' like "acme/ticket". */
+    const char *code;
+    uint8_t default_level;
+    /* Called with a day as JSON like
+     * {"date": "2024-04-15", "line": 1, "entries": [{"line": 2, "start": "09:00",
+     * "end": "10:00", "identifier": "AA", "comment": null}]}.
+     * Returns NULL or a JSON array of problems like [{"line": 2, "message": "..."}], which is
+     * passed to free_result afterwards. The rules are shared between threads, so check and
+     * free_result may be called from several threads at the same time. */
+    char *(*check)(const char *day);
+    void (*free_result)(char *result);
+} TimesLintRule;
+
+/* Exported by the plugin: its rules, valid as long as the library is loaded, with their number
+ * written to count. */
+const TimesLintRule *times_lint_rules(size_t *count);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
@@ -0,0 +1,272 @@
+use std::fmt::{Display, Formatter};
+use std::io::Read;
+
+use crate::parse::{parse_date, DateError, EntryError};
+use crate::{Date, Entry};
+
+#[derive(Debug, Eq, PartialEq)]
+pub enum Node {
+    Blank,
+    Comment,
+    Day(Result<Date, DateError>),
+    Entry(Result<Entry, EntryError>),
+}
+
+impl Node {
+    fn parse(text: &str, month: Date) -> Self {
+        let text = text.trim();
+        if text.is_empty() {
+            Node::Blank
+        } else if text.starts_with('#') {
+            Node::Comment
+        } else if let Some(header) = text.strip_prefix('*') {
+            Node::Day(parse_date(header, month, 0))
+        } else {
+            Node::Entry(text.parse())
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Line {
+    text: String,
+    ending: String,
+    node: Node,
+}
+
+impl Line {
+    #[must_use]
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    #[must_use]
+    pub fn node(&self) -> &Node {
+        &self.node
+    }
+
+    #[must_use]
+    pub fn date(&self) -> Option<Date> {
+        match &self.node {
+            Node::Day(Ok(date)) => Some(*date),
+            _ => None,
+        }
+    }
+
+    #[must_use]
+    pub fn entry(&self) -> Option<&Entry> {
+        match &self.node {
+            Node::Entry(Ok(entry)) => Some(entry),
+            _ => None,
+        }
+    }
+}
+
+/// A day header and the entries following it, as indices into [`Document::lines`].
+#[derive(Debug, Eq, PartialEq)]
+pub struct Block {
+    pub header: usize,
+    pub entries: Vec<usize>,
+}
+
+impl Block {
+    /// Index after the last entry of the block, where a new trailing entry would go.
+    #[must_use]
+    pub fn end(&self) -> usize {
+        self.entries.last().unwrap_or(&self.header) + 1
+    }
+}
+
+/// A timesheet file that can be modified line by line and written back unchanged everywhere else.
+#[derive(Debug)]
+pub struct Document {
+    month: Date,
+    lines: Vec<Line>,
+}
+
+impl Document {
+    #[must_use]
+    pub fn parse(text: &str, month: Date) -> Self {
+        let lines = text
+            .split_inclusive('\n')
+            .map(|raw| {
+                let text = raw.trim_end_matches(['\r', '\n']);
+                Line {
+                    text: text.to_owned(),
+                    ending: raw[text.len()..].to_owned(),
+                    node: Node::parse(text, month),
+                }
+            })
+            .collect();
+        Self { month, lines }
+    }
+
+    pub fn read(mut r: impl Read, month: Date) -> std::io::Result<Self> {
+        let mut text = String::new();
+        r.read_to_string(&mut text)?;
+        Ok(Self::parse(&text, month))
+    }
+
+    #[must_use]
+    pub fn month(&self) -> Date {
+        self.month
+    }
+
+    #[must_use]
+    pub fn lines(&self) -> &[Line] {
+        &self.lines
+    }
+
+    #[must_use]
+    pub fn blocks(&self) -> Vec<Block> {
+        let mut blocks: Vec<Block> = Vec::new();
+        for (index, line) in self.lines.iter().enumerate() {
+            match line.node {
+                Node::Day(_) => blocks.push(Block {
+                    header: index,
+                    entries: Vec::new(),
+                }),
+                Node::Entry(_) => {
+                    if let Some(block) = blocks.last_mut() {
+                        block.entries.push(index);
+                    }
+                }
+                Node::Blank | Node::Comment => {}
+            }
+        }
+        blocks
+    }
+
+    #[must_use]
+    pub fn block(&self, date: Date) -> Option<Block> {
+        self.blocks()
+            .into_iter()
+            .find(|b| self.lines[b.header].date() == Some(date))
+    }
+
+    pub(crate) fn line_ending(&self) -> &str {
+        self.lines
+            .iter()
+            .map(|l| l.ending.as_str())
+            .find(|e| !e.is_empty())
+            .unwrap_or("\n")
+    }
+
+    /// Inserts a new line before `index`, `index == lines().len()` appends to the end.
+    pub fn insert(&mut self, index: usize, text: &str) {
+        let ending = self.line_ending().to_owned();
+        if index == self.lines.len() {
+            if let Some(last) = self.lines.last_mut() {
+                if last.ending.is_empty() {
+                    last.ending.clone_from(&ending);
+                }
+            }
+        }
+        self.lines.insert(
+            index,
+            Line {
+                text: text.to_owned(),
+                ending,
+                node: Node::parse(text, self.month),
+            },
+        );
+    }
+
+    /// Replaces the content of the line at `index`, keeping its indentation and line ending.
+    pub fn replace(&mut self, index: usize, text: &str) {
+        let line = &mut self.lines[index];
+        let indent = &line.text[..line.text.len() - line.text.trim_start().len()];
+        line.text = format!("{indent}{text}");
+        line.node = Node::parse(&line.text, self.month);
+    }
+
+    pub fn remove(&mut self, index: usize) -> Line {
+        let line = self.lines.remove(index);
+        if index == self.lines.len() {
+            if let Some(last) = self.lines.last_mut() {
+                last.ending.clone_from(&line.ending);
+            }
+        }
+        line
+    }
+}
+
+impl Display for Document {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for line in &self.lines {
+            write!(f, "{}{}", line.text, line.ending)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use super::*;
+    use crate::{Time, Topic};
+
+    fn month() -> Date {
+        Date(NaiveDate::from_ymd_opt(2024, 4, 1).unwrap())
+    }
+
+    const TEXT: &str =
+        "# comment\r\n* Mo. 15.04.\r\n  09:00 AA A\r\n12:00\r\n\r\n* Di. 16.04.\r\n09:00 AA";
+
+    #[test]
+    fn test_round_trip() {
+        for text in [TEXT, "", "\n\n", "* Mo. 15.04.\n09:00 AA\n"] {
+            assert_eq!(Document::parse(text, month()).to_string(), text);
+        }
+    }
+
+    #[test]
+    fn test_blocks() {
+        let document = Document::parse(TEXT, month());
+        assert_eq!(
+            document.blocks(),
+            vec![
+                Block {
+                    header: 1,
+                    entries: vec![2, 3],
+                },
+                Block {
+                    header: 5,
+                    entries: vec![6],
+                },
+            ]
+        );
+        let date = Date(NaiveDate::from_ymd_opt(2024, 4, 16).unwrap());
+        assert_eq!(document.block(date).unwrap().end(), 7);
+        assert_eq!(
+            document.lines()[2].entry(),
+            Some(&Entry {
+                time: Time::new(9, 0).unwrap(),
+                topic: Topic::Project {
+                    identifier: "AA".to_owned(),
+                    comment: Some("A".to_owned()),
+                },
+            })
+        );
+    }
+
+    #[test]
+    fn test_modify() {
+        let mut document = Document::parse(TEXT, month());
+        document.replace(2, "09:03 BB");
+        document.insert(3, "10:00 CC");
+        document.remove(5);
+        document.insert(document.lines().len(), "12:00");
+        assert_eq!(
+            document.to_string(),
+            "# comment\r\n* Mo. 15.04.\r\n  09:03 BB\r\n10:00 CC\r\n12:00\r\n* Di. 16.04.\r\n09:00 AA\r\n12:00\r\n"
+        );
+
+        document.remove(document.lines().len() - 1);
+        assert_eq!(
+            document.to_string(),
+            "# comment\r\n* Mo. 15.04.\r\n  09:03 BB\r\n10:00 CC\r\n12:00\r\n* Di. 16.04.\r\n09:00 AA\r\n"
+        );
+    }
+}
@@ -0,0 +1,109 @@
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// The temporary file next to `path` that is renamed over it, e.g. `.2024-04.tsh.tmp`.
+fn temp_path(path: &Path) -> PathBuf {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    path.with_file_name(format!(".{name}.tmp"))
+}
+
+fn replace(path: &Path, contents: &[u8]) -> io::Result<()> {
+    // Renaming over a symlink would replace the link instead of the file it points to.
+    let path = &fs::canonicalize(path).unwrap_or_else(|_| path.to_owned());
+    let temp = temp_path(path);
+    let result = (|| {
+        let mut file = File::create(&temp)?;
+        file.write_all(contents)?;
+        if let Ok(metadata) = fs::metadata(path) {
+            file.set_permissions(metadata.permissions())?;
+        }
+        file.sync_all()?;
+        fs::rename(&temp, path)
+    })();
+    if result.is_err() {
+        fs::remove_file(&temp).ok();
+    }
+    result?;
+    // The rename is only durable once the directory is synced, which is not possible on Windows.
+    #[cfg(unix)]
+    if let Some(dir) = path.parent() {
+        let dir = if dir.as_os_str().is_empty() {
+            Path::new(".")
+        } else {
+            dir
+        };
+        File::open(dir)?.sync_all()?;
+    }
+    Ok(())
+}
+
+fn with_path(path: &Path, e: &io::Error) -> io::Error {
+    io::Error::new(e.kind(), format!("failed to write {}: {e}", path.display()))
+}
+
+/// Replaces the contents of `path` by writing a temporary file next to it and renaming it over
+/// `path`, so that a crash leaves either the old or the new contents but never a mix.
+pub fn write(path: &Path, contents: impl AsRef<[u8]>) -> io::Result<()> {
+    replace(path, contents.as_ref()).map_err(|e| with_path(path, &e))
+}
+
+/// Appends `text` to the file like [`write`] and returns the offset it was written at.
+pub fn append(path: &Path, text: &str) -> io::Result<usize> {
+    let mut contents = fs::read(path).map_err(|e| with_path(path, &e))?;
+    let offset = contents.len();
+    contents.extend_from_slice(text.as_bytes());
+    write(path, contents)?;
+    Ok(offset)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_and_append() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("2024-04.tsh");
+        write(&file, "* Mo. 15.04.\n").unwrap();
+        assert_eq!(append(&file, "09:00 AA\n").unwrap(), 13);
+        assert_eq!(
+            fs::read_to_string(&file).unwrap(),
+            "* Mo. 15.04.\n09:00 AA\n"
+        );
+        assert!(!temp_path(&file).exists());
+
+        let missing = dir.path().join("missing").join("2024-05.tsh");
+        let error = write(&missing, "").unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::NotFound);
+        assert!(error.to_string().contains("2024-05.tsh"));
+        assert!(append(&missing, "").is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("2024-04.tsh");
+        fs::write(&file, "").unwrap();
+        fs::set_permissions(&file, fs::Permissions::from_mode(0o600)).unwrap();
+        write(&file, "* Mo. 15.04.\n").unwrap();
+        let mode = fs::metadata(&file).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_symlink() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("2024-04.tsh");
+        let link = dir.path().join("link.tsh");
+        fs::write(&file, "").unwrap();
+        std::os::unix::fs::symlink(&file, &link).unwrap();
+        append(&link, "* Mo. 15.04.\n").unwrap();
+        assert!(fs::symlink_metadata(&link).unwrap().is_symlink());
+        assert_eq!(fs::read_to_string(&file).unwrap(), "* Mo. 15.04.\n");
+    }
+}
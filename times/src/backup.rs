@@ -0,0 +1,255 @@
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+use chrono::{NaiveDateTime, TimeDelta};
+use thiserror::Error;
+
+/// Name of the directory next to the files that holds their snapshots.
+pub const DIR: &str = ".backups";
+
+const TIME_FORMAT: &str = "%Y-%m-%dT%H-%M-%S%.3f";
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("Failed to access {path}: {1}", path = .0.display())]
+    Io(PathBuf, std::io::Error),
+    #[error("There is no snapshot {0}")]
+    NotFound(String),
+}
+
+/// How many snapshots are kept per file.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Options {
+    /// The newest snapshots that are kept.
+    pub keep: usize,
+    /// Snapshots older than this many days are removed, even if they are among the newest.
+    pub keep_days: Option<u32>,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            keep: 50,
+            keep_days: None,
+        }
+    }
+}
+
+/// A copy of a file at `time`.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Snapshot {
+    pub path: PathBuf,
+    pub time: NaiveDateTime,
+}
+
+/// The snapshot directory of `file`, e.g. `a/.backups` for `a/2024-04.tsh`.
+#[must_use]
+pub fn backup_dir(file: &Path) -> PathBuf {
+    file.with_file_name(DIR)
+}
+
+fn snapshot_path(file: &Path, time: NaiveDateTime) -> PathBuf {
+    let name = file.file_name().unwrap_or_default().to_string_lossy();
+    backup_dir(file).join(format!("{name}.{}", time.format(TIME_FORMAT)))
+}
+
+/// The time of the snapshot `name` of the file `file_name`, `None` for other files.
+fn parse_name(file_name: &str, name: &str) -> Option<NaiveDateTime> {
+    let time = name.strip_prefix(file_name)?.strip_prefix('.')?;
+    NaiveDateTime::parse_from_str(time, TIME_FORMAT).ok()
+}
+
+/// The snapshots of `file`, oldest first.
+pub fn snapshots(file: &Path) -> Result<Vec<Snapshot>, Error> {
+    let dir = backup_dir(file);
+    let entries = match fs::read_dir(&dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(Error::Io(dir, e)),
+    };
+    let file_name = file.file_name().unwrap_or_default().to_string_lossy();
+    let mut snapshots = Vec::new();
+    for entry in entries {
+        let path = entry.map_err(|e| Error::Io(dir.clone(), e))?.path();
+        let time = path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .and_then(|n| parse_name(&file_name, n));
+        if let Some(time) = time {
+            snapshots.push(Snapshot { path, time });
+        }
+    }
+    snapshots.sort_by_key(|s| s.time);
+    Ok(snapshots)
+}
+
+/// The snapshots that `options` do not keep at `now`, `snapshots` are ordered oldest first.
+#[must_use]
+pub fn expired<'a>(
+    snapshots: &'a [Snapshot],
+    now: NaiveDateTime,
+    options: &Options,
+) -> Vec<&'a Snapshot> {
+    let excess = snapshots.len().saturating_sub(options.keep);
+    let oldest = options
+        .keep_days
+        .map(|days| now - TimeDelta::days(days.into()));
+    snapshots
+        .iter()
+        .enumerate()
+        .filter(|(i, s)| *i < excess || oldest.is_some_and(|o| s.time < o))
+        .map(|(_, s)| s)
+        .collect()
+}
+
+/// Copies `file` into its snapshot directory and removes the snapshots that are no longer kept.
+/// Nothing is copied if the file does not exist or is unchanged since the last snapshot.
+pub fn snapshot(
+    file: &Path,
+    now: NaiveDateTime,
+    options: &Options,
+) -> Result<Option<PathBuf>, Error> {
+    let contents = match fs::read(file) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(Error::Io(file.to_owned(), e)),
+    };
+    let mut snapshots = snapshots(file)?;
+    let unchanged = match snapshots.last() {
+        Some(last) => fs::read(&last.path).is_ok_and(|c| c == contents),
+        None => false,
+    };
+    let path = if unchanged {
+        None
+    } else {
+        let dir = backup_dir(file);
+        fs::create_dir_all(&dir).map_err(|e| Error::Io(dir, e))?;
+        let path = snapshot_path(file, now);
+        crate::atomic::write(&path, contents).map_err(|e| Error::Io(path.clone(), e))?;
+        snapshots.push(Snapshot {
+            path: path.clone(),
+            time: now,
+        });
+        Some(path)
+    };
+    for expired in expired(&snapshots, now, options) {
+        fs::remove_file(&expired.path).map_err(|e| Error::Io(expired.path.clone(), e))?;
+    }
+    Ok(path)
+}
+
+/// The snapshot of `file` selected by `selector`, either its number counting from 1 for the
+/// newest or the file name of the snapshot.
+pub fn find(file: &Path, selector: &str) -> Result<Snapshot, Error> {
+    let mut snapshots = snapshots(file)?;
+    snapshots.reverse();
+    let found = match selector.parse::<usize>() {
+        Ok(number) => number.checked_sub(1).and_then(|i| snapshots.get(i)),
+        Err(_) => snapshots
+            .iter()
+            .find(|s| s.path.file_name().is_some_and(|n| n == selector)),
+    };
+    found
+        .cloned()
+        .ok_or_else(|| Error::NotFound(selector.to_owned()))
+}
+
+/// Replaces `file` with `snapshot`, after taking a snapshot of the current contents.
+pub fn restore(
+    file: &Path,
+    snapshot: &Snapshot,
+    now: NaiveDateTime,
+    options: &Options,
+) -> Result<(), Error> {
+    let contents = fs::read(&snapshot.path).map_err(|e| Error::Io(snapshot.path.clone(), e))?;
+    self::snapshot(file, now, options)?;
+    crate::atomic::write(file, contents).map_err(|e| Error::Io(file.to_owned(), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use super::*;
+
+    fn time(day: u32, hour: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2024, 4, day)
+            .unwrap()
+            .and_hms_milli_opt(hour, 0, 0, 5)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_names() {
+        let path = snapshot_path(Path::new("a/2024-04.tsh"), time(15, 9));
+        assert_eq!(
+            path,
+            PathBuf::from("a/.backups/2024-04.tsh.2024-04-15T09-00-00.005")
+        );
+        let name = path.file_name().unwrap().to_str().unwrap();
+        assert_eq!(parse_name("2024-04.tsh", name), Some(time(15, 9)));
+        assert_eq!(parse_name("2024-05.tsh", name), None);
+        assert_eq!(parse_name("2024-04.tsh", "2024-04.tsh.journal"), None);
+    }
+
+    #[test]
+    fn test_expired() {
+        let snapshots: Vec<_> = (10..15)
+            .map(|day| Snapshot {
+                path: PathBuf::from(day.to_string()),
+                time: time(day, 12),
+            })
+            .collect();
+        let times = |options: &Options| {
+            expired(&snapshots, time(15, 0), options)
+                .iter()
+                .map(|s| s.time)
+                .collect::<Vec<_>>()
+        };
+        assert!(times(&Options::default()).is_empty());
+        let keep = Options {
+            keep: 3,
+            keep_days: None,
+        };
+        assert_eq!(times(&keep), [time(10, 12), time(11, 12)]);
+        let days = Options {
+            keep: 10,
+            keep_days: Some(2),
+        };
+        assert_eq!(times(&days), [time(10, 12), time(11, 12), time(12, 12)]);
+    }
+
+    #[test]
+    fn test_snapshot_and_restore() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("2024-04.tsh");
+        let options = Options {
+            keep: 2,
+            keep_days: None,
+        };
+        assert_eq!(snapshot(&file, time(15, 9), &options).unwrap(), None);
+
+        fs::write(&file, "a").unwrap();
+        assert!(snapshot(&file, time(15, 9), &options).unwrap().is_some());
+        assert_eq!(snapshot(&file, time(15, 10), &options).unwrap(), None);
+        fs::write(&file, "b").unwrap();
+        snapshot(&file, time(15, 11), &options).unwrap();
+        fs::write(&file, "c").unwrap();
+        snapshot(&file, time(15, 12), &options).unwrap();
+        let times: Vec<_> = snapshots(&file).unwrap().iter().map(|s| s.time).collect();
+        assert_eq!(times, [time(15, 11), time(15, 12)]);
+
+        fs::write(&file, "d").unwrap();
+        let oldest = find(&file, "2").unwrap();
+        assert_eq!(oldest.time, time(15, 11));
+        let name = oldest.path.file_name().unwrap().to_str().unwrap();
+        assert_eq!(find(&file, name).unwrap(), oldest);
+        assert!(matches!(find(&file, "3"), Err(Error::NotFound(_))));
+        assert!(matches!(find(&file, "0"), Err(Error::NotFound(_))));
+        restore(&file, &oldest, time(15, 13), &options).unwrap();
+        assert_eq!(fs::read_to_string(&file).unwrap(), "b");
+        let newest = find(&file, "1").unwrap();
+        assert_eq!(fs::read_to_string(newest.path).unwrap(), "d");
+    }
+}
@@ -0,0 +1,132 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::time::SystemTime;
+
+use serde::{Deserialize, Serialize};
+
+use crate::convert::Month;
+use crate::index::Index;
+
+/// Name of the cache file in the directory of the timesheet files.
+pub const FILE: &str = ".timesheet-cache";
+
+#[derive(Serialize, Deserialize)]
+struct Entry {
+    modified: SystemTime,
+    /// The options the month was converted with.
+    options: u64,
+    month: Month,
+}
+
+/// Converted months by file name, each valid as long as its file is not modified, and the
+/// [`Index`] of all of them.
+#[derive(Default, Serialize, Deserialize)]
+pub struct DiskCache {
+    version: String,
+    entries: BTreeMap<String, Entry>,
+    index: Index,
+    #[serde(skip)]
+    changed: bool,
+}
+
+impl DiskCache {
+    /// Reads the cache of `dir`, a missing, unreadable or outdated cache is empty.
+    #[must_use]
+    pub fn read(dir: &Path) -> Self {
+        let cache = std::fs::read(dir.join(FILE))
+            .ok()
+            .and_then(|bytes| serde_json::from_slice::<Self>(&bytes).ok())
+            .filter(|cache| cache.version == env!("CARGO_PKG_VERSION"));
+        debug!(dir = %dir.display(), entries = cache.as_ref().map(|c| c.entries.len()), "read cache");
+        cache.unwrap_or_default()
+    }
+
+    #[must_use]
+    pub fn get(&self, name: &str, modified: SystemTime, options: u64) -> Option<&Month> {
+        self.entries
+            .get(name)
+            .filter(|e| e.modified == modified && e.options == options)
+            .map(|e| &e.month)
+    }
+
+    /// Names of the files with a cached month.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.entries.keys().map(String::as_str)
+    }
+
+    /// The usage of identifiers and comments in the cached months as of the last write.
+    #[must_use]
+    pub fn index(&self) -> &Index {
+        &self.index
+    }
+
+    pub fn insert(&mut self, name: String, modified: SystemTime, options: u64, month: Month) {
+        self.entries.insert(
+            name,
+            Entry {
+                modified,
+                options,
+                month,
+            },
+        );
+        self.changed = true;
+    }
+
+    /// Writes the cache to `dir` if it changed, dropping the months of deleted files.
+    pub fn write(mut self, dir: &Path) -> std::io::Result<()> {
+        if !self.changed {
+            return Ok(());
+        }
+        env!("CARGO_PKG_VERSION").clone_into(&mut self.version);
+        self.entries.retain(|name, _| dir.join(name).is_file());
+        self.index = Index::default();
+        for entry in self.entries.values() {
+            self.index.add_days(&entry.month.days);
+        }
+        let bytes = serde_json::to_vec(&self).map_err(std::io::Error::other)?;
+        debug!(dir = %dir.display(), entries = self.entries.len(), "writing cache");
+        crate::atomic::write(&dir.join(FILE), bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use crate::convert::Options;
+    use crate::parse::parse_month;
+    use crate::{Date, Minutes};
+
+    use super::*;
+
+    #[test]
+    fn test_roundtrip() {
+        let date = Date::new(NaiveDate::from_ymd_opt(2024, 4, 1).unwrap());
+        let text = "#! hours_per_week: 30\n* Mo. 15.04.\n09:00 AA Foo\n12:00\n";
+        let month = Month::convert(
+            parse_month(text.as_bytes(), date).unwrap(),
+            &Options::default(),
+        )
+        .unwrap();
+        let modified = SystemTime::UNIX_EPOCH;
+        let mut cache = DiskCache::default();
+        cache.insert("2024-04.tsh".to_owned(), modified, 1, month);
+        let cache: DiskCache =
+            serde_json::from_slice(&serde_json::to_vec(&cache).unwrap()).unwrap();
+        let cached = cache.get("2024-04.tsh", modified, 1).unwrap();
+        assert_eq!(cached.meta.hours_per_week, Some(Minutes::from_hours(30)));
+        assert_eq!(
+            cached.days[0].entries[0].value.comment.as_deref(),
+            Some("Foo")
+        );
+        assert_eq!(cached.times().billable_time(), Minutes::from_hours(3));
+        assert!(cache.get("2024-04.tsh", modified, 2).is_none());
+        assert!(cache
+            .get(
+                "2024-04.tsh",
+                modified + std::time::Duration::from_secs(1),
+                1
+            )
+            .is_none());
+    }
+}
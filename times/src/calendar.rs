@@ -0,0 +1,502 @@
+use std::collections::{HashMap, HashSet};
+use std::io::BufRead;
+
+use chrono::{
+    Datelike, Days, Local, NaiveDate, NaiveDateTime, TimeDelta, TimeZone, Timelike, Weekday,
+};
+use chrono_tz::Tz;
+use ical::parser::ical::component::IcalEvent;
+use ical::property::Property;
+use thiserror::Error;
+
+use crate::Time;
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("Failed to read calendar: {0}")]
+    Ical(#[from] ical::parser::ParserError),
+    #[error("Invalid {0} {1:?}")]
+    Invalid(&'static str, String),
+    #[error("Event without {0}")]
+    Missing(&'static str),
+}
+
+/// A meeting that starts and ends on the same day.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Event {
+    pub date: NaiveDate,
+    pub start: Time,
+    pub end: Time,
+    pub summary: Option<String>,
+}
+
+/// Which events of a calendar are meetings to import.
+#[derive(Debug, Clone)]
+pub struct Options {
+    /// Events this attendee declined are skipped.
+    pub email: Option<String>,
+    /// Events before this day are skipped.
+    pub since: Option<NaiveDate>,
+    /// Events ending later are skipped, recurring events are expanded up to here.
+    pub until: NaiveDateTime,
+}
+
+fn property<'a>(event: &'a IcalEvent, name: &str) -> Option<&'a Property> {
+    event.properties.iter().find(|p| p.name == name)
+}
+
+fn value<'a>(event: &'a IcalEvent, name: &str) -> Option<&'a str> {
+    property(event, name)?.value.as_deref()
+}
+
+fn param<'a>(property: &'a Property, name: &str) -> Option<&'a str> {
+    property
+        .params
+        .as_ref()?
+        .iter()
+        .find(|(n, _)| n.eq_ignore_ascii_case(name))?
+        .1
+        .first()
+        .map(String::as_str)
+}
+
+/// A date-time like `20240415T093000` as local time, converted from UTC with a trailing `Z` or
+/// from the IANA time zone `tzid`. Times with neither are local already. `None` for dates of
+/// all-day events.
+fn date_time(value: &str, tzid: Option<&str>) -> Result<Option<NaiveDateTime>, Error> {
+    let invalid = || Error::Invalid("date", value.to_owned());
+    if value.len() == 8 {
+        NaiveDate::parse_from_str(value, "%Y%m%d").map_err(|_| invalid())?;
+        return Ok(None);
+    }
+    let (value, zone) = match value.strip_suffix('Z') {
+        Some(utc) => (utc, Some(Tz::UTC)),
+        None => (value, tzid.map(time_zone).transpose()?),
+    };
+    let time = NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S").map_err(|_| invalid())?;
+    let Some(zone) = zone else {
+        return Ok(Some(time));
+    };
+    // Times skipped by a daylight saving time change do not exist.
+    let time = zone
+        .from_local_datetime(&time)
+        .earliest()
+        .ok_or_else(invalid)?;
+    Ok(Some(time.with_timezone(&Local).naive_local()))
+}
+
+fn time_zone(tzid: &str) -> Result<Tz, Error> {
+    tzid.parse()
+        .map_err(|_| Error::Invalid("time zone", tzid.to_owned()))
+}
+
+/// The date-time of `property` in the time zone of its `TZID`, see [`date_time`].
+fn property_date_time(property: &Property) -> Result<Option<NaiveDateTime>, Error> {
+    let value = property.value.as_deref().unwrap_or_default();
+    date_time(value, param(property, "TZID"))
+}
+
+/// A duration like `PT1H30M` or `P1D`.
+fn duration(value: &str) -> Result<TimeDelta, Error> {
+    let invalid = || Error::Invalid("duration", value.to_owned());
+    let rest = value.strip_prefix('P').ok_or_else(invalid)?;
+    let mut seconds = 0;
+    let mut number = String::new();
+    for c in rest.chars() {
+        let unit = match c {
+            '0'..='9' => {
+                number.push(c);
+                continue;
+            }
+            'T' if number.is_empty() => continue,
+            'W' => 7 * 24 * 3600,
+            'D' => 24 * 3600,
+            'H' => 3600,
+            'M' => 60,
+            'S' => 1,
+            _ => return Err(invalid()),
+        };
+        seconds += number.parse::<i64>().map_err(|_| invalid())? * unit;
+        number.clear();
+    }
+    if !number.is_empty() {
+        return Err(invalid());
+    }
+    TimeDelta::try_seconds(seconds).ok_or_else(invalid)
+}
+
+#[derive(Debug, Default)]
+struct Rule {
+    daily: bool,
+    interval: u32,
+    count: Option<usize>,
+    until: Option<NaiveDateTime>,
+    weekdays: Vec<Weekday>,
+}
+
+impl Rule {
+    /// Only daily and weekly rules are expanded, all others are `None`.
+    fn parse(value: &str) -> Result<Option<Self>, Error> {
+        let invalid = || Error::Invalid("recurrence rule", value.to_owned());
+        let mut rule = Rule {
+            interval: 1,
+            ..Rule::default()
+        };
+        for part in value.split(';') {
+            let (name, value) = part.split_once('=').ok_or_else(invalid)?;
+            match name {
+                "FREQ" if value == "DAILY" => rule.daily = true,
+                "FREQ" if value != "WEEKLY" => return Ok(None),
+                "INTERVAL" => rule.interval = value.parse().map_err(|_| invalid())?,
+                "COUNT" => rule.count = Some(value.parse().map_err(|_| invalid())?),
+                // The end of the last day, since all-day values have no time.
+                "UNTIL" => {
+                    rule.until = Some(date_time(value, None)?.unwrap_or_else(|| {
+                        let date = NaiveDate::parse_from_str(value, "%Y%m%d").unwrap();
+                        date.and_hms_opt(23, 59, 59).unwrap()
+                    }));
+                }
+                "BYDAY" => {
+                    rule.weekdays = value
+                        .split(',')
+                        .map(|d| weekday(d).ok_or_else(invalid))
+                        .collect::<Result<_, _>>()?;
+                }
+                "BYMONTH" | "BYMONTHDAY" | "BYSETPOS" | "BYYEARDAY" | "BYWEEKNO" => {
+                    return Ok(None)
+                }
+                _ => {}
+            }
+        }
+        Ok(Some(rule))
+    }
+
+    /// The starts of all occurrences up to `limit`.
+    fn occurrences(&self, start: NaiveDateTime, limit: NaiveDateTime) -> Vec<NaiveDateTime> {
+        let limit = self.until.map_or(limit, |u| u.min(limit));
+        let step = u64::from(self.interval.max(1));
+        let weekdays = if self.weekdays.is_empty() {
+            vec![start.weekday()]
+        } else {
+            self.weekdays.clone()
+        };
+        let mut occurrences = Vec::new();
+        // Days of the daily rule or Mondays of the weekly rule.
+        let mut period = if self.daily {
+            start.date()
+        } else {
+            start.date().week(Weekday::Mon).first_day()
+        };
+        while period.and_time(start.time()) <= limit {
+            let mut dates: Vec<_> = if self.daily {
+                let by_day = self.weekdays.is_empty() || self.weekdays.contains(&period.weekday());
+                by_day.then_some(period).into_iter().collect()
+            } else {
+                weekdays
+                    .iter()
+                    .map(|d| period + Days::new(u64::from(d.num_days_from_monday())))
+                    .collect()
+            };
+            dates.sort();
+            for date in dates {
+                let occurrence = date.and_time(start.time());
+                if occurrence < start || occurrence > limit {
+                    continue;
+                }
+                if self.count.is_some_and(|c| occurrences.len() >= c) {
+                    return occurrences;
+                }
+                occurrences.push(occurrence);
+            }
+            let days = if self.daily { step } else { 7 * step };
+            let Some(next) = period.checked_add_days(Days::new(days)) else {
+                break;
+            };
+            period = next;
+        }
+        occurrences
+    }
+}
+
+fn weekday(value: &str) -> Option<Weekday> {
+    Some(match value {
+        "MO" => Weekday::Mon,
+        "TU" => Weekday::Tue,
+        "WE" => Weekday::Wed,
+        "TH" => Weekday::Thu,
+        "FR" => Weekday::Fri,
+        "SA" => Weekday::Sat,
+        "SU" => Weekday::Sun,
+        _ => return None,
+    })
+}
+
+/// Private, cancelled and declined events are no meetings to book.
+fn is_skipped(event: &IcalEvent, options: &Options) -> bool {
+    let private = value(event, "CLASS").is_some_and(|c| c == "PRIVATE" || c == "CONFIDENTIAL");
+    let cancelled = value(event, "STATUS") == Some("CANCELLED");
+    let declined = options.email.as_deref().is_some_and(|email| {
+        event.properties.iter().any(|p| {
+            p.name == "ATTENDEE"
+                && param(p, "PARTSTAT") == Some("DECLINED")
+                && p.value.as_deref().is_some_and(|v| {
+                    let address = v.strip_prefix("mailto:").unwrap_or(v);
+                    address.eq_ignore_ascii_case(email)
+                })
+        })
+    });
+    private || cancelled || declined
+}
+
+fn dates(event: &IcalEvent, name: &str) -> Result<HashSet<NaiveDateTime>, Error> {
+    let mut dates = HashSet::new();
+    for property in event.properties.iter().filter(|p| p.name == name) {
+        for value in property.value.as_deref().unwrap_or_default().split(',') {
+            dates.extend(date_time(value, param(property, "TZID"))?);
+        }
+    }
+    Ok(dates)
+}
+
+/// The start of an event and its length, `None` for all-day events.
+fn span(event: &IcalEvent) -> Result<Option<(NaiveDateTime, TimeDelta)>, Error> {
+    let start = property(event, "DTSTART").ok_or(Error::Missing("DTSTART"))?;
+    let Some(start) = property_date_time(start)? else {
+        return Ok(None);
+    };
+    let length = match (property(event, "DTEND"), value(event, "DURATION")) {
+        (Some(end), _) => match property_date_time(end)? {
+            Some(end) => end - start,
+            None => return Ok(None),
+        },
+        (None, Some(d)) => duration(d)?,
+        (None, None) => TimeDelta::zero(),
+    };
+    Ok(Some((start, length)))
+}
+
+fn time(date_time: NaiveDateTime) -> Time {
+    Time::new(
+        u8::try_from(date_time.hour()).unwrap(),
+        u8::try_from(date_time.minute()).unwrap(),
+    )
+    .unwrap()
+}
+
+/// The meetings of an iCalendar file in order. Recurring events are expanded for daily and
+/// weekly rules with their exceptions, other rules only give their first occurrence. All-day
+/// and multi-day events are skipped. Times are converted to the local time zone, in which
+/// recurring events are expanded.
+pub fn events(reader: impl BufRead, options: &Options) -> Result<Vec<Event>, Error> {
+    let mut events = Vec::new();
+    for calendar in ical::IcalParser::new(reader) {
+        events.extend(calendar?.events);
+    }
+    // Occurrences of recurring events that were moved or changed are separate events.
+    let mut moved: HashMap<&str, HashSet<NaiveDateTime>> = HashMap::new();
+    for event in &events {
+        if let (Some(uid), Some(id)) = (value(event, "UID"), property(event, "RECURRENCE-ID")) {
+            moved
+                .entry(uid)
+                .or_default()
+                .extend(property_date_time(id)?);
+        }
+    }
+    let mut meetings = Vec::new();
+    for event in &events {
+        let Some((start, length)) = span(event)? else {
+            continue;
+        };
+        if is_skipped(event, options) {
+            continue;
+        }
+        let rule = match value(event, "RRULE") {
+            Some(rule) if property(event, "RECURRENCE-ID").is_none() => Rule::parse(rule)?,
+            _ => None,
+        };
+        let mut starts = rule.map_or(vec![start], |r| r.occurrences(start, options.until));
+        let excluded = dates(event, "EXDATE")?;
+        let moved = value(event, "UID").and_then(|uid| moved.get(uid));
+        if property(event, "RECURRENCE-ID").is_none() {
+            starts.retain(|s| !excluded.contains(s) && moved.is_none_or(|m| !m.contains(s)));
+        }
+        let summary = value(event, "SUMMARY").and_then(|s| {
+            let s = s.split_whitespace().collect::<Vec<_>>().join(" ");
+            (!s.is_empty()).then_some(s)
+        });
+        for start in starts {
+            let end = start + length;
+            let in_range = end <= options.until && options.since.is_none_or(|s| start.date() >= s);
+            if !in_range || end.date() != start.date() || end <= start {
+                continue;
+            }
+            meetings.push(Event {
+                date: start.date(),
+                start: time(start),
+                end: time(end),
+                summary: summary.clone(),
+            });
+        }
+    }
+    meetings.sort_by_key(|e| (e.date, e.start));
+    Ok(meetings)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+
+    use super::*;
+
+    const CALENDAR: &str = "BEGIN:VCALENDAR\r
+VERSION:2.0\r
+BEGIN:VEVENT\r
+UID:daily\r
+DTSTART:20240415T093000\r
+DTEND:20240415T094500\r
+RRULE:FREQ=WEEKLY;BYDAY=MO,WE,FR\r
+EXDATE:20240417T093000\r
+SUMMARY:Daily\r
+END:VEVENT\r
+BEGIN:VEVENT\r
+UID:daily\r
+RECURRENCE-ID:20240419T093000\r
+DTSTART:20240419T100000\r
+DURATION:PT30M\r
+SUMMARY:Daily\r
+END:VEVENT\r
+BEGIN:VEVENT\r
+UID:review\r
+DTSTART:20240416T140000\r
+DTEND:20240416T153000\r
+SUMMARY:Design\r
+  review\r
+END:VEVENT\r
+BEGIN:VEVENT\r
+UID:private\r
+CLASS:PRIVATE\r
+DTSTART:20240416T120000\r
+DTEND:20240416T130000\r
+END:VEVENT\r
+BEGIN:VEVENT\r
+UID:declined\r
+DTSTART:20240416T160000\r
+DTEND:20240416T170000\r
+ATTENDEE;PARTSTAT=DECLINED:mailto:Me@example.com\r
+END:VEVENT\r
+BEGIN:VEVENT\r
+UID:holiday\r
+DTSTART;VALUE=DATE:20240418\r
+DTEND;VALUE=DATE:20240419\r
+END:VEVENT\r
+END:VCALENDAR\r
+";
+
+    fn options() -> Options {
+        Options {
+            email: Some("me@example.com".to_owned()),
+            since: NaiveDate::from_ymd_opt(2024, 4, 16),
+            until: NaiveDate::from_ymd_opt(2024, 4, 22)
+                .unwrap()
+                .and_hms_opt(9, 40, 0)
+                .unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_events() {
+        let events = events(CALENDAR.as_bytes(), &options()).unwrap();
+        let summary: Vec<_> = events
+            .iter()
+            .map(|e| {
+                format!(
+                    "{} {}-{} {}",
+                    e.date,
+                    e.start,
+                    e.end,
+                    e.summary.as_deref().unwrap_or_default()
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                "2024-04-16 14:00-15:30 Design review",
+                "2024-04-19 10:00-10:30 Daily",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_rule() {
+        let start = NaiveDate::from_ymd_opt(2024, 4, 1)
+            .unwrap()
+            .and_hms_opt(9, 0, 0)
+            .unwrap();
+        let limit = start + TimeDelta::days(30);
+        let rule = Rule::parse("FREQ=DAILY;INTERVAL=2;COUNT=3")
+            .unwrap()
+            .unwrap();
+        let days: Vec<_> = rule
+            .occurrences(start, limit)
+            .iter()
+            .map(NaiveDateTime::day)
+            .collect();
+        assert_eq!(days, [1, 3, 5]);
+
+        let rule = Rule::parse("FREQ=DAILY;BYDAY=MO,FR;COUNT=3")
+            .unwrap()
+            .unwrap();
+        let days: Vec<_> = rule
+            .occurrences(start, limit)
+            .iter()
+            .map(NaiveDateTime::day)
+            .collect();
+        assert_eq!(days, [1, 5, 8]);
+
+        let rule = Rule::parse("FREQ=WEEKLY;INTERVAL=2;BYDAY=TU,MO;UNTIL=20240416")
+            .unwrap()
+            .unwrap();
+        let days: Vec<_> = rule
+            .occurrences(start, limit)
+            .iter()
+            .map(NaiveDateTime::day)
+            .collect();
+        assert_eq!(days, [1, 2, 15, 16]);
+
+        assert!(Rule::parse("FREQ=MONTHLY").unwrap().is_none());
+        assert!(Rule::parse("FREQ").is_err());
+        assert_eq!(duration("PT1H30M").unwrap(), TimeDelta::minutes(90));
+        assert_eq!(duration("P1DT1S").unwrap(), TimeDelta::seconds(86401));
+        assert!(duration("PT1X").is_err());
+    }
+
+    #[test]
+    fn test_date_time() {
+        let local = |hour| {
+            Utc.with_ymd_and_hms(2024, 4, 15, hour, 30, 0)
+                .unwrap()
+                .with_timezone(&Local)
+                .naive_local()
+        };
+        let date_time = |value, tzid| date_time(value, tzid).unwrap();
+        assert_eq!(date_time("20240415T093000Z", None), Some(local(9)));
+        assert_eq!(
+            date_time("20240415T093000", Some("Europe/Berlin")),
+            Some(local(7))
+        );
+        assert_eq!(
+            date_time("20240415T093000", Some("America/New_York")),
+            Some(local(13))
+        );
+        assert_eq!(
+            date_time("20240415T093000", None),
+            NaiveDate::from_ymd_opt(2024, 4, 15)
+                .unwrap()
+                .and_hms_opt(9, 30, 0)
+        );
+        assert_eq!(date_time("20240415", Some("Europe/Berlin")), None);
+        assert!(super::date_time("20240415T093000", Some("Mars/Olympus")).is_err());
+        assert!(super::date_time("20240331T023000", Some("Europe/Berlin")).is_err());
+    }
+}
@@ -0,0 +1,275 @@
+//! C bindings for parsing a month with the same rules as the command line, enabled by the
+//! `capi` feature. Build a library with
+//! `cargo rustc -p times --release --features capi --crate-type cdylib` and include
+//! `include/times.h`.
+
+use std::ffi::{c_char, CStr, CString};
+use std::ptr;
+
+use chrono::NaiveDate;
+
+use crate::config::Config;
+use crate::convert::{Month, Options};
+use crate::parse::{parse_bytes, Error};
+use crate::{Date, Minutes, Positioned};
+
+/// The totals of a day in minutes.
+#[repr(C)]
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq)]
+pub struct TimesDay {
+    pub year: i32,
+    pub month: u32,
+    pub day: u32,
+    pub expected: u64,
+    pub work: u64,
+    pub travel: u64,
+    pub billable: u64,
+    pub under_hours: u64,
+}
+
+/// A parsed month with either its days or the errors that prevented converting it.
+pub struct TimesMonth {
+    days: Vec<TimesDay>,
+    errors: Vec<Positioned<CString>>,
+}
+
+fn error(line: usize, message: &impl ToString) -> Positioned<CString> {
+    let message = message.to_string().replace('\0', " ");
+    Positioned::new(line, CString::new(message).unwrap())
+}
+
+fn options(config: Option<&str>) -> Result<Options, Positioned<CString>> {
+    let Some(config) = config else {
+        return Ok(Options::default());
+    };
+    let config: Config = config.parse().map_err(|e| error(0, &e))?;
+    Ok(config.convert_options())
+}
+
+fn minutes(minutes: Minutes) -> u64 {
+    minutes.into_inner() as u64
+}
+
+fn convert(
+    bytes: &[u8],
+    month: Date,
+    config: Option<&str>,
+) -> Result<Month, Vec<Positioned<CString>>> {
+    let options = options(config).map_err(|e| vec![e])?;
+    let parsed = parse_bytes(bytes, month).map_err(|e| match e {
+        Error::Many(errors) => errors
+            .0
+            .into_iter()
+            .map(|e| error(e.line, &e.value))
+            .collect(),
+        Error::ExpectedDay(line) => vec![error(line, &e)],
+        Error::Io(_) => vec![error(0, &e)],
+    })?;
+    Month::convert(parsed, &options).map_err(|e| vec![error(e.line(), &e)])
+}
+
+impl TimesMonth {
+    fn new(result: Result<Month, Vec<Positioned<CString>>>) -> Self {
+        match result {
+            Ok(month) => Self {
+                days: month
+                    .days
+                    .iter()
+                    .map(|day| {
+                        let date = day.date.value;
+                        let times = &day.times;
+                        TimesDay {
+                            year: date.year(),
+                            month: date.month(),
+                            day: date.day(),
+                            expected: minutes(month.expected_time(day)),
+                            work: minutes(times.work_time()),
+                            travel: minutes(times.travel_time()),
+                            billable: minutes(times.billable_time()),
+                            under_hours: minutes(times.under_hours()),
+                        }
+                    })
+                    .collect(),
+                errors: Vec::new(),
+            },
+            Err(errors) => Self {
+                days: Vec::new(),
+                errors,
+            },
+        }
+    }
+}
+
+/// Parses and converts the file of `month` in `year` with the rules of the TOML `config`, or
+/// the defaults if it is null. Returns null for an invalid month, the result must be freed
+/// with [`times_free`].
+///
+/// # Safety
+///
+/// `data` must point to `len` readable bytes and `config` must be null or a nul-terminated
+/// string.
+#[no_mangle]
+pub unsafe extern "C" fn times_parse(
+    data: *const u8,
+    len: usize,
+    year: i32,
+    month: u32,
+    config: *const c_char,
+) -> *mut TimesMonth {
+    let Some(month) = NaiveDate::from_ymd_opt(year, month, 1) else {
+        return ptr::null_mut();
+    };
+    let bytes = if len == 0 {
+        &[]
+    } else {
+        // SAFETY: the caller guarantees `len` readable bytes.
+        unsafe { std::slice::from_raw_parts(data, len) }
+    };
+    let config = (!config.is_null()).then(|| {
+        // SAFETY: the caller guarantees a nul-terminated string.
+        unsafe { CStr::from_ptr(config) }.to_string_lossy()
+    });
+    let result = convert(bytes, Date::new(month), config.as_deref());
+    Box::into_raw(Box::new(TimesMonth::new(result)))
+}
+
+/// Frees a month returned by [`times_parse`], null is ignored.
+///
+/// # Safety
+///
+/// `month` must be null or returned by [`times_parse`] and not freed before.
+#[no_mangle]
+pub unsafe extern "C" fn times_free(month: *mut TimesMonth) {
+    if !month.is_null() {
+        // SAFETY: the caller guarantees it came from `Box::into_raw` in `times_parse`.
+        drop(unsafe { Box::from_raw(month) });
+    }
+}
+
+/// Number of days, zero if there are errors.
+///
+/// # Safety
+///
+/// `month` must be a live pointer returned by [`times_parse`].
+#[no_mangle]
+pub unsafe extern "C" fn times_day_count(month: *const TimesMonth) -> usize {
+    // SAFETY: the caller guarantees a live month.
+    unsafe { &*month }.days.len()
+}
+
+/// Writes the totals of the day at `index` to `out`, returns false if there is no such day.
+///
+/// # Safety
+///
+/// `month` must be a live pointer returned by [`times_parse`] and `out` must be writable.
+#[no_mangle]
+pub unsafe extern "C" fn times_day(
+    month: *const TimesMonth,
+    index: usize,
+    out: *mut TimesDay,
+) -> bool {
+    // SAFETY: the caller guarantees a live month.
+    let Some(day) = unsafe { &*month }.days.get(index) else {
+        return false;
+    };
+    // SAFETY: the caller guarantees a writable `out`.
+    unsafe { out.write(*day) };
+    true
+}
+
+/// Number of errors, zero if the month could be converted.
+///
+/// # Safety
+///
+/// `month` must be a live pointer returned by [`times_parse`].
+#[no_mangle]
+pub unsafe extern "C" fn times_error_count(month: *const TimesMonth) -> usize {
+    // SAFETY: the caller guarantees a live month.
+    unsafe { &*month }.errors.len()
+}
+
+/// The message of the error at `index`, or null if there is no such error. Its line is written
+/// to `line` unless it is null, 0 for errors not tied to a line. The message lives as long as
+/// `month`.
+///
+/// # Safety
+///
+/// `month` must be a live pointer returned by [`times_parse`] and `line` must be null or
+/// writable.
+#[no_mangle]
+pub unsafe extern "C" fn times_error(
+    month: *const TimesMonth,
+    index: usize,
+    line: *mut usize,
+) -> *const c_char {
+    // SAFETY: the caller guarantees a live month.
+    let Some(error) = unsafe { &*month }.errors.get(index) else {
+        return ptr::null();
+    };
+    if !line.is_null() {
+        // SAFETY: the caller guarantees a writable `line`.
+        unsafe { line.write(error.line) };
+    }
+    error.value.as_ptr()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(text: &str, config: Option<&CStr>) -> *mut TimesMonth {
+        let config = config.map_or(ptr::null(), CStr::as_ptr);
+        unsafe { times_parse(text.as_ptr(), text.len(), 2024, 4, config) }
+    }
+
+    #[test]
+    fn test_days() {
+        let month = parse("* Mo. 15.04.\n09:00 AA\n12:00 TNGFa\n13:00\n", None);
+        unsafe {
+            assert_eq!(times_error_count(month), 0);
+            assert_eq!(times_day_count(month), 1);
+            let mut day = TimesDay::default();
+            assert!(times_day(month, 0, &raw mut day));
+            assert_eq!(
+                day,
+                TimesDay {
+                    year: 2024,
+                    month: 4,
+                    day: 15,
+                    expected: 8 * 60,
+                    work: 3 * 60,
+                    travel: 60,
+                    billable: 3 * 60 + 11,
+                    under_hours: 0,
+                }
+            );
+            assert!(!times_day(month, 1, &raw mut day));
+            times_free(month);
+        }
+        assert!(unsafe { times_parse(ptr::null(), 0, 2024, 13, ptr::null()) }.is_null());
+    }
+
+    #[test]
+    fn test_errors() {
+        let month = parse("* Mo. 15.04.\n9:00 AA\n12:00\n* Di. 17.04.\n", None);
+        unsafe {
+            assert_eq!(times_day_count(month), 0);
+            assert_eq!(times_error_count(month), 2);
+            let mut line = 0;
+            let message = CStr::from_ptr(times_error(month, 0, &raw mut line));
+            assert_eq!(line, 2);
+            assert_eq!(message.to_str().unwrap(), "Invalid time format");
+            assert!(times_error(month, 2, ptr::null_mut()).is_null());
+            times_free(month);
+        }
+
+        let month = parse("* Mo. 15.04.\n09:01 AA\n12:00\n", Some(c"granularity = 15"));
+        unsafe {
+            assert_eq!(times_error_count(month), 1);
+            let mut line = 0;
+            times_error(month, 0, &raw mut line);
+            assert_eq!(line, 2);
+            times_free(month);
+        }
+    }
+}
@@ -0,0 +1,84 @@
+use std::collections::BTreeSet;
+use std::convert::Infallible;
+use std::str::FromStr;
+
+const MAX_SUGGESTION_DISTANCE: usize = 2;
+
+/// Known project identifiers, one per line, `#` starts a comment.
+#[derive(Debug, Default)]
+pub struct Catalog(BTreeSet<String>);
+
+impl FromStr for Catalog {
+    type Err = Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Self(
+            s.lines()
+                .map(|l| l.split_once('#').map_or(l, |(l, _)| l).trim())
+                .filter(|l| !l.is_empty())
+                .map(str::to_owned)
+                .collect(),
+        ))
+    }
+}
+
+impl<S: Into<String>> FromIterator<S> for Catalog {
+    fn from_iter<T: IntoIterator<Item = S>>(iter: T) -> Self {
+        Self(iter.into_iter().map(Into::into).collect())
+    }
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+impl Catalog {
+    #[must_use]
+    pub fn contains(&self, identifier: &str) -> bool {
+        self.0.contains(identifier)
+    }
+
+    /// The closest known identifier, if it is similar enough.
+    #[must_use]
+    pub fn suggest(&self, identifier: &str) -> Option<&str> {
+        self.0
+            .iter()
+            .map(|known| (edit_distance(identifier, known), known))
+            .filter(|(distance, _)| *distance <= MAX_SUGGESTION_DISTANCE)
+            .min_by_key(|(distance, _)| *distance)
+            .map(|(_, known)| known.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_distance() {
+        assert_eq!(edit_distance("AANB", "AANB"), 0);
+        assert_eq!(edit_distance("ANB", "AANB"), 1);
+        assert_eq!(edit_distance("TNGFa", "TGNFa"), 2);
+        assert_eq!(edit_distance("", "AA"), 2);
+    }
+
+    #[test]
+    fn test_suggest() {
+        let catalog: Catalog = "AA\nAANB # client\n\n  TNG\n".parse().unwrap();
+        assert!(catalog.contains("AANB"));
+        assert!(!catalog.contains("ANB"));
+        assert_eq!(catalog.suggest("ANB"), Some("AANB"));
+        assert_eq!(catalog.suggest("A"), Some("AA"));
+        assert_eq!(catalog.suggest("Urlaub"), None);
+    }
+}
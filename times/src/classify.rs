@@ -0,0 +1,147 @@
+use std::hash::{Hash, Hasher};
+use std::str::FromStr;
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum RuleError {
+    #[error("Expected a rule in the format <prefix|suffix|regex|list>:<value>")]
+    Format,
+    #[error("Invalid regex: {0}")]
+    Regex(#[from] regex::Error),
+}
+
+#[derive(Debug, Clone)]
+pub enum Rule {
+    Prefix(String),
+    Suffix(String),
+    Regex(Regex),
+    List(Vec<String>),
+}
+
+impl Rule {
+    #[must_use]
+    pub fn matches(&self, identifier: &str) -> bool {
+        match self {
+            Rule::Prefix(prefix) => identifier.starts_with(prefix.as_str()),
+            Rule::Suffix(suffix) => identifier.ends_with(suffix.as_str()),
+            Rule::Regex(regex) => regex.is_match(identifier),
+            Rule::List(list) => list.iter().any(|i| i == identifier),
+        }
+    }
+}
+
+impl Hash for Rule {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        std::mem::discriminant(self).hash(state);
+        match self {
+            Rule::Prefix(value) | Rule::Suffix(value) => value.hash(state),
+            Rule::Regex(regex) => regex.as_str().hash(state),
+            Rule::List(list) => list.hash(state),
+        }
+    }
+}
+
+impl FromStr for Rule {
+    type Err = RuleError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (kind, value) = s.split_once(':').ok_or(RuleError::Format)?;
+        match kind.trim() {
+            "prefix" => Ok(Rule::Prefix(value.to_owned())),
+            "suffix" => Ok(Rule::Suffix(value.to_owned())),
+            "regex" => Ok(Rule::Regex(Regex::new(value)?)),
+            "list" => Ok(Rule::List(
+                value.split(',').map(|i| i.trim().to_owned()).collect(),
+            )),
+            _ => Err(RuleError::Format),
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+pub struct Class {
+    pub tng: bool,
+    pub travel: bool,
+    pub under_hours: bool,
+}
+
+/// Decides which identifiers are TNG projects, travel or under-hours, an identifier is part of a
+/// class if any of its rules match.
+#[derive(Debug, Clone, Hash)]
+pub struct Classifier {
+    pub tng: Vec<Rule>,
+    pub travel: Vec<Rule>,
+    pub under_hours: Vec<Rule>,
+}
+
+impl Default for Classifier {
+    fn default() -> Self {
+        Self {
+            tng: vec![Rule::Prefix("TNG".to_owned())],
+            travel: vec![Rule::Suffix("Fa".to_owned())],
+            under_hours: vec![Rule::Prefix("Ustd".to_owned())],
+        }
+    }
+}
+
+impl Classifier {
+    #[must_use]
+    pub fn classify(&self, identifier: &str) -> Class {
+        let any = |rules: &[Rule]| rules.iter().any(|r| r.matches(identifier));
+        Class {
+            tng: any(&self.tng),
+            travel: any(&self.travel),
+            under_hours: any(&self.under_hours),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_rules() {
+        let classifier = Classifier::default();
+        assert_eq!(
+            classifier.classify("TNGFa"),
+            Class {
+                tng: true,
+                travel: true,
+                under_hours: false,
+            }
+        );
+        assert_eq!(
+            classifier.classify("UstdPart"),
+            Class {
+                tng: false,
+                travel: false,
+                under_hours: true,
+            }
+        );
+        assert_eq!(classifier.classify("AA"), Class::default());
+    }
+
+    #[test]
+    fn test_rules() {
+        let classifier = Classifier {
+            tng: vec!["list: Intern, Admin".parse().unwrap()],
+            travel: vec!["regex:^Reise".parse().unwrap()],
+            under_hours: vec!["suffix:-".parse().unwrap(), "prefix:Minus".parse().unwrap()],
+        };
+        assert!(classifier.classify("Admin").tng);
+        assert!(!classifier.classify("Administration").tng);
+        assert!(classifier.classify("ReiseKunde").travel);
+        assert!(!classifier.classify("KundeReise").travel);
+        assert!(classifier.classify("AA-").under_hours);
+        assert!(classifier.classify("MinusStunden").under_hours);
+        assert!(matches!("infix:A".parse::<Rule>(), Err(RuleError::Format)));
+        assert!(matches!(
+            "regex:(".parse::<Rule>(),
+            Err(RuleError::Regex(_))
+        ));
+    }
+}
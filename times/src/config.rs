@@ -0,0 +1,651 @@
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use serde::de::Error as _;
+use serde::{Deserialize, Deserializer};
+use thiserror::Error;
+
+use crate::classify::{Classifier, Rule};
+use crate::export::Locale;
+use crate::i18n::Language;
+use crate::lint::Level;
+use crate::parse::parse_hours;
+use crate::{Granularity, Minutes, Time};
+
+/// Name of the file that overrides the global configuration for a directory and its children.
+pub const LOCAL_FILE: &str = ".timesheet.toml";
+
+/// A config with every setting commented out at its default value.
+pub const TEMPLATE: &str = r#"# timesheets_dir = "timesheets"
+# granularity = 3
+# locale = "en"
+# language = "en"
+# cache = true
+
+[schedule]
+# hours_per_week = 40
+
+[export.csv]
+# locale = "de"
+
+[export.xlsx]
+# locale = "de"
+
+[classify]
+# tng = ["prefix:TNG"]
+# travel = ["suffix:Fa"]
+# under_hours = ["prefix:Ustd"]
+
+[import]
+# meetings = "Meeting"
+# email = "me@example.com"
+
+[import.identifiers]
+# "Project name" = "AA"
+
+[jira]
+# url = "https://example.atlassian.net"
+# user = "me@example.com"
+
+[jira.issues]
+# AA = "PROJ-1"
+
+[backup]
+# enabled = true
+# keep = 50
+# keep_days = 90
+
+[hooks]
+# on_add = "~/bin/sync-timesheets"
+# on_change = "~/bin/sync-timesheets"
+# on_check_fail = "notify-send 'Timesheet check failed'"
+
+[remind]
+# start_by = "10:00"
+# max_running = "4h"
+
+[lint]
+# max_daily_work = "10h"
+# max_gap = "2h"
+# plugins = ["/usr/local/lib/libacme_lint.so"]
+
+[lint.rules]
+# missing-break = "off"
+# unterminated-day = "warning"
+# gap = "off"
+# weekend-work = "off"
+"#;
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("Failed to read config {path}: {1}", path = .0.display())]
+    Io(PathBuf, std::io::Error),
+    #[error("Invalid config {path}: {1}", path = .0.display())]
+    Toml(PathBuf, toml::de::Error),
+}
+
+/// Settings that are not part of the timesheet files, every unset value uses the built-in default.
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Directory with the month files, relative paths are resolved against the directory of the
+    /// config file and a leading `~` against the home directory.
+    pub timesheets_dir: Option<PathBuf>,
+    #[serde(deserialize_with = "parsed")]
+    pub granularity: Option<Granularity>,
+    pub locale: Option<Locale>,
+    pub language: Option<Language>,
+    pub cache: Option<bool>,
+    pub schedule: Schedule,
+    pub classify: Classify,
+    pub export: Export,
+    pub import: Import,
+    pub jira: Jira,
+    pub backup: Backup,
+    pub hooks: Hooks,
+    pub remind: Remind,
+    pub lint: Lint,
+}
+
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Schedule {
+    /// Used for files without `#! hours_per_week`.
+    #[serde(deserialize_with = "duration")]
+    pub hours_per_week: Option<Minutes>,
+}
+
+/// Rules like `prefix:TNG` replacing the defaults of [`Classifier`].
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Classify {
+    #[serde(deserialize_with = "rules")]
+    pub tng: Option<Vec<Rule>>,
+    #[serde(deserialize_with = "rules")]
+    pub travel: Option<Vec<Rule>>,
+    #[serde(deserialize_with = "rules")]
+    pub under_hours: Option<Vec<Rule>>,
+}
+
+/// Settings of each export format, unset values fall back to the top-level ones.
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Export {
+    pub csv: ExportTarget,
+    pub xlsx: ExportTarget,
+}
+
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ExportTarget {
+    pub locale: Option<Locale>,
+}
+
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Import {
+    /// Identifiers by the project names of imported exports.
+    pub identifiers: BTreeMap<String, String>,
+    /// Identifier of imported calendar events, defaults to `Meeting`.
+    pub meetings: Option<String>,
+    /// Address of the calendar owner, events it declined are not imported.
+    pub email: Option<String>,
+}
+
+/// The Jira instance of `sync jira`, the API token is passed on the command line.
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Jira {
+    pub url: Option<String>,
+    pub user: Option<String>,
+    /// Issue keys by identifier, for entries without a ticket key in their comment.
+    pub issues: BTreeMap<String, String>,
+}
+
+/// Snapshots taken before a file is changed, see [`crate::backup`].
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Backup {
+    pub enabled: Option<bool>,
+    pub keep: Option<usize>,
+    pub keep_days: Option<u32>,
+}
+
+/// Shell commands run by the command line after an event, with the path of the file and the
+/// date of the changed day as arguments.
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Hooks {
+    /// After add wrote a day.
+    pub on_add: Option<String>,
+    /// After any command changed a file, the date is only passed if a single day changed.
+    pub on_change: Option<String>,
+    /// After check failed, only with the path of the file or directory.
+    pub on_check_fail: Option<String>,
+}
+
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Remind {
+    #[serde(deserialize_with = "parsed")]
+    pub start_by: Option<Time>,
+    #[serde(deserialize_with = "parsed")]
+    pub max_running: Option<Minutes>,
+}
+
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Lint {
+    #[serde(deserialize_with = "duration")]
+    pub max_daily_work: Option<Minutes>,
+    #[serde(deserialize_with = "duration")]
+    pub max_gap: Option<Minutes>,
+    /// Levels by rule code, see [`crate::lint::RULES`].
+    #[serde(deserialize_with = "levels")]
+    pub rules: BTreeMap<String, Level>,
+    /// Dynamic libraries with custom rules, loaded by the command line with the plugins feature.
+    pub plugins: Vec<PathBuf>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Value {
+    Integer(u64),
+    String(String),
+}
+
+impl Value {
+    fn into_string(self) -> String {
+        match self {
+            Value::Integer(i) => i.to_string(),
+            Value::String(s) => s,
+        }
+    }
+}
+
+fn parsed<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: FromStr,
+    T::Err: Display,
+{
+    Value::deserialize(deserializer)?
+        .into_string()
+        .parse()
+        .map(Some)
+        .map_err(D::Error::custom)
+}
+
+/// A duration like `40`, `38h30m` or `01:30`, plain numbers are hours.
+fn duration<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Minutes>, D::Error> {
+    let value = Value::deserialize(deserializer)?.into_string();
+    parse_hours(&value)
+        .map(Some)
+        .ok_or_else(|| D::Error::custom("expected a duration like 40, 38h30m or 01:30"))
+}
+
+/// `path` with a leading `~` replaced by the home directory, other relative paths joined to `dir`.
+fn resolve(dir: &Path, path: &Path) -> PathBuf {
+    match (path.strip_prefix("~"), std::env::var_os("HOME")) {
+        (Ok(rest), Some(home)) => PathBuf::from(home).join(rest),
+        _ => dir.join(path),
+    }
+}
+
+fn rules<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Vec<Rule>>, D::Error> {
+    Vec::<String>::deserialize(deserializer)?
+        .iter()
+        .map(|r| r.parse().map_err(D::Error::custom))
+        .collect::<Result<_, _>>()
+        .map(Some)
+}
+
+fn levels<'de, D: Deserializer<'de>>(deserializer: D) -> Result<BTreeMap<String, Level>, D::Error> {
+    let levels = BTreeMap::<String, Level>::deserialize(deserializer)?;
+    // Codes of custom rules are only known once their plugins are loaded.
+    for code in levels.keys().filter(|c| !c.contains('/')) {
+        if !crate::lint::RULES.iter().any(|(c, _)| c == code) {
+            return Err(D::Error::custom(format!("unknown lint rule {code}")));
+        }
+    }
+    Ok(levels)
+}
+
+impl FromStr for Config {
+    type Err = toml::de::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        toml::from_str(s)
+    }
+}
+
+impl Config {
+    /// `$XDG_CONFIG_HOME/timesheet/config.toml`, falling back to `~/.config`.
+    #[must_use]
+    pub fn global_path() -> Option<PathBuf> {
+        let dir = std::env::var_os("XDG_CONFIG_HOME")
+            .filter(|d| !d.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| Some(PathBuf::from(std::env::var_os("HOME")?).join(".config")))?;
+        Some(dir.join("timesheet").join("config.toml"))
+    }
+
+    /// The nearest [`LOCAL_FILE`] in `dir` or one of its parents.
+    #[must_use]
+    pub fn local_path(dir: &Path) -> Option<PathBuf> {
+        dir.ancestors()
+            .map(|d| d.join(LOCAL_FILE))
+            .find(|p| p.is_file())
+    }
+
+    /// Reads the config at `path`, `None` if it does not exist. Paths in it are resolved relative
+    /// to the file.
+    pub fn from_file(path: &Path) -> Result<Option<Self>, Error> {
+        let config: Self = match std::fs::read_to_string(path) {
+            Ok(text) => text.parse().map_err(|e| Error::Toml(path.to_owned(), e))?,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(Error::Io(path.to_owned(), e)),
+        };
+        let dir = path.parent().unwrap_or(Path::new(""));
+        Ok(Some(Self {
+            timesheets_dir: config.timesheets_dir.map(|d| resolve(dir, &d)),
+            lint: Lint {
+                plugins: config
+                    .lint
+                    .plugins
+                    .iter()
+                    .map(|p| resolve(dir, p))
+                    .collect(),
+                ..config.lint
+            },
+            ..config
+        }))
+    }
+
+    /// The global config overridden by the local config for `dir`.
+    pub fn load(dir: &Path) -> Result<Self, Error> {
+        Self::load_with(dir, Self::default())
+    }
+
+    /// Like [`Self::load`], but with `flags`, the settings given on the command line, taking
+    /// precedence over both files.
+    pub fn load_with(dir: &Path, flags: Self) -> Result<Self, Error> {
+        let mut config = Self::default();
+        for path in [Self::global_path(), Self::local_path(dir)]
+            .into_iter()
+            .flatten()
+        {
+            if let Some(file) = Self::from_file(&path)? {
+                debug!(path = %path.display(), "loaded config");
+                config = config.merge(file);
+            }
+        }
+        Ok(config.merge(flags))
+    }
+
+    /// Values set in `other` take precedence.
+    #[must_use]
+    pub fn merge(self, other: Self) -> Self {
+        Self {
+            timesheets_dir: other.timesheets_dir.or(self.timesheets_dir),
+            granularity: other.granularity.or(self.granularity),
+            locale: other.locale.or(self.locale),
+            language: other.language.or(self.language),
+            cache: other.cache.or(self.cache),
+            schedule: Schedule {
+                hours_per_week: other
+                    .schedule
+                    .hours_per_week
+                    .or(self.schedule.hours_per_week),
+            },
+            classify: Classify {
+                tng: other.classify.tng.or(self.classify.tng),
+                travel: other.classify.travel.or(self.classify.travel),
+                under_hours: other.classify.under_hours.or(self.classify.under_hours),
+            },
+            export: Export {
+                csv: ExportTarget {
+                    locale: other.export.csv.locale.or(self.export.csv.locale),
+                },
+                xlsx: ExportTarget {
+                    locale: other.export.xlsx.locale.or(self.export.xlsx.locale),
+                },
+            },
+            import: Import {
+                identifiers: self
+                    .import
+                    .identifiers
+                    .into_iter()
+                    .chain(other.import.identifiers)
+                    .collect(),
+                meetings: other.import.meetings.or(self.import.meetings),
+                email: other.import.email.or(self.import.email),
+            },
+            jira: Jira {
+                url: other.jira.url.or(self.jira.url),
+                user: other.jira.user.or(self.jira.user),
+                issues: self
+                    .jira
+                    .issues
+                    .into_iter()
+                    .chain(other.jira.issues)
+                    .collect(),
+            },
+            backup: Backup {
+                enabled: other.backup.enabled.or(self.backup.enabled),
+                keep: other.backup.keep.or(self.backup.keep),
+                keep_days: other.backup.keep_days.or(self.backup.keep_days),
+            },
+            hooks: Hooks {
+                on_add: other.hooks.on_add.or(self.hooks.on_add),
+                on_change: other.hooks.on_change.or(self.hooks.on_change),
+                on_check_fail: other.hooks.on_check_fail.or(self.hooks.on_check_fail),
+            },
+            remind: Remind {
+                start_by: other.remind.start_by.or(self.remind.start_by),
+                max_running: other.remind.max_running.or(self.remind.max_running),
+            },
+            lint: Lint {
+                max_daily_work: other.lint.max_daily_work.or(self.lint.max_daily_work),
+                max_gap: other.lint.max_gap.or(self.lint.max_gap),
+                rules: self
+                    .lint
+                    .rules
+                    .into_iter()
+                    .chain(other.lint.rules)
+                    .collect(),
+                plugins: self
+                    .lint
+                    .plugins
+                    .into_iter()
+                    .chain(other.lint.plugins)
+                    .collect(),
+            },
+        }
+    }
+
+    #[must_use]
+    pub fn lint_options(&self) -> crate::lint::Options {
+        let default = crate::lint::Options::default();
+        crate::lint::Options {
+            max_daily_work: self.lint.max_daily_work.unwrap_or(default.max_daily_work),
+            max_gap: self.lint.max_gap.unwrap_or(default.max_gap),
+            levels: self.lint.rules.clone(),
+            custom: default.custom,
+        }
+    }
+
+    /// Unset keeps the German identifiers of the templates that existing timesheets already use.
+    #[must_use]
+    pub fn template_language(&self) -> Language {
+        self.language.unwrap_or(Language::De)
+    }
+
+    /// `None` if snapshots are disabled.
+    #[must_use]
+    pub fn backup_options(&self) -> Option<crate::backup::Options> {
+        let default = crate::backup::Options::default();
+        self.backup
+            .enabled
+            .unwrap_or(true)
+            .then(|| crate::backup::Options {
+                keep: self.backup.keep.unwrap_or(default.keep),
+                keep_days: self.backup.keep_days.or(default.keep_days),
+            })
+    }
+
+    #[must_use]
+    pub fn csv_locale(&self) -> Locale {
+        self.export.csv.locale.or(self.locale).unwrap_or_default()
+    }
+
+    #[must_use]
+    pub fn xlsx_locale(&self) -> Locale {
+        self.export.xlsx.locale.or(self.locale).unwrap_or_default()
+    }
+
+    #[must_use]
+    pub fn remind_options(&self) -> crate::remind::Options {
+        let default = crate::remind::Options::default();
+        crate::remind::Options {
+            start_by: self.remind.start_by.unwrap_or(default.start_by),
+            max_running: self.remind.max_running.unwrap_or(default.max_running),
+        }
+    }
+
+    #[must_use]
+    pub fn convert_options(&self) -> crate::convert::Options {
+        crate::convert::Options {
+            granularity: self.granularity.unwrap_or_default(),
+            classifier: self.classifier(),
+            hours_per_week: self.schedule.hours_per_week,
+        }
+    }
+
+    #[must_use]
+    pub fn classifier(&self) -> Classifier {
+        let default = Classifier::default();
+        Classifier {
+            tng: self.classify.tng.clone().unwrap_or(default.tng),
+            travel: self.classify.travel.clone().unwrap_or(default.travel),
+            under_hours: self
+                .classify
+                .under_hours
+                .clone()
+                .unwrap_or(default.under_hours),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let config: Config = r#"
+            timesheets_dir = "~/work"
+            granularity = 5
+            locale = "de"
+
+            [schedule]
+            hours_per_week = "38h30m"
+
+            [classify]
+            travel = ["suffix:Fa", "prefix:Reise"]
+            "#
+        .parse()
+        .unwrap();
+        assert_eq!(config.granularity, Granularity::new(5));
+        assert_eq!(config.locale, Some(Locale::De));
+        assert_eq!(
+            config.schedule.hours_per_week,
+            Some(Minutes::from(38 * 60 + 30))
+        );
+        let classifier = config.classifier();
+        assert!(classifier.classify("ReiseMuc").travel);
+        assert!(classifier.classify("TNGFoo").tng);
+
+        assert!("granularity = 7".parse::<Config>().is_err());
+        assert!("colour = true".parse::<Config>().is_err());
+        assert!("[classify]\ntng = [\"TNG\"]".parse::<Config>().is_err());
+    }
+
+    #[test]
+    fn test_template() {
+        let config: Config = TEMPLATE.parse().unwrap();
+        assert!(config.granularity.is_none() && config.classify.tng.is_none());
+        let uncommented = TEMPLATE.replace("# ", "");
+        let config: Config = uncommented.parse().unwrap();
+        assert_eq!(config.granularity, Some(Granularity::default()));
+        assert_eq!(config.template_language(), Language::En);
+        assert_eq!(Config::default().template_language(), Language::De);
+        assert_eq!(
+            config.schedule.hours_per_week,
+            Some(Minutes::from_hours(40))
+        );
+        assert_eq!(config.export.csv.locale, Some(Locale::De));
+        assert_eq!(config.export.xlsx.locale, Some(Locale::De));
+        assert_eq!(config.jira.issues["AA"], "PROJ-1");
+        assert_eq!(
+            config.hooks.on_add.as_deref(),
+            Some("~/bin/sync-timesheets")
+        );
+        assert_eq!(config.remind_options(), crate::remind::Options::default());
+        let backup = config.backup_options().unwrap();
+        assert_eq!((backup.keep, backup.keep_days), (50, Some(90)));
+        let disabled: Config = "[backup]\nenabled = false".parse().unwrap();
+        assert_eq!(disabled.backup_options(), None);
+    }
+
+    #[test]
+    fn test_merge() {
+        let global: Config = "granularity = 5\nlocale = \"de\"\n[schedule]\nhours_per_week = 40"
+            .parse()
+            .unwrap();
+        let local: Config =
+            "granularity = 15\ncache = true\n[import.identifiers]\nInference = \"AA\""
+                .parse()
+                .unwrap();
+        let config = global.merge(local);
+        let config = config.merge(
+            "[lint]\nmax_gap = \"1h\"\nrules = { gap = \"error\" }"
+                .parse()
+                .unwrap(),
+        );
+        let options = config.lint_options();
+        assert_eq!(options.max_gap, Minutes::from_hours(1));
+        assert_eq!(options.level("gap"), Level::Error);
+        assert_eq!(options.level("weekend-work"), Level::Off);
+        assert!("[lint.rules]\nbogus = \"off\"".parse::<Config>().is_err());
+        let custom: Config = "[lint.rules]\n\"acme/ticket\" = \"error\"".parse().unwrap();
+        assert_eq!(custom.lint_options().level("acme/ticket"), Level::Error);
+        assert_eq!(config.granularity, Granularity::new(15));
+        assert_eq!(config.import.identifiers["Inference"], "AA");
+        assert_eq!(config.locale, Some(Locale::De));
+        assert_eq!(config.csv_locale(), Locale::De);
+        let config = config.merge(
+            "locale = \"en\"\n[export.csv]\nlocale = \"de\""
+                .parse()
+                .unwrap(),
+        );
+        assert_eq!(
+            (config.locale, config.csv_locale(), config.xlsx_locale()),
+            (Some(Locale::En), Locale::De, Locale::En)
+        );
+        assert_eq!(Config::default().csv_locale(), Locale::En);
+        assert_eq!(config.cache, Some(true));
+        assert_eq!(
+            config.schedule.hours_per_week,
+            Some(Minutes::from_hours(40))
+        );
+    }
+
+    #[test]
+    fn test_from_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(LOCAL_FILE);
+        std::fs::write(&path, "timesheets_dir = \"work\"").unwrap();
+        let config = Config::from_file(&path).unwrap().unwrap();
+        assert_eq!(config.timesheets_dir, Some(dir.path().join("work")));
+
+        std::fs::write(&path, "timesheets_dir = \"~/work\"").unwrap();
+        let config = Config::from_file(&path).unwrap().unwrap();
+        let home = PathBuf::from(std::env::var_os("HOME").unwrap());
+        assert_eq!(config.timesheets_dir, Some(home.join("work")));
+
+        std::fs::write(&path, "[lint]\nplugins = [\"lint.so\", \"/lib/acme.so\"]").unwrap();
+        let config = Config::from_file(&path).unwrap().unwrap();
+        assert_eq!(
+            config.lint.plugins,
+            [dir.path().join("lint.so"), PathBuf::from("/lib/acme.so")]
+        );
+
+        assert!(Config::from_file(&dir.path().join("missing.toml"))
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_load_with() {
+        let dir = tempfile::tempdir().unwrap();
+        let dir = dir.path();
+        let nested = dir.join("timesheets");
+        std::fs::create_dir_all(&nested).unwrap();
+        std::fs::write(dir.join(LOCAL_FILE), "granularity = 5\nlocale = \"de\"").unwrap();
+        let flags = Config {
+            granularity: Granularity::new(15),
+            ..Config::default()
+        };
+        let config = Config::load_with(&nested, flags).unwrap();
+        assert_eq!(config.granularity, Granularity::new(15));
+        assert_eq!(config.locale, Some(Locale::De));
+        assert_eq!(
+            Config::load(&nested).unwrap().convert_options().granularity,
+            Granularity::new(5).unwrap()
+        );
+    }
+}
@@ -1,16 +1,24 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, HashMap};
 use std::fmt::{Display, Formatter};
+use std::hash::{Hash, Hasher};
 use std::ops::Add;
+use std::sync::Arc;
 
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
-use crate::{Date, Minutes, Positioned, Time, Topic};
+use crate::ast::Document;
+use crate::classify::{Class, Classifier};
+use crate::edit::{with_time, Fix};
+use crate::{Date, FileMeta, Granularity, Minutes, Positioned, Time, Topic};
 
 #[derive(Debug, Error, Eq, PartialEq)]
 pub enum Error {
     #[error("Time span in line {0} is never terminated")]
     NotTerminated(usize),
-    #[error("Minutes of time in line {0} are not a multiple of three")]
-    TimeNotMultipleOfThree(usize),
+    #[error("Minutes of time in line {0} are not a multiple of {1}")]
+    TimeNotMultipleOfGranularity(usize, Granularity),
     #[error("Time in line {0} ends before it starts")]
     EndsBeforeItStarts(usize),
     #[error("Time in line {0} overlaps with the time before it")]
@@ -19,8 +27,70 @@ pub enum Error {
     AcrossTravelTime(usize),
 }
 
-#[cfg_attr(test, derive(Default, Eq, PartialEq))]
-pub struct Identifier(String);
+impl Error {
+    #[must_use]
+    pub fn line(&self) -> usize {
+        match *self {
+            Error::NotTerminated(line)
+            | Error::TimeNotMultipleOfGranularity(line, _)
+            | Error::EndsBeforeItStarts(line)
+            | Error::OverlapWithPrevious(line)
+            | Error::AcrossTravelTime(line) => line,
+        }
+    }
+
+    #[must_use]
+    pub fn code(&self) -> &'static str {
+        match self {
+            Error::NotTerminated(_) => "not-terminated",
+            Error::TimeNotMultipleOfGranularity(..) => "granularity",
+            Error::EndsBeforeItStarts(_) => "ends-before-start",
+            Error::OverlapWithPrevious(_) => "overlap",
+            Error::AcrossTravelTime(_) => "across-travel-time",
+        }
+    }
+
+    /// Suggests a fix for this error in `document`.
+    #[must_use]
+    pub fn fix(&self, document: &Document) -> Option<Fix> {
+        let lines = document.lines();
+        match *self {
+            Error::TimeNotMultipleOfGranularity(line, granularity) => {
+                let text = lines.get(line.checked_sub(1)?)?.text();
+                let time = lines[line - 1].entry()?.time;
+                let rounded = time.rounded(granularity.minutes());
+                Some(Fix::new(
+                    line,
+                    format!("Round {time} to {rounded}"),
+                    vec![(line, with_time(text, rounded))],
+                ))
+            }
+            Error::EndsBeforeItStarts(line) => {
+                let start = lines.get(line.checked_sub(1)?)?;
+                let (index, end) = lines
+                    .iter()
+                    .enumerate()
+                    .skip(line)
+                    .find(|(_, l)| l.entry().is_some())?;
+                let start_time = start.entry()?.time;
+                let end_time = end.entry()?.time;
+                Some(Fix::new(
+                    line,
+                    format!("Swap start {start_time} and end {end_time}"),
+                    vec![
+                        (line, with_time(start.text(), end_time)),
+                        (index + 1, with_time(end.text(), start_time)),
+                    ],
+                ))
+            }
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize)]
+#[cfg_attr(test, derive(Default))]
+pub struct Identifier(Arc<str>);
 
 impl Display for Identifier {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
@@ -30,30 +100,39 @@ impl Display for Identifier {
 
 impl Identifier {
     #[must_use]
-    pub fn is_tng(&self) -> bool {
-        self.0.starts_with("TNG")
+    pub fn as_str(&self) -> &str {
+        &self.0
     }
+}
 
-    #[must_use]
-    pub fn is_travel(&self) -> bool {
-        self.0.ends_with("Fa")
-    }
+/// Shares one allocation and classification between all entries with the same identifier.
+#[derive(Default)]
+struct Identifiers(HashMap<Arc<str>, Class>);
 
-    #[must_use]
-    pub fn is_under_hours(&self) -> bool {
-        self.0.starts_with("Ustd")
+impl Identifiers {
+    fn get(&mut self, identifier: &str, classifier: &Classifier) -> (Identifier, Class) {
+        if let Some((identifier, class)) = self.0.get_key_value(identifier) {
+            return (Identifier(identifier.clone()), *class);
+        }
+        let class = classifier.classify(identifier);
+        let identifier: Arc<str> = identifier.into();
+        self.0.insert(identifier.clone(), class);
+        (Identifier(identifier), class)
     }
 }
 
+#[derive(Clone, Serialize, Deserialize)]
 #[cfg_attr(test, derive(Default, Eq, PartialEq))]
 pub struct Entry {
     pub start: Positioned<Time>,
     pub end: Positioned<Time>,
     pub duration: Minutes,
     pub identifier: Identifier,
+    pub class: Class,
     pub comment: Option<String>,
 }
 
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Day {
     pub comments: Vec<String>,
     pub date: Positioned<Date>,
@@ -61,69 +140,218 @@ pub struct Day {
     pub times: AccumulatedTime,
 }
 
+const DEFAULT_HOURS_PER_WEEK: usize = 40;
+
+fn is_default_half_day(date: Date) -> bool {
+    date.month() == 12 && [24, 31].contains(&date.day())
+}
+
+fn expected_time(date: Date, hours_per_week: Minutes, half_day: bool) -> Minutes {
+    if !date.is_weekday() {
+        return Minutes::default();
+    }
+    let minutes = hours_per_week.into_inner() / 5;
+    if half_day {
+        Minutes::from(minutes / 2)
+    } else {
+        Minutes::from(minutes)
+    }
+}
+
 impl Day {
     #[must_use]
     pub fn expected_time(&self) -> Minutes {
-        if self.date.value.is_weekday() {
-            Minutes::from_hours(8)
-        } else {
-            Minutes::default()
+        let date = self.date.value;
+        expected_time(
+            date,
+            Minutes::from_hours(DEFAULT_HOURS_PER_WEEK),
+            is_default_half_day(date),
+        )
+    }
+
+    #[must_use]
+    pub fn by_project(&self) -> &BTreeMap<Identifier, Minutes> {
+        self.times.by_project()
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Month {
+    /// The first day of the month.
+    pub month: Date,
+    pub meta: FileMeta,
+    pub days: Vec<Day>,
+}
+
+impl Month {
+    #[must_use]
+    pub fn expected_time(&self, day: &Day) -> Minutes {
+        self.expected_time_on(day.date.value)
+    }
+
+    #[must_use]
+    pub fn expected_time_on(&self, date: Date) -> Minutes {
+        let hours_per_week = self
+            .meta
+            .hours_per_week
+            .unwrap_or(Minutes::from_hours(DEFAULT_HOURS_PER_WEEK));
+        let half_day = match &self.meta.half_days {
+            Some(half_days) => half_days.contains(&date),
+            None => is_default_half_day(date),
+        };
+        expected_time(date, hours_per_week, half_day)
+    }
+
+    #[must_use]
+    pub fn times(&self) -> AccumulatedTime {
+        self.days
+            .iter()
+            .map(|d| d.times.clone())
+            .fold(AccumulatedTime::default(), AccumulatedTime::add)
+    }
+
+    #[must_use]
+    pub fn by_project(&self) -> BTreeMap<Identifier, Minutes> {
+        self.times().by_project
+    }
+}
+
+impl TryFrom<crate::Month> for Month {
+    type Error = Error;
+
+    fn try_from(value: crate::Month) -> Result<Self, Self::Error> {
+        Month::convert(value, &Options::default())
+    }
+}
+
+impl Month {
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(month = ?value.month))
+    )]
+    pub fn convert(value: crate::Month, options: &Options) -> Result<Self, Error> {
+        let crate::Month {
+            month,
+            mut meta,
+            days,
+        } = value;
+        meta.hours_per_week = meta.hours_per_week.or(options.hours_per_week);
+        let mut identifiers = Identifiers::default();
+        let days = days
+            .into_iter()
+            .map(|d| Day::convert_with(d, options, &mut identifiers))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Month { month, meta, days })
+    }
+}
+
+/// Identifies a parsed day by its content and the positions of its lines relative to the header.
+fn day_key(day: &crate::Day) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    day.date.value.hash(&mut hasher);
+    day.comments.hash(&mut hasher);
+    for entry in &day.entries {
+        (entry.line - day.date.line).hash(&mut hasher);
+        entry.value.hash(&mut hasher);
+    }
+    hasher.finish()
+}
+
+/// Converts successive versions of the same file, reusing the converted days whose lines did
+/// not change apart from moving up or down in the file.
+#[derive(Default)]
+pub struct Incremental {
+    options: Options,
+    identifiers: Identifiers,
+    days: HashMap<u64, Day>,
+    reused: usize,
+}
+
+impl Incremental {
+    #[must_use]
+    pub fn new(options: Options) -> Self {
+        Self {
+            options,
+            ..Self::default()
         }
     }
+
+    /// Number of days the last conversion took from the previous one.
+    #[must_use]
+    pub fn reused(&self) -> usize {
+        self.reused
+    }
+
+    pub fn convert(&mut self, value: crate::Month) -> Result<Month, Error> {
+        let crate::Month {
+            month,
+            mut meta,
+            days,
+        } = value;
+        meta.hours_per_week = meta.hours_per_week.or(self.options.hours_per_week);
+        let mut cache = HashMap::with_capacity(days.len());
+        let mut converted = Vec::with_capacity(days.len());
+        let mut reused = 0;
+        for day in days {
+            let key = day_key(&day);
+            let line = day.date.line;
+            let day = if let Some(day) = self.days.get(&key) {
+                reused += 1;
+                day.clone().moved_to(line)
+            } else {
+                Day::convert_with(day, &self.options, &mut self.identifiers)?
+            };
+            cache.insert(key, day.clone());
+            converted.push(day);
+        }
+        self.days = cache;
+        self.reused = reused;
+        debug!(reused, days = converted.len(), "converted incrementally");
+        Ok(Month {
+            month,
+            meta,
+            days: converted,
+        })
+    }
 }
 
 #[must_use]
 fn accumulated_time<'a>(entries: impl IntoIterator<Item = &'a Entry>) -> AccumulatedTime {
-    let mut last_travel = None;
-    entries
-        .into_iter()
-        .fold(AccumulatedTime::default(), |acc, entry| {
-            let AccumulatedTime { travel, work } = acc;
-            let duration = entry.duration;
-            if entry.identifier.is_travel() {
+    let mut last_travel: Option<&Entry> = None;
+    let mut acc = AccumulatedTime::default();
+    for entry in entries {
+        let duration = entry.duration;
+        *acc.by_project.entry(entry.identifier.clone()).or_default() += duration;
+        if entry.class.under_hours {
+            if entry.class.travel {
                 last_travel = Some(entry);
             }
-            if entry.identifier.is_under_hours() {
-                AccumulatedTime { travel, work }
-            } else if entry.identifier.is_travel() {
-                let travel = if entry.identifier.is_tng() {
-                    TravelTime {
-                        tng: travel.tng + duration,
-                        other: travel.other,
-                    }
-                } else {
-                    TravelTime {
-                        tng: travel.tng,
-                        other: travel.other + duration,
-                    }
-                };
-                AccumulatedTime { travel, work }
-            } else if let Some(last_travel) = last_travel
+            acc.under_hours += duration;
+        } else if entry.class.travel {
+            last_travel = Some(entry);
+            if entry.class.tng {
+                acc.travel.tng += duration;
+            } else {
+                acc.travel.other += duration;
+            }
+        } else {
+            if let Some(last_travel) = last_travel
                 .filter(|t| t.start.value <= entry.start.value && entry.end.value <= t.end.value)
             {
-                let travel = if last_travel.identifier.is_tng() {
-                    TravelTime {
-                        tng: travel.tng - duration,
-                        other: travel.other,
-                    }
+                let travel = if last_travel.class.tng {
+                    &mut acc.travel.tng
                 } else {
-                    TravelTime {
-                        tng: travel.tng,
-                        other: travel.other - duration,
-                    }
+                    &mut acc.travel.other
                 };
-
-                AccumulatedTime {
-                    work: work + duration,
-                    travel,
-                }
-            } else {
-                AccumulatedTime {
-                    work: work + duration,
-                    travel,
+                *travel = travel.saturating_sub(duration);
+                if let Some(minutes) = acc.by_project.get_mut(&last_travel.identifier) {
+                    *minutes = minutes.saturating_sub(duration);
                 }
             }
-        })
+            acc.work += duration;
+        }
+    }
+    acc
 }
 
 fn validate_ordering(
@@ -149,10 +377,42 @@ fn validate_ordering(
     }
 }
 
+#[derive(Debug, Default, Clone, Hash)]
+pub struct Options {
+    pub granularity: Granularity,
+    pub classifier: Classifier,
+    /// Used for months without `#! hours_per_week`.
+    pub hours_per_week: Option<Minutes>,
+}
+
+impl Options {
+    /// Identifies the options months were converted with, equal options have equal keys.
+    #[must_use]
+    pub fn key(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        self.hash(&mut hasher);
+        hasher.finish()
+    }
+}
+
 impl TryFrom<crate::Day> for Day {
     type Error = Error;
 
     fn try_from(value: crate::Day) -> Result<Self, Self::Error> {
+        Day::convert(value, &Options::default())
+    }
+}
+
+impl Day {
+    pub fn convert(value: crate::Day, options: &Options) -> Result<Self, Error> {
+        Self::convert_with(value, options, &mut Identifiers::default())
+    }
+
+    fn convert_with(
+        value: crate::Day,
+        options: &Options,
+        identifiers: &mut Identifiers,
+    ) -> Result<Self, Error> {
         let crate::Day {
             comments,
             date,
@@ -162,15 +422,18 @@ impl TryFrom<crate::Day> for Day {
         let mut last_travel = None;
         let mut iter = entries.into_iter().peekable();
         while let Some(entry) = iter.next() {
-            if entry.value.time.minute % 3 != 0 {
-                return Err(Error::TimeNotMultipleOfThree(entry.line));
+            if !options.granularity.matches(entry.value.time) {
+                return Err(Error::TimeNotMultipleOfGranularity(
+                    entry.line,
+                    options.granularity,
+                ));
             }
             if let Topic::Project {
                 identifier,
                 comment,
             } = entry.value.topic
             {
-                let identifier = Identifier(identifier);
+                let (identifier, class) = identifiers.get(&identifier, &options.classifier);
                 let next = iter.peek().ok_or(Error::NotTerminated(entry.line))?;
                 let duration = next
                     .value
@@ -182,10 +445,11 @@ impl TryFrom<crate::Day> for Day {
                     end: Positioned::new(next.line, next.value.time),
                     duration,
                     identifier,
+                    class,
                     comment,
                 };
 
-                if new_entry.identifier.is_travel() {
+                if new_entry.class.travel {
                     last_travel = Some(new_entries.len());
                 }
                 if let Some(previous_entry) = new_entries.last() {
@@ -210,9 +474,22 @@ impl TryFrom<crate::Day> for Day {
             times,
         })
     }
+
+    /// Moves the day so that its header is in `line`.
+    fn moved_to(mut self, line: usize) -> Self {
+        let header = self.date.line;
+        let shift = |l: &mut usize| *l = *l - header + line;
+        shift(&mut self.date.line);
+        for entry in &mut self.entries {
+            shift(&mut entry.line);
+            shift(&mut entry.value.start.line);
+            shift(&mut entry.value.end.line);
+        }
+        self
+    }
 }
 
-#[derive(Default, Clone)]
+#[derive(Default, Clone, Serialize, Deserialize)]
 #[cfg_attr(test, derive(Debug, Eq, PartialEq))]
 struct TravelTime {
     tng: Minutes,
@@ -250,11 +527,13 @@ fn billable_travel_time(minutes: Minutes) -> Minutes {
     }
 }
 
-#[derive(Default, Clone)]
+#[derive(Default, Clone, Serialize, Deserialize)]
 #[cfg_attr(test, derive(Debug, Eq, PartialEq))]
 pub struct AccumulatedTime {
     travel: TravelTime,
     work: Minutes,
+    under_hours: Minutes,
+    by_project: BTreeMap<Identifier, Minutes>,
 }
 
 impl AccumulatedTime {
@@ -277,6 +556,18 @@ impl AccumulatedTime {
     pub fn billable_time(&self) -> Minutes {
         self.work + self.billable_travel_time()
     }
+
+    #[must_use]
+    pub fn under_hours(&self) -> Minutes {
+        self.under_hours
+    }
+
+    /// The time booked per identifier. Work done during a travel time is only counted for the
+    /// work, so the values add up to the sum of travel, work and under hours.
+    #[must_use]
+    pub fn by_project(&self) -> &BTreeMap<Identifier, Minutes> {
+        &self.by_project
+    }
 }
 
 impl Add<AccumulatedTime> for AccumulatedTime {
@@ -286,17 +577,37 @@ impl Add<AccumulatedTime> for AccumulatedTime {
         AccumulatedTime {
             travel: self.travel + rhs.travel,
             work: self.work + rhs.work,
+            under_hours: self.under_hours + rhs.under_hours,
+            by_project: merge(self.by_project, rhs.by_project),
         }
     }
 }
 
+fn merge(
+    mut lhs: BTreeMap<Identifier, Minutes>,
+    rhs: BTreeMap<Identifier, Minutes>,
+) -> BTreeMap<Identifier, Minutes> {
+    for (identifier, minutes) in rhs {
+        *lhs.entry(identifier).or_default() += minutes;
+    }
+    lhs
+}
+
 #[cfg(test)]
 mod test {
+    use std::collections::BTreeMap;
+
+    use chrono::NaiveDate;
+
+    use crate::ast::Document;
+    use crate::classify::Classifier;
     use crate::convert::{
-        accumulated_time, billable_travel_time, validate_ordering, AccumulatedTime, Entry, Error,
-        Identifier, TravelTime,
+        accumulated_time, billable_travel_time, expected_time, is_default_half_day,
+        validate_ordering, AccumulatedTime, Entry, Error, Identifier, Incremental, Month, Options,
+        TravelTime,
     };
-    use crate::{Minutes, Positioned, Time};
+    use crate::edit::Fix;
+    use crate::{Date, Granularity, Minutes, Positioned, Time};
 
     #[test]
     fn travel_time_calc() {
@@ -316,11 +627,19 @@ mod test {
             start: Positioned::new(0, start.unwrap()),
             end: Positioned::new(0, end.unwrap()),
             duration: end.unwrap().elapsed(start.unwrap()).unwrap(),
-            identifier: Identifier(identifier.to_string()),
+            identifier: Identifier(identifier.into()),
+            class: Classifier::default().classify(identifier),
             comment: None,
         }
     }
 
+    fn by_project(minutes: &[(&str, usize)]) -> BTreeMap<Identifier, Minutes> {
+        minutes
+            .iter()
+            .map(|(identifier, minutes)| (Identifier((*identifier).into()), (*minutes).into()))
+            .collect()
+    }
+
     #[test]
     fn accumulated_travel_time() {
         let entries = [
@@ -337,6 +656,8 @@ mod test {
                     other: 0.into()
                 },
                 work: 120.into(),
+                under_hours: 0.into(),
+                by_project: by_project(&[("TNG", 120), ("TNGFa", 30)]),
             }
         );
     }
@@ -359,6 +680,13 @@ mod test {
                     other: 0.into()
                 },
                 work: 30.into(),
+                under_hours: 60.into(),
+                by_project: by_project(&[
+                    ("TNG", 30),
+                    ("TNGFa", 60),
+                    ("Ustd", 30),
+                    ("UstdPart", 30)
+                ]),
             }
         );
     }
@@ -383,10 +711,51 @@ mod test {
                     other: 15.into(),
                 },
                 work: 100.into(),
+                under_hours: 0.into(),
+                by_project: by_project(&[("AA", 15), ("AAFa", 15), ("TNG", 85), ("TNGFa", 5)]),
             }
         );
     }
 
+    #[test]
+    fn test_half_day_expected_time() {
+        let date = |day| Date::new(NaiveDate::from_ymd_opt(2024, 12, day).unwrap());
+        assert_eq!(
+            expected_time(date(23), Minutes::from_hours(40), false),
+            Minutes::from_hours(8)
+        );
+        assert_eq!(
+            expected_time(date(24), Minutes::from_hours(40), true),
+            Minutes::from_hours(4)
+        );
+        assert!(is_default_half_day(date(24)));
+        assert!(is_default_half_day(date(31)));
+        assert!(!is_default_half_day(date(25)));
+    }
+
+    #[test]
+    fn test_fix() {
+        let month = Date::new(NaiveDate::from_ymd_opt(2024, 4, 1).unwrap());
+        let document = Document::parse("* Mo. 15.04.\n10:17 AA A\n# c\n  09:00 BB\n", month);
+        assert_eq!(
+            Error::TimeNotMultipleOfGranularity(2, Granularity::default()).fix(&document),
+            Some(Fix::new(
+                2,
+                "Round 10:17 to 10:18".to_owned(),
+                vec![(2, "10:18 AA A".to_owned())]
+            ))
+        );
+        assert_eq!(
+            Error::EndsBeforeItStarts(2).fix(&document),
+            Some(Fix::new(
+                2,
+                "Swap start 10:17 and end 09:00".to_owned(),
+                vec![(2, "09:00 AA A".to_owned()), (4, "10:17 BB".to_owned())]
+            ))
+        );
+        assert_eq!(Error::NotTerminated(4).fix(&document), None);
+    }
+
     #[test]
     fn ordering() {
         let previous_entry = new_entry(Time::new(1, 0), Time::new(2, 0), "TNG");
@@ -442,4 +811,35 @@ mod test {
             Err(Error::OverlapWithPrevious(0))
         );
     }
+
+    #[test]
+    fn test_incremental() {
+        let month = Date::new(NaiveDate::from_ymd_opt(2024, 4, 1).unwrap());
+        let parse = |text: &str| crate::parse::parse_month(text.as_bytes(), month).unwrap();
+        let before = "* Mo. 15.04.\n09:00 AA\n12:00\n\n* Di. 16.04.\n09:00 BB\n10:00\n\n* Mi. 17.04.\n09:00 CC\n11:00\n";
+        let after = "#! hours_per_week: 30\n\n* Mo. 15.04.\n09:00 AA\n12:00\n\n* Di. 16.04.\n09:00 BB\n11:00\n\n* Mi. 17.04.\n09:00 CC\n11:00\n";
+        let mut incremental = Incremental::new(Options::default());
+        incremental.convert(parse(before)).unwrap();
+        assert_eq!(incremental.reused(), 0);
+        let converted = incremental.convert(parse(after)).unwrap();
+        assert_eq!(incremental.reused(), 2);
+        let full = Month::convert(parse(after), &Options::default()).unwrap();
+        let lines = |m: &Month| {
+            m.days
+                .iter()
+                .flat_map(|d| {
+                    let entries = d
+                        .entries
+                        .iter()
+                        .map(|e| (e.line, e.value.end.line, e.value.duration));
+                    std::iter::once((d.date.line, 0, d.times.billable_time())).chain(entries)
+                })
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(lines(&converted), lines(&full));
+        assert_eq!(converted.meta, full.meta);
+        assert!(incremental
+            .convert(parse("* Mo. 15.04.\n09:00 AA\n"))
+            .is_err());
+    }
 }
@@ -0,0 +1,276 @@
+use std::collections::{BTreeSet, HashMap};
+use std::fs::{self, File};
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+use thiserror::Error;
+
+use crate::cache::DiskCache;
+use crate::convert::{Month, Options};
+use crate::index::Index;
+use crate::parse::{from_stem, parse_month};
+use crate::Date;
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("Failed to read {path}: {1}", path = .0.display())]
+    Io(PathBuf, std::io::Error),
+    #[error("Failed to parse {path}: {1}", path = .0.display())]
+    Parse(PathBuf, crate::parse::Error),
+    #[error("Invalid times in {path}: {1}", path = .0.display())]
+    Convert(PathBuf, crate::convert::Error),
+}
+
+/// A timesheet file named after its month, e.g. `2024-04.tsh`.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct MonthFile {
+    pub month: Date,
+    pub path: PathBuf,
+}
+
+impl MonthFile {
+    #[must_use]
+    pub fn from_path(path: PathBuf) -> Option<Self> {
+        if path.extension()? != "tsh" {
+            return None;
+        }
+        let month = from_stem(path.file_stem()?.to_str()?)?;
+        Some(Self { month, path })
+    }
+
+    /// Parses the file without validating its times.
+    pub fn parse(&self) -> Result<crate::Month, Error> {
+        let file = File::open(&self.path).map_err(|e| Error::Io(self.path.clone(), e))?;
+        parse_month(&mut BufReader::new(file), self.month)
+            .map_err(|e| Error::Parse(self.path.clone(), e))
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(path = %self.path.display()))
+    )]
+    pub fn load(&self, options: &Options) -> Result<Month, Error> {
+        Month::convert(self.parse()?, options).map_err(|e| Error::Convert(self.path.clone(), e))
+    }
+}
+
+/// A loaded month with the modification time of its file and the options it was converted with.
+struct Cached {
+    modified: SystemTime,
+    options: u64,
+    month: Month,
+}
+
+/// All timesheet files of a directory, ordered by month.
+#[derive(Default)]
+pub struct Data {
+    pub files: Vec<MonthFile>,
+    cache: Mutex<HashMap<PathBuf, Cached>>,
+    /// The directory to keep a [`DiskCache`] in.
+    disk_cache: Option<PathBuf>,
+}
+
+impl std::fmt::Debug for Data {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Data")
+            .field("files", &self.files)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Data {
+    pub fn from_dir(dir: &Path) -> Result<Self, Error> {
+        let io_error = |e| Error::Io(dir.to_owned(), e);
+        let mut files = Vec::new();
+        for entry in fs::read_dir(dir).map_err(io_error)? {
+            let entry = entry.map_err(io_error)?;
+            if let Some(file) = MonthFile::from_path(entry.path()) {
+                files.push(file);
+            }
+        }
+        files.sort_by_key(|f| f.month);
+        Ok(Self {
+            files,
+            ..Self::default()
+        })
+    }
+
+    /// Only keeps the files of `year`.
+    #[must_use]
+    pub fn year(self, year: i32) -> Self {
+        Self {
+            files: self
+                .files
+                .into_iter()
+                .filter(|f| f.month.year() == year)
+                .collect(),
+            ..self
+        }
+    }
+
+    /// Also keeps the loaded months in a [`DiskCache`] in `dir` to reuse them across runs.
+    #[must_use]
+    pub fn with_disk_cache(self, dir: &Path) -> Self {
+        Self {
+            disk_cache: Some(dir.to_owned()),
+            ..self
+        }
+    }
+
+    pub fn load(&self, options: &Options) -> Result<Vec<Month>, Error> {
+        self.files.iter().map(|f| f.load(options)).collect()
+    }
+
+    /// Like [`Self::load`], but loads the files in parallel with the `rayon` feature and reuses
+    /// the months of earlier calls whose files were not modified since.
+    pub fn load_all(&self, options: &Options) -> Result<Vec<Month>, Error> {
+        let key = options.key();
+        let disk = self
+            .disk_cache
+            .as_deref()
+            .map(|dir| Mutex::new(DiskCache::read(dir)));
+        let load = |file: &MonthFile| {
+            let modified = fs::metadata(&file.path)
+                .and_then(|m| m.modified())
+                .map_err(|e| Error::Io(file.path.clone(), e))?;
+            if let Some(cached) = self.cache.lock().unwrap().get(&file.path) {
+                if cached.modified == modified && cached.options == key {
+                    debug!(path = %file.path.display(), "reusing loaded month");
+                    return Ok(cached.month.clone());
+                }
+            }
+            let name = file.path.file_name().unwrap().to_string_lossy();
+            let cached = disk
+                .as_ref()
+                .and_then(|d| d.lock().unwrap().get(&name, modified, key).cloned());
+            let month = if let Some(month) = cached {
+                debug!(path = %file.path.display(), "reusing cached month");
+                month
+            } else {
+                let month = file.load(options)?;
+                if let Some(disk) = &disk {
+                    disk.lock()
+                        .unwrap()
+                        .insert(name.into_owned(), modified, key, month.clone());
+                }
+                month
+            };
+            self.cache.lock().unwrap().insert(
+                file.path.clone(),
+                Cached {
+                    modified,
+                    options: key,
+                    month: month.clone(),
+                },
+            );
+            Ok(month)
+        };
+        #[cfg(feature = "rayon")]
+        let months = self.files.par_iter().map(load).collect();
+        #[cfg(not(feature = "rayon"))]
+        let months = self.files.iter().map(load).collect();
+        if let (Some(dir), Some(disk)) = (&self.disk_cache, disk) {
+            // The cache only saves time, failing to write it must not fail loading.
+            disk.into_inner().unwrap().write(dir).ok();
+        }
+        months
+    }
+
+    /// How often identifiers and comments are used in the files. The [`DiskCache`] keeps the
+    /// index, which is reused if it covers exactly these files.
+    pub fn index(&self, options: &Options) -> Result<Index, Error> {
+        let months = self.load_all(options)?;
+        if let Some(dir) = &self.disk_cache {
+            let cache = DiskCache::read(dir);
+            let names: BTreeSet<_> = self
+                .files
+                .iter()
+                .filter_map(|f| f.path.file_name()?.to_str())
+                .collect();
+            if cache.names().eq(names) {
+                debug!(dir = %dir.display(), "reusing cached index");
+                return Ok(cache.index().clone());
+            }
+        }
+        let mut index = Index::default();
+        for month in &months {
+            index.add_days(&month.days);
+        }
+        Ok(index)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use super::*;
+
+    #[test]
+    fn test_month_file() {
+        assert_eq!(
+            MonthFile::from_path(PathBuf::from("a/2024-04.tsh")),
+            Some(MonthFile {
+                month: Date::new(NaiveDate::from_ymd_opt(2024, 4, 1).unwrap()),
+                path: PathBuf::from("a/2024-04.tsh"),
+            })
+        );
+        assert_eq!(MonthFile::from_path(PathBuf::from("2024-04.txt")), None);
+        assert_eq!(MonthFile::from_path(PathBuf::from("notes.tsh")), None);
+        assert_eq!(MonthFile::from_path(PathBuf::from("2024-04")), None);
+    }
+
+    #[test]
+    fn test_index() {
+        let dir = tempfile::tempdir().unwrap();
+        let dir = dir.path();
+        fs::write(
+            dir.join("2024-04.tsh"),
+            "* Mo. 15.04.\n09:00 AA\n10:00 BB\n12:00\n",
+        )
+        .unwrap();
+        fs::write(dir.join("2024-05.tsh"), "* Di. 14.05.\n09:00 BB\n10:00\n").unwrap();
+        let data = Data::from_dir(dir).unwrap().with_disk_cache(dir);
+        let index = data.index(&Options::default()).unwrap();
+        assert_eq!(index.identifiers(""), vec!["BB", "AA"]);
+        assert_eq!(DiskCache::read(dir).index().count("BB"), 2);
+    }
+
+    #[test]
+    fn test_load_all() {
+        let dir = tempfile::tempdir().unwrap();
+        let dir = dir.path();
+        fs::write(dir.join("2024-04.tsh"), "* Mo. 15.04.\n09:00 AA\n12:00\n").unwrap();
+        fs::write(dir.join("2024-05.tsh"), "* Di. 14.05.\n09:00 AA\n10:00\n").unwrap();
+        let data = Data::from_dir(dir).unwrap();
+        let months = data.load_all(&Options::default()).unwrap();
+        assert_eq!(months.len(), 2);
+        assert_eq!(data.cache.lock().unwrap().len(), 2);
+        let again = data.load_all(&Options::default()).unwrap();
+        let totals = |months: &[Month]| {
+            months
+                .iter()
+                .map(|m| m.times().billable_time())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(totals(&again), totals(&months));
+        assert_eq!(data.index(&Options::default()).unwrap().count("AA"), 2);
+        fs::write(dir.join("2024-05.tsh"), "* Di. 14.05.\n09:00 AA\n").unwrap();
+        let cached = data.cache.lock().unwrap()[&dir.join("2024-05.tsh")].modified;
+        // Filesystems with a coarse mtime need the time to be set explicitly.
+        File::options()
+            .write(true)
+            .open(dir.join("2024-05.tsh"))
+            .unwrap()
+            .set_modified(cached + std::time::Duration::from_secs(1))
+            .unwrap();
+        assert!(matches!(
+            data.load_all(&Options::default()),
+            Err(Error::Convert(..))
+        ));
+    }
+}
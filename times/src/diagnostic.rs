@@ -0,0 +1,118 @@
+use std::fmt::{Display, Formatter};
+
+use crate::lint::Warning;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd)]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+impl Severity {
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        }
+    }
+}
+
+/// A problem of a file in a uniform shape for machine-readable output.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Diagnostic {
+    /// Line number starting at 1, `None` if the problem concerns the whole file.
+    pub line: Option<usize>,
+    pub severity: Severity,
+    pub code: String,
+    pub message: String,
+}
+
+impl Diagnostic {
+    /// All problems that prevented parsing a file.
+    #[must_use]
+    pub fn from_parse_error(error: &crate::parse::Error) -> Vec<Self> {
+        match error {
+            crate::parse::Error::Many(errors) => errors
+                .0
+                .iter()
+                .map(|e| Self {
+                    line: Some(e.line),
+                    severity: Severity::Error,
+                    code: "syntax".to_owned(),
+                    message: e.value.to_string(),
+                })
+                .collect(),
+            crate::parse::Error::ExpectedDay(line) => vec![Self {
+                line: Some(*line),
+                severity: Severity::Error,
+                code: "syntax".to_owned(),
+                message: error.to_string(),
+            }],
+            crate::parse::Error::Io(e) => vec![Self::io(e)],
+        }
+    }
+
+    #[must_use]
+    pub fn io(error: &std::io::Error) -> Self {
+        Self {
+            line: None,
+            severity: Severity::Error,
+            code: "io".to_owned(),
+            message: error.to_string(),
+        }
+    }
+}
+
+impl From<&crate::convert::Error> for Diagnostic {
+    fn from(error: &crate::convert::Error) -> Self {
+        Self {
+            line: Some(error.line()),
+            severity: Severity::Error,
+            code: error.code().to_owned(),
+            message: error.to_string(),
+        }
+    }
+}
+
+impl From<&Warning> for Diagnostic {
+    fn from(warning: &Warning) -> Self {
+        Self {
+            line: Some(warning.line()),
+            severity: Severity::Warning,
+            code: warning.code().to_owned(),
+            message: warning.to_string(),
+        }
+    }
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.severity.as_str(), self.message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use crate::parse::parse_month;
+    use crate::Date;
+
+    use super::*;
+
+    #[test]
+    fn test_from_parse_error() {
+        let month = Date::new(NaiveDate::from_ymd_opt(2024, 4, 1).unwrap());
+        let error = parse_month("* Mo. 15.04.\n9:00 AA\n".as_bytes(), month).unwrap_err();
+        assert_eq!(
+            Diagnostic::from_parse_error(&error),
+            vec![Diagnostic {
+                line: Some(2),
+                severity: Severity::Error,
+                code: "syntax".to_owned(),
+                message: "Invalid time format".to_owned(),
+            }]
+        );
+    }
+}
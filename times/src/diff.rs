@@ -0,0 +1,158 @@
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter, Result};
+
+use crate::convert::{Day, Entry, Month};
+use crate::{Date, Minutes, SignedMinutes};
+
+/// A difference between the entries of a day, `Changed` entries start at the same time.
+pub enum Change<'a> {
+    Added(&'a Entry),
+    Removed(&'a Entry),
+    Changed(&'a Entry, &'a Entry),
+}
+
+pub struct DayDiff<'a> {
+    pub date: Date,
+    pub changes: Vec<Change<'a>>,
+    /// Difference of the billable time.
+    pub delta: SignedMinutes,
+}
+
+/// The changes from one month to another.
+pub struct Diff<'a> {
+    pub days: Vec<DayDiff<'a>>,
+    pub delta: SignedMinutes,
+}
+
+fn same(a: &Entry, b: &Entry) -> bool {
+    a.start.value == b.start.value
+        && a.end.value == b.end.value
+        && a.identifier == b.identifier
+        && a.comment == b.comment
+}
+
+fn billable_time(day: Option<&Day>) -> Minutes {
+    day.map(|d| d.times.billable_time()).unwrap_or_default()
+}
+
+fn diff_day<'a>(date: Date, old: Option<&'a Day>, new: Option<&'a Day>) -> DayDiff<'a> {
+    let entries = |day: Option<&'a Day>| {
+        day.into_iter()
+            .flat_map(|d| &d.entries)
+            .map(|e| &e.value)
+            .collect::<Vec<_>>()
+    };
+    let (old_entries, mut new_entries) = (entries(old), entries(new));
+    let mut changes = Vec::new();
+    for old in old_entries {
+        if let Some(index) = new_entries.iter().position(|new| same(old, new)) {
+            new_entries.remove(index);
+        } else if let Some(index) = new_entries
+            .iter()
+            .position(|new| new.start.value == old.start.value)
+        {
+            changes.push(Change::Changed(old, new_entries.remove(index)));
+        } else {
+            changes.push(Change::Removed(old));
+        }
+    }
+    changes.extend(new_entries.into_iter().map(Change::Added));
+    changes.sort_by_key(|c| match c {
+        Change::Added(e) | Change::Removed(e) | Change::Changed(e, _) => e.start.value,
+    });
+    DayDiff {
+        date,
+        changes,
+        delta: billable_time(new) - billable_time(old),
+    }
+}
+
+impl<'a> Diff<'a> {
+    #[must_use]
+    pub fn new(old: &'a Month, new: &'a Month) -> Self {
+        let mut days: BTreeMap<Date, (Option<&Day>, Option<&Day>)> = BTreeMap::new();
+        for day in &old.days {
+            days.entry(day.date.value).or_default().0 = Some(day);
+        }
+        for day in &new.days {
+            days.entry(day.date.value).or_default().1 = Some(day);
+        }
+        let days: Vec<_> = days
+            .into_iter()
+            .map(|(date, (old, new))| diff_day(date, old, new))
+            .filter(|d| !d.changes.is_empty())
+            .collect();
+        let delta = days.iter().map(|d| d.delta).sum();
+        Self { days, delta }
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.days.is_empty()
+    }
+}
+
+fn write_entry(f: &mut Formatter<'_>, prefix: char, entry: &Entry) -> Result {
+    write!(
+        f,
+        "{prefix} {} - {} {}",
+        entry.start.value, entry.end.value, entry.identifier
+    )?;
+    if let Some(comment) = &entry.comment {
+        write!(f, " {comment}")?;
+    }
+    writeln!(f)
+}
+
+impl Display for Diff<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        for day in &self.days {
+            writeln!(f, "* {} ({})", day.date, day.delta)?;
+            for change in &day.changes {
+                match change {
+                    Change::Added(entry) => write_entry(f, '+', entry)?,
+                    Change::Removed(entry) => write_entry(f, '-', entry)?,
+                    Change::Changed(old, new) => {
+                        write_entry(f, '-', old)?;
+                        write_entry(f, '+', new)?;
+                    }
+                }
+            }
+        }
+        write!(f, "Total: {}", self.delta)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fixture::month;
+
+    use super::*;
+
+    #[test]
+    fn test_diff() {
+        let old = month(
+            4,
+            "* Mo. 15.04.\n09:00 AA A\n10:00 BB\n12:00\n* Di. 16.04.\n09:00 AA\n10:00\n",
+        );
+        let new = month(
+            4,
+            "* Mo. 15.04.\n09:00 AA B\n10:00 BB\n12:30\n* Mi. 17.04.\n09:00 AA\n10:00\n",
+        );
+        let diff = Diff::new(&old, &new);
+        assert_eq!(
+            diff.to_string(),
+            "* Mo. 15.04. (+00:30)
+- 09:00 - 10:00 AA A
++ 09:00 - 10:00 AA B
+- 10:00 - 12:00 BB
++ 10:00 - 12:30 BB
+* Di. 16.04. (-01:00)
+- 09:00 - 10:00 AA
+* Mi. 17.04. (+01:00)
++ 09:00 - 10:00 AA
+Total: +00:30"
+        );
+        assert!(Diff::new(&old, &old).is_empty());
+    }
+}
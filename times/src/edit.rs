@@ -0,0 +1,373 @@
+use std::fs;
+use std::path::Path;
+
+use chrono::Datelike;
+
+use thiserror::Error;
+
+use crate::ast::{Block, Document, Node};
+use crate::{Date, Entry, Time, Topic};
+
+/// A machine-applicable fix replacing whole lines, line numbers start at 1.
+#[derive(Debug, Eq, PartialEq)]
+pub struct Fix {
+    pub line: usize,
+    pub description: String,
+    pub replacements: Vec<(usize, String)>,
+}
+
+impl Fix {
+    #[must_use]
+    pub fn new(line: usize, description: String, replacements: Vec<(usize, String)>) -> Self {
+        Self {
+            line,
+            description,
+            replacements,
+        }
+    }
+
+    pub fn apply(&self, document: &mut Document) {
+        for (line, text) in &self.replacements {
+            document.replace(line - 1, text);
+        }
+    }
+}
+
+/// Replaces the leading time of an entry line.
+pub(crate) fn with_time(text: &str, time: Time) -> String {
+    let text = text.trim();
+    let rest = text.find(char::is_whitespace).map_or("", |i| &text[i..]);
+    format!("{time}{rest}")
+}
+
+fn month_of(date: Date) -> Date {
+    Date(date.0.with_day(1).unwrap())
+}
+
+/// Returns the block of `date`, inserting an empty day before the first later day if it is missing.
+pub fn find_or_insert_day(document: &mut Document, date: Date) -> Block {
+    if let Some(block) = document.block(date) {
+        return block;
+    }
+    let next = document.blocks().into_iter().find(|b| {
+        document.lines()[b.header]
+            .date()
+            .is_some_and(|d| d.0 > date.0)
+    });
+    let header = format!("* {date}");
+    let index = if let Some(next) = next {
+        let mut index = next.header;
+        while index > 0 && matches!(document.lines()[index - 1].node(), Node::Comment) {
+            index -= 1;
+        }
+        document.insert(index, &header);
+        document.insert(index + 1, "");
+        index
+    } else {
+        if document
+            .lines()
+            .last()
+            .is_some_and(|l| !matches!(l.node(), Node::Blank))
+        {
+            document.insert(document.lines().len(), "");
+        }
+        let index = document.lines().len();
+        document.insert(index, &header);
+        index
+    };
+    Block {
+        header: index,
+        entries: Vec::new(),
+    }
+}
+
+/// Inserts `entry` into the day `date` before the first entry with a later time and returns its index.
+pub fn insert_entry_into(document: &mut Document, date: Date, entry: &Entry) -> usize {
+    let block = find_or_insert_day(document, date);
+    let index = block
+        .entries
+        .iter()
+        .copied()
+        .find(|&i| {
+            document.lines()[i]
+                .entry()
+                .is_some_and(|e| e.time > entry.time)
+        })
+        .unwrap_or_else(|| block.end());
+    document.insert(index, &entry.to_string());
+    index
+}
+
+pub fn insert_entry(path: &Path, date: Date, entry: &Entry) -> std::io::Result<()> {
+    let mut document = Document::read(fs::File::open(path)?, month_of(date))?;
+    insert_entry_into(&mut document, date, entry);
+    crate::atomic::write(path, document.to_string())
+}
+
+#[derive(Debug, Error, Eq, PartialEq)]
+pub enum InsertError {
+    #[error("Day {0} already has entries")]
+    DayExists(Date),
+    #[error("Day {0} already has an entry at {1}")]
+    EntryExists(Date, Time),
+    #[error("Day {0} already has entries from {1} to {2}")]
+    Overlaps(Date, Time, Time),
+}
+
+/// Inserts `topic` from `start` to `end` into the day `date` and continues with whatever was
+/// running at `start` afterwards. A break at `start` is replaced, any other entry starting in
+/// the span is an error.
+pub fn insert_span(
+    document: &mut Document,
+    date: Date,
+    start: Time,
+    end: Time,
+    topic: &Topic,
+) -> Result<(), InsertError> {
+    let block = find_or_insert_day(document, date);
+    let entries: Vec<_> = block
+        .entries
+        .iter()
+        .filter_map(|&i| Some((i, document.lines()[i].entry()?)))
+        .collect();
+    if let Some((_, existing)) = entries.iter().find(|(_, e)| e.time == start) {
+        if existing.topic == *topic {
+            return Err(InsertError::EntryExists(date, start));
+        }
+    }
+    let overlaps =
+        |e: &Entry| e.time > start && e.time < end || e.time == start && e.topic != Topic::Break;
+    if entries.iter().any(|(_, e)| overlaps(e)) {
+        return Err(InsertError::Overlaps(date, start, end));
+    }
+    let resumed = entries
+        .iter()
+        .rev()
+        .find(|(_, e)| e.time <= start)
+        .map_or(Topic::Break, |(_, e)| e.topic.clone());
+    let has_end = entries.iter().any(|(_, e)| e.time == end);
+    let replaced = entries
+        .iter()
+        .find(|(_, e)| e.time == start)
+        .map(|(i, _)| *i);
+    let entry = Entry {
+        time: start,
+        topic: topic.clone(),
+    };
+    match replaced {
+        Some(index) => document.replace(index, &entry.to_string()),
+        None => {
+            insert_entry_into(document, date, &entry);
+        }
+    }
+    if !has_end {
+        let entry = Entry {
+            time: end,
+            topic: resumed,
+        };
+        insert_entry_into(document, date, &entry);
+    }
+    Ok(())
+}
+
+/// Inserts the lines of `text`, e.g. a rendered template, as the entries of the day `date` and
+/// returns the index of its header. Blank lines and day headers in `text` are skipped.
+pub fn insert_day(document: &mut Document, date: Date, text: &str) -> Result<usize, InsertError> {
+    if document.block(date).is_some_and(|b| !b.entries.is_empty()) {
+        return Err(InsertError::DayExists(date));
+    }
+    let block = find_or_insert_day(document, date);
+    let inserted = Document::parse(text, document.month());
+    let lines = inserted
+        .lines()
+        .iter()
+        .filter(|l| !matches!(l.node(), Node::Blank | Node::Day(_)));
+    for (offset, line) in lines.enumerate() {
+        document.insert(block.header + 1 + offset, line.text());
+    }
+    Ok(block.header)
+}
+
+#[derive(Debug, Error, Eq, PartialEq)]
+pub enum AmendError {
+    #[error("There is no entry to amend")]
+    NoEntry,
+    #[error("The entry in line {0} is a break and has no identifier or comment")]
+    Break(usize),
+}
+
+/// Changes to the parts of an entry, `None` keeps the current value.
+#[derive(Debug, Default)]
+pub struct Amendment {
+    pub time: Option<Time>,
+    pub identifier: Option<String>,
+    pub comment: Option<Option<String>>,
+}
+
+/// Index of the last successfully parsed entry line.
+#[must_use]
+pub fn last_entry(document: &Document) -> Option<usize> {
+    document.lines().iter().rposition(|l| l.entry().is_some())
+}
+
+/// Applies `amendment` to the last entry of `document` and returns its index and the new entry.
+pub fn amend_last(
+    document: &mut Document,
+    amendment: Amendment,
+) -> Result<(usize, Entry), AmendError> {
+    let index = last_entry(document).ok_or(AmendError::NoEntry)?;
+    let Entry { time, topic } = document.lines()[index].entry().unwrap().clone();
+    let topic = match topic {
+        Topic::Break if amendment.identifier.is_some() || amendment.comment.is_some() => {
+            return Err(AmendError::Break(index + 1));
+        }
+        Topic::Break => Topic::Break,
+        Topic::Project {
+            identifier,
+            comment,
+        } => Topic::Project {
+            identifier: amendment.identifier.unwrap_or(identifier),
+            comment: amendment.comment.unwrap_or(comment),
+        },
+    };
+    let entry = Entry {
+        time: amendment.time.unwrap_or(time),
+        topic,
+    };
+    document.replace(index, &entry.to_string());
+    Ok((index, entry))
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use super::*;
+    use crate::{Time, Topic};
+
+    fn date(day: u32) -> Date {
+        Date(NaiveDate::from_ymd_opt(2024, 4, day).unwrap())
+    }
+
+    fn entry(hour: u8, identifier: &str) -> Entry {
+        Entry {
+            time: Time::new(hour, 0).unwrap(),
+            topic: Topic::Project {
+                identifier: identifier.to_owned(),
+                comment: None,
+            },
+        }
+    }
+
+    const TEXT: &str = "* Mo. 15.04.\n09:00 AA\n12:00\n\n# comment\n* Mi. 17.04.\n09:00 AA\n";
+
+    #[test]
+    fn test_insert_into_existing_day() {
+        let mut document = Document::parse(TEXT, date(1));
+        assert_eq!(
+            insert_entry_into(&mut document, date(15), &entry(10, "BB")),
+            2
+        );
+        assert_eq!(
+            insert_entry_into(&mut document, date(15), &entry(13, "CC")),
+            4
+        );
+        assert_eq!(
+            document.to_string(),
+            "* Mo. 15.04.\n09:00 AA\n10:00 BB\n12:00\n13:00 CC\n\n# comment\n* Mi. 17.04.\n09:00 AA\n"
+        );
+    }
+
+    #[test]
+    fn test_amend() {
+        let mut document = Document::parse("* Mo. 15.04.\n09:00 AA A\n10:00 BB B\n# c\n", date(1));
+        let amendment = Amendment {
+            time: Time::new(10, 3),
+            comment: Some(None),
+            ..Amendment::default()
+        };
+        assert_eq!(
+            amend_last(&mut document, amendment).map(|(index, _)| index),
+            Ok(2)
+        );
+        assert_eq!(
+            document.to_string(),
+            "* Mo. 15.04.\n09:00 AA A\n10:03 BB\n# c\n"
+        );
+
+        let mut document = Document::parse("* Mo. 15.04.\n09:00 AA A\n10:00\n", date(1));
+        let amendment = Amendment {
+            identifier: Some("CC".to_owned()),
+            ..Amendment::default()
+        };
+        assert_eq!(
+            amend_last(&mut document, amendment),
+            Err(AmendError::Break(3))
+        );
+        let mut document = Document::parse("* Mo. 15.04.\n", date(1));
+        assert_eq!(
+            amend_last(&mut document, Amendment::default()),
+            Err(AmendError::NoEntry)
+        );
+    }
+
+    #[test]
+    fn test_insert_rendered_day() {
+        let mut document = Document::parse(TEXT, date(1));
+        let rendered = "\n* Di. 16.04.\n09:00 Urlaub\n17:00\n";
+        assert_eq!(insert_day(&mut document, date(16), rendered), Ok(4));
+        assert_eq!(
+            insert_day(&mut document, date(15), rendered),
+            Err(InsertError::DayExists(date(15)))
+        );
+        assert_eq!(insert_day(&mut document, date(30), rendered), Ok(12));
+        assert_eq!(
+            document.to_string(),
+            "* Mo. 15.04.\n09:00 AA\n12:00\n\n* Di. 16.04.\n09:00 Urlaub\n17:00\n\n# comment\n* Mi. 17.04.\n09:00 AA\n\n* Di. 30.04.\n09:00 Urlaub\n17:00\n"
+        );
+    }
+
+    #[test]
+    fn test_insert_meeting() {
+        let mut document = Document::parse(TEXT, date(1));
+        let span = |start, end| (Time::new(start, 0).unwrap(), Time::new(end, 0).unwrap());
+        let meeting = entry(0, "Meeting").topic;
+        let mut insert =
+            |date, (start, end)| insert_span(&mut document, date, start, end, &meeting);
+        assert_eq!(insert(date(15), span(10, 11)), Ok(()));
+        assert_eq!(insert(date(15), span(12, 13)), Ok(()));
+        assert_eq!(insert(date(17), span(8, 9)), Ok(()));
+        assert_eq!(insert(date(16), span(14, 15)), Ok(()));
+        assert_eq!(
+            insert(date(15), span(10, 11)),
+            Err(InsertError::EntryExists(
+                date(15),
+                Time::new(10, 0).unwrap()
+            ))
+        );
+        assert_eq!(
+            insert(date(15), span(8, 10)),
+            Err(InsertError::Overlaps(
+                date(15),
+                Time::new(8, 0).unwrap(),
+                Time::new(10, 0).unwrap()
+            ))
+        );
+        assert_eq!(
+            document.to_string(),
+            "* Mo. 15.04.\n09:00 AA\n10:00 Meeting\n11:00 AA\n12:00 Meeting\n13:00\n\n* Di. 16.04.\n14:00 Meeting\n15:00\n\n# comment\n* Mi. 17.04.\n08:00 Meeting\n09:00 AA\n"
+        );
+    }
+
+    #[test]
+    fn test_insert_missing_day() {
+        let mut document = Document::parse(TEXT, date(1));
+        insert_entry_into(&mut document, date(16), &entry(10, "BB"));
+        insert_entry_into(&mut document, date(18), &entry(10, "CC"));
+        insert_entry_into(&mut document, date(12), &entry(10, "DD"));
+        assert_eq!(
+            document.to_string(),
+            "* Fr. 12.04.\n10:00 DD\n\n* Mo. 15.04.\n09:00 AA\n12:00\n\n* Di. 16.04.\n10:00 BB\n\n# comment\n* Mi. 17.04.\n09:00 AA\n\n* Do. 18.04.\n10:00 CC\n"
+        );
+    }
+}
@@ -0,0 +1,158 @@
+use std::fmt::{Display, Formatter};
+use std::path::{Path, PathBuf};
+
+use thiserror::Error;
+
+use crate::diagnostic::{Diagnostic, Severity};
+
+/// The error of the module that failed.
+#[derive(Debug, Error)]
+pub enum Kind {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Parse(#[from] crate::parse::Error),
+    #[error(transparent)]
+    Convert(#[from] crate::convert::Error),
+    #[error(transparent)]
+    Lint(#[from] crate::lint::Warning),
+    #[error(transparent)]
+    Config(#[from] crate::config::Error),
+    #[error(transparent)]
+    Template(#[from] crate::generate::Error),
+    #[error(transparent)]
+    Insert(#[from] crate::edit::InsertError),
+    #[error(transparent)]
+    Amend(#[from] crate::edit::AmendError),
+    #[error(transparent)]
+    Shift(#[from] crate::transform::ShiftError),
+    #[error(transparent)]
+    Journal(#[from] crate::journal::Error),
+    #[error(transparent)]
+    Import(#[from] crate::import::Error),
+}
+
+/// An error of any module together with the file and line it concerns, so that frontends can
+/// report all errors the same way.
+#[derive(Debug, Error)]
+pub struct Error {
+    pub path: Option<PathBuf>,
+    /// Line number starting at 1, `None` if the error concerns the whole file or no file.
+    pub line: Option<usize>,
+    pub severity: Severity,
+    #[source]
+    pub kind: Box<Kind>,
+}
+
+impl Error {
+    #[must_use]
+    pub fn with_path(self, path: &Path) -> Self {
+        Self {
+            path: Some(path.to_owned()),
+            ..self
+        }
+    }
+
+    /// The problems in a uniform shape, one per line for files with several syntax errors.
+    #[must_use]
+    pub fn diagnostics(&self) -> Vec<Diagnostic> {
+        match &*self.kind {
+            Kind::Parse(e) => Diagnostic::from_parse_error(e),
+            Kind::Convert(e) => vec![e.into()],
+            Kind::Lint(w) => vec![w.into()],
+            Kind::Io(e) => vec![Diagnostic::io(e)],
+            kind => vec![Diagnostic {
+                line: self.line,
+                severity: self.severity,
+                code: "error".to_owned(),
+                message: kind.to_string(),
+            }],
+        }
+    }
+}
+
+impl<E: Into<Kind>> From<E> for Error {
+    fn from(error: E) -> Self {
+        let kind = error.into();
+        let line = match &kind {
+            Kind::Parse(crate::parse::Error::ExpectedDay(line)) => Some(*line),
+            Kind::Parse(crate::parse::Error::Many(errors)) if errors.0.len() == 1 => {
+                Some(errors.0[0].line)
+            }
+            Kind::Convert(e) => Some(e.line()),
+            Kind::Lint(w) => Some(w.line()),
+            _ => None,
+        };
+        let severity = match kind {
+            Kind::Lint(_) => Severity::Warning,
+            _ => Severity::Error,
+        };
+        Self {
+            path: None,
+            line,
+            severity,
+            kind: Box::new(kind),
+        }
+    }
+}
+
+impl From<crate::data::Error> for Error {
+    fn from(error: crate::data::Error) -> Self {
+        match error {
+            crate::data::Error::Io(path, e) => Self::from(e).with_path(&path),
+            crate::data::Error::Parse(path, e) => Self::from(e).with_path(&path),
+            crate::data::Error::Convert(path, e) => Self::from(e).with_path(&path),
+        }
+    }
+}
+
+/// The messages of errors with a line already mention it, so only the path is prepended.
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if let Some(path) = &self.path {
+            write!(f, "{}: ", path.display())?;
+        }
+        write!(f, "{}: {}", self.severity.as_str(), self.kind)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use crate::convert::{Month, Options};
+    use crate::parse::parse_month;
+    use crate::Date;
+
+    use super::*;
+
+    fn load(text: &str) -> Result<Month, Error> {
+        let month = Date::new(NaiveDate::from_ymd_opt(2024, 4, 1).unwrap());
+        Ok(Month::convert(
+            parse_month(text.as_bytes(), month)?,
+            &Options::default(),
+        )?)
+    }
+
+    #[test]
+    fn test_error() {
+        let error = load("* Mo. 15.04.\n9:00 AA\n").err().unwrap();
+        assert_eq!(error.line, Some(2));
+        assert!(matches!(*error.kind, Kind::Parse(_)));
+        assert_eq!(error.diagnostics()[0].code, "syntax");
+
+        let error = load("* Mo. 15.04.\n09:00 AA\n")
+            .err()
+            .unwrap()
+            .with_path(Path::new("2024-04.tsh"));
+        assert_eq!(
+            error.to_string(),
+            "2024-04.tsh: error: Time span in line 2 is never terminated"
+        );
+        assert!(std::error::Error::source(&error).is_some());
+
+        let error = Error::from(crate::lint::Warning::WeekendWork(3));
+        assert_eq!(error.severity, Severity::Warning);
+        assert_eq!(error.to_string(), "warning: Day in line 3 is on a weekend");
+    }
+}
@@ -0,0 +1,314 @@
+use std::fmt::{Display, Formatter, Result};
+
+use chrono::Datelike;
+use serde::Deserialize;
+
+use crate::convert::Month;
+use crate::{Date, Minutes};
+
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Locale {
+    #[default]
+    En,
+    De,
+}
+
+impl Locale {
+    #[must_use]
+    pub fn decimal_separator(self) -> char {
+        match self {
+            Locale::En => '.',
+            Locale::De => ',',
+        }
+    }
+
+    #[must_use]
+    pub fn field_separator(self) -> char {
+        match self {
+            Locale::En => ',',
+            Locale::De => ';',
+        }
+    }
+
+    #[must_use]
+    pub fn format_hours(self, minutes: Minutes) -> String {
+        let minutes = minutes.into_inner();
+        let hundredths = ((minutes % 60) * 100 + 30) / 60;
+        format!(
+            "{}{}{hundredths:0>2}",
+            minutes / 60,
+            self.decimal_separator()
+        )
+    }
+
+    #[must_use]
+    pub fn format_date(self, date: Date) -> String {
+        let Date(date) = date;
+        match self {
+            Locale::En => format!("{:0>2}/{:0>2}/{}", date.month(), date.day(), date.year()),
+            Locale::De => format!("{:0>2}.{:0>2}.{}", date.day(), date.month(), date.year()),
+        }
+    }
+}
+
+pub struct Csv<'a> {
+    month: &'a Month,
+    locale: Locale,
+}
+
+impl<'a> Csv<'a> {
+    #[must_use]
+    pub fn new(month: &'a Month, locale: Locale) -> Self {
+        Self { month, locale }
+    }
+
+    fn write_field(&self, f: &mut Formatter<'_>, field: &str) -> Result {
+        let separator = self.locale.field_separator();
+        if field.contains([separator, '"', '\n']) {
+            write!(f, "\"{}\"", field.replace('"', "\"\""))
+        } else {
+            write!(f, "{field}")
+        }
+    }
+
+    fn write_row(&self, f: &mut Formatter<'_>, fields: &[&str]) -> Result {
+        let mut first = true;
+        for field in fields {
+            if first {
+                first = false;
+            } else {
+                write!(f, "{}", self.locale.field_separator())?;
+            }
+            self.write_field(f, field)?;
+        }
+        writeln!(f)
+    }
+}
+
+/// A spreadsheet with a sheet per month, a row per entry and the hours of every day and of the
+/// month as formulas.
+#[cfg(feature = "xlsx")]
+pub struct Xlsx<'a> {
+    months: &'a [Month],
+    locale: Locale,
+}
+
+#[cfg(feature = "xlsx")]
+impl<'a> Xlsx<'a> {
+    const HEADER: [&'static str; 7] = [
+        "Date",
+        "Start",
+        "End",
+        "Identifier",
+        "Comment",
+        "Hours",
+        "Day total",
+    ];
+
+    #[must_use]
+    pub fn new(months: &'a [Month], locale: Locale) -> Self {
+        Self { months, locale }
+    }
+
+    fn hours(minutes: Minutes) -> f64 {
+        f64::from(u32::try_from(minutes.into_inner()).unwrap_or(u32::MAX)) / 60.0
+    }
+
+    fn sum(first: u32, last: u32, minutes: Minutes) -> rust_xlsxwriter::Formula {
+        rust_xlsxwriter::Formula::new(format!("=SUM(F{}:F{})", first + 1, last + 1))
+            .set_result(Self::hours(minutes).to_string())
+    }
+
+    fn write_month(
+        &self,
+        sheet: &mut rust_xlsxwriter::Worksheet,
+        month: &Month,
+    ) -> std::result::Result<(), rust_xlsxwriter::XlsxError> {
+        use rust_xlsxwriter::{ExcelDateTime, Format};
+
+        let bold = Format::new().set_bold();
+        let date_format = Format::new().set_num_format(match self.locale {
+            Locale::En => "mm/dd/yyyy",
+            Locale::De => "dd.mm.yyyy",
+        });
+        let time_format = Format::new().set_num_format("hh:mm");
+        let hours_format = Format::new().set_num_format("0.00");
+        let mut row = 0;
+        for (key, value) in &month.meta.values {
+            sheet.write_string(row, 0, key)?;
+            sheet.write_string(row, 1, value)?;
+            row += 1;
+        }
+        if row > 0 {
+            row += 1;
+        }
+        sheet.write_row_with_format(row, 0, Self::HEADER, &bold)?;
+        sheet.set_freeze_panes(row + 1, 0)?;
+        let first_entry = row + 1;
+        for day in &month.days {
+            let date = day.date.value.0;
+            let date = ExcelDateTime::from_ymd(
+                u16::try_from(date.year()).unwrap_or_default(),
+                u8::try_from(date.month()).unwrap(),
+                u8::try_from(date.day()).unwrap(),
+            )?;
+            let first = row + 1;
+            for entry in &day.entries {
+                let entry = &entry.value;
+                row += 1;
+                sheet.write_datetime_with_format(row, 0, &date, &date_format)?;
+                for (column, time) in [(1, entry.start.value), (2, entry.end.value)] {
+                    let time = ExcelDateTime::from_hms(time.hour.into(), time.minute, 0)?;
+                    sheet.write_datetime_with_format(row, column, &time, &time_format)?;
+                }
+                sheet.write_string(row, 3, entry.identifier.as_str())?;
+                sheet.write_string(row, 4, entry.comment.as_deref().unwrap_or_default())?;
+                sheet.write_number_with_format(
+                    row,
+                    5,
+                    Self::hours(entry.duration),
+                    &hours_format,
+                )?;
+            }
+            if row >= first {
+                let total = day.entries.iter().map(|e| e.value.duration).sum();
+                sheet.write_formula_with_format(
+                    row,
+                    6,
+                    Self::sum(first, row, total),
+                    &hours_format,
+                )?;
+            }
+        }
+        // A month without entries has nothing to sum up.
+        if row >= first_entry {
+            let total = month
+                .days
+                .iter()
+                .flat_map(|d| &d.entries)
+                .map(|e| e.value.duration)
+                .sum();
+            let formula = Self::sum(first_entry, row, total);
+            row += 1;
+            sheet.write_string_with_format(row, 0, "Total", &bold)?;
+            sheet.write_formula_with_format(row, 5, formula, &hours_format.set_bold())?;
+        }
+        sheet.set_column_width(0, 12)?;
+        sheet.set_column_width(4, 40)?;
+        Ok(())
+    }
+
+    /// The contents of the `.xlsx` file.
+    pub fn to_bytes(&self) -> std::result::Result<Vec<u8>, rust_xlsxwriter::XlsxError> {
+        let mut workbook = rust_xlsxwriter::Workbook::new();
+        for month in self.months {
+            let sheet = workbook.add_worksheet();
+            sheet.set_name(month.month.0.format("%Y-%m").to_string())?;
+            self.write_month(sheet, month)?;
+        }
+        workbook.save_to_buffer()
+    }
+}
+
+impl Display for Csv<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        for (key, value) in &self.month.meta.values {
+            self.write_row(f, &[key, value])?;
+        }
+        if !self.month.meta.values.is_empty() {
+            writeln!(f)?;
+        }
+        self.write_row(
+            f,
+            &["Date", "Start", "End", "Identifier", "Comment", "Hours"],
+        )?;
+        for day in &self.month.days {
+            let date = self.locale.format_date(day.date.value);
+            for entry in &day.entries {
+                let entry = &entry.value;
+                self.write_row(
+                    f,
+                    &[
+                        &date,
+                        &entry.start.value.to_string(),
+                        &entry.end.value.to_string(),
+                        entry.identifier.as_str(),
+                        entry.comment.as_deref().unwrap_or_default(),
+                        &self.locale.format_hours(entry.duration),
+                    ],
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fixture::month;
+
+    use super::*;
+
+    #[test]
+    fn test_format_hours() {
+        assert_eq!(Locale::En.format_hours(Minutes::from(7 * 60 + 45)), "7.75");
+        assert_eq!(Locale::De.format_hours(Minutes::from(7 * 60 + 45)), "7,75");
+        assert_eq!(Locale::De.format_hours(Minutes::from(3)), "0,05");
+        assert_eq!(Locale::En.format_hours(Minutes::from(20)), "0.33");
+        assert_eq!(Locale::En.format_hours(Minutes::from(59)), "0.98");
+    }
+
+    #[test]
+    fn test_csv() {
+        let text = r#"
+        * Sa. 20.04.
+        09:00 AA A, "B"
+        12:30
+        "#;
+        let month = month(4, text);
+
+        assert_eq!(
+            Csv::new(&month, Locale::En).to_string(),
+            "Date,Start,End,Identifier,Comment,Hours\n04/20/2024,09:00,12:30,AA,\"A, \"\"B\"\"\",3.50\n"
+        );
+        assert_eq!(
+            Csv::new(&month, Locale::De).to_string(),
+            "Date;Start;End;Identifier;Comment;Hours\n20.04.2024;09:00;12:30;AA;\"A, \"\"B\"\"\";3,50\n"
+        );
+    }
+
+    #[cfg(feature = "xlsx")]
+    #[test]
+    fn test_xlsx() {
+        use std::io::Cursor;
+
+        use calamine::{open_workbook_from_rs, Data, Reader};
+
+        let text = "#! hours_per_week: 40\n* Mo. 15.04.\n09:00 AA Daily\n09:15 BB\n12:00\n* Di. 16.04.\n09:00 AA\n10:30\n";
+        let months = [month(4, text), month(5, "")];
+        let bytes = Xlsx::new(&months, Locale::De).to_bytes().unwrap();
+
+        let mut workbook: calamine::Xlsx<_> = open_workbook_from_rs(Cursor::new(bytes)).unwrap();
+        assert_eq!(workbook.sheet_names(), ["2024-04", "2024-05"]);
+        let values = workbook.worksheet_range("2024-04").unwrap();
+        assert_eq!(
+            values.get_value((0, 1)),
+            Some(&Data::String("40".to_owned()))
+        );
+        assert_eq!(
+            values.get_value((3, 4)),
+            Some(&Data::String("Daily".to_owned()))
+        );
+        assert_eq!(values.get_value((4, 5)), Some(&Data::Float(2.75)));
+        assert_eq!(values.get_value((4, 6)), Some(&Data::Float(3.0)));
+        assert_eq!(values.get_value((6, 5)), Some(&Data::Float(4.5)));
+        let formulas = workbook.worksheet_formula("2024-04").unwrap();
+        let formula = |position| formulas.get_value(position).map(String::as_str);
+        assert_eq!(formula((4, 6)), Some("SUM(F4:F5)"));
+        assert_eq!(formula((5, 6)), Some("SUM(F6:F6)"));
+        assert_eq!(formula((6, 5)), Some("SUM(F4:F6)"));
+        let formulas = workbook.worksheet_formula("2024-05").unwrap();
+        assert!(formulas.used_cells().next().is_none());
+    }
+}
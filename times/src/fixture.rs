@@ -0,0 +1,23 @@
+//! Timesheets written inline in the tests of the modules.
+
+use chrono::NaiveDate;
+
+use crate::convert::{Day, Month};
+use crate::parse::{parse, parse_month};
+use crate::Date;
+
+/// The converted days of `text` in the given month of 2024.
+pub fn days(month: u32, text: &str) -> Vec<Day> {
+    let month = Date::new(NaiveDate::from_ymd_opt(2024, month, 1).unwrap());
+    parse(text.as_bytes(), month)
+        .unwrap()
+        .into_iter()
+        .map(|day| Day::try_from(day).unwrap())
+        .collect()
+}
+
+/// The converted month of `text`, which may start with front matter, in the given month of 2024.
+pub fn month(month: u32, text: &str) -> Month {
+    let month = Date::new(NaiveDate::from_ymd_opt(2024, month, 1).unwrap());
+    Month::try_from(parse_month(text.as_bytes(), month).unwrap()).unwrap()
+}
@@ -1,24 +1,27 @@
 use std::fmt::{Display, Formatter, Result};
 
-use crate::convert::{Day, Entry};
-use crate::{Positioned, Time};
+use crate::convert::{Day, Entry, Month};
+use crate::{Positioned, Time, Topic};
 
-pub struct Output<'a>(pub &'a [Day]);
+pub struct Output<'a>(pub &'a Month);
 
 pub trait Format {
     fn format(&self, f: &mut Formatter<'_>) -> Result;
 }
 
-impl<'a> Display for Output<'a> {
+impl Display for Output<'_> {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result {
         self.0.format(f)
     }
 }
 
-impl<'a> Format for &'a [Day] {
+impl Format for &Month {
     fn format(&self, f: &mut Formatter<'_>) -> Result {
-        let mut first = true;
-        for day in *self {
+        for (key, value) in &self.meta.values {
+            writeln!(f, "#! {key}: {value}")?;
+        }
+        let mut first = self.meta.values.is_empty();
+        for day in &self.days {
             if first {
                 first = false;
             } else {
@@ -31,7 +34,7 @@ impl<'a> Format for &'a [Day] {
     }
 }
 
-impl<'a> Format for &'a Day {
+impl Format for &Day {
     fn format(&self, f: &mut Formatter<'_>) -> Result {
         writeln!(f, "* {}", self.date.value)?;
         self.entries.as_slice().format(f)?;
@@ -70,13 +73,38 @@ impl Display for Time {
     }
 }
 
+impl Display for Topic {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        match self {
+            Topic::Break => Ok(()),
+            Topic::Project {
+                identifier,
+                comment: None,
+            } => write!(f, "{identifier}"),
+            Topic::Project {
+                identifier,
+                comment: Some(comment),
+            } => write!(f, "{identifier} {comment}"),
+        }
+    }
+}
+
+impl Display for crate::Entry {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        match self.topic {
+            Topic::Break => write!(f, "{}", self.time),
+            Topic::Project { .. } => write!(f, "{} {}", self.time, self.topic),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::io::{BufReader, Cursor};
 
     use chrono::NaiveDate;
 
-    use crate::parse::parse;
+    use crate::parse::parse_month;
     use crate::Date;
 
     use super::*;
@@ -84,6 +112,7 @@ mod tests {
     #[test]
     fn test_format() {
         let text = r"
+        #! name: J. Doe
         * Sa. 20.04.
         09:00 AA A
         12:30
@@ -91,22 +120,20 @@ mod tests {
         15:00 TNG C
         17:30
         ";
-        let days = parse(
+        let month = parse_month(
             &mut BufReader::new(Cursor::new(text)),
             Date(NaiveDate::from_ymd_opt(2024, 4, 1).unwrap()),
         )
         .unwrap();
-        let days = days
-            .into_iter()
-            .map(Day::try_from)
-            .collect::<std::result::Result<Vec<_>, _>>()
-            .unwrap();
+        let month = Month::try_from(month).unwrap();
+
+        let expected = r"#! name: J. Doe
 
-        let expected = r"* Sa. 20.04.
+* Sa. 20.04.
 09:00 - 12:30 AA A
 13:00 - 15:00 AANB B
 15:00 - 17:30 TNG C
 ";
-        assert_eq!(format!("{}", Output(&days)), expected);
+        assert_eq!(format!("{}", Output(&month)), expected);
     }
 }
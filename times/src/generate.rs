@@ -2,6 +2,7 @@ use std::fmt::{Display, Formatter, Write};
 
 use thiserror::Error;
 
+use crate::i18n::{Language, Message};
 use crate::Date;
 
 #[derive(Debug, Error, Eq, PartialEq)]
@@ -17,6 +18,7 @@ pub enum Template {
     Empty,
     TechDay,
     Holiday,
+    PublicHoliday,
     Normal,
     Ill,
 }
@@ -25,14 +27,14 @@ trait FormatterEx {
     fn header(&mut self, date: Date) -> std::fmt::Result;
 }
 
-impl<'a> FormatterEx for Formatter<'a> {
+impl FormatterEx for Formatter<'_> {
     fn header(&mut self, date: Date) -> std::fmt::Result {
         writeln!(self, "\n* {date}")
     }
 }
 
 impl Template {
-    fn full_day(output: &mut String, date: Date, what: &str) -> std::fmt::Result {
+    fn full_day(output: &mut String, date: Date, what: impl Display) -> std::fmt::Result {
         write_with(output, |f| {
             f.header(date)?;
             writeln!(f, "09:00 {what}")?;
@@ -40,7 +42,13 @@ impl Template {
         })
     }
 
-    pub fn execute(&self, date: Date, args: &[String]) -> Result<String, Error> {
+    /// The identifiers of holidays and sick days are written in `language`.
+    pub fn execute(
+        &self,
+        date: Date,
+        args: &[String],
+        language: Language,
+    ) -> Result<String, Error> {
         let mut output = String::new();
 
         match self {
@@ -60,13 +68,24 @@ impl Template {
                 if !args.is_empty() {
                     return Err(Error::Argc(0, args.len()));
                 }
-                Self::full_day(&mut output, date, "Urlaub").unwrap();
+                Self::full_day(&mut output, date, language.tr(Message::Holiday)).unwrap();
+            }
+            Template::PublicHoliday => {
+                if 1 < args.len() {
+                    return Err(Error::Argc(1, args.len()));
+                }
+                let holiday = language.tr(Message::PublicHoliday);
+                let what = match args.first() {
+                    Some(name) => format!("{holiday} {name}"),
+                    None => holiday.to_string(),
+                };
+                Self::full_day(&mut output, date, &what).unwrap();
             }
             Template::Ill => {
                 if !args.is_empty() {
                     return Err(Error::Argc(0, args.len()));
                 }
-                Self::full_day(&mut output, date, "Krank").unwrap();
+                Self::full_day(&mut output, date, language.tr(Message::Ill)).unwrap();
             }
             Template::Normal => {
                 if args.is_empty() || 2 < args.len() {
@@ -119,16 +138,75 @@ mod tests {
             (Template::Empty, vec![], "\n* Mo. 5.08.\n"),
             (Template::TechDay, vec![], "\n* Mo. 5.08.\n09:00 TNGFo Techday\n17:00\n"),
             (Template::Holiday, vec![], "\n* Mo. 5.08.\n09:00 Urlaub\n17:00\n"),
+            (Template::PublicHoliday, vec![], "\n* Mo. 5.08.\n09:00 Feiertag\n17:00\n"),
+            (Template::PublicHoliday, vec!["Tag der Arbeit".into()], "\n* Mo. 5.08.\n09:00 Feiertag Tag der Arbeit\n17:00\n"),
             (Template::Normal, vec!["A".into()], "\n* Mo. 5.08.\n09:00 AA Ops Daily\n09:15 AA Inference Daily\n09:45 AA A\n12:30\n13:00 AA A\n17:30\n"),
             (Template::Normal, vec!["A".into(), "B".into()], "\n* Mo. 5.08.\n09:00 AA Ops Daily\n09:15 AA Inference Daily\n09:45 AA A\n12:30\n13:00 AA B\n17:30\n"),
             (Template::Ill, vec![], "\n* Mo. 5.08.\n09:00 Krank\n17:00\n")
         ];
         for (template, args, result) in tests {
             assert_eq!(
-                template.execute(date, &args).as_deref(),
+                template.execute(date, &args, Language::De).as_deref(),
                 Ok(result),
                 "{template:?}"
             );
         }
+        assert_eq!(
+            Template::Ill.execute(date, &[], Language::En).as_deref(),
+            Ok("\n* Mo. 5.08.\n09:00 Sick\n17:00\n")
+        );
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let date = Date::new(NaiveDate::from_ymd_opt(2024, 8, 5).unwrap());
+        for language in [Language::En, Language::De] {
+            let identifier = |message| language.tr(message).to_string();
+            let tests = [
+                (Template::Empty, vec![], None),
+                (
+                    Template::TechDay,
+                    vec![],
+                    Some(("TNGFo".to_owned(), Some("Techday"))),
+                ),
+                (
+                    Template::Holiday,
+                    vec![],
+                    Some((identifier(Message::Holiday), None)),
+                ),
+                (
+                    Template::PublicHoliday,
+                    vec![],
+                    Some((identifier(Message::PublicHoliday), None)),
+                ),
+                (
+                    Template::PublicHoliday,
+                    vec!["Labour Day".into()],
+                    Some((identifier(Message::PublicHoliday), Some("Labour Day"))),
+                ),
+                (
+                    Template::Normal,
+                    vec!["A".into()],
+                    Some(("AA".to_owned(), Some("Ops Daily"))),
+                ),
+                (
+                    Template::Ill,
+                    vec![],
+                    Some((identifier(Message::Ill), None)),
+                ),
+            ];
+            for (template, args, first) in tests {
+                let text = template.execute(date, &args, language).unwrap();
+                let days = crate::fixture::days(8, &text);
+                let entry = days[0].entries.first().map(|e| {
+                    let entry = &e.value;
+                    (
+                        entry.identifier.as_str().to_owned(),
+                        entry.comment.as_deref(),
+                    )
+                });
+                assert_eq!(entry, first, "{template:?} in {language:?}");
+            }
+        }
     }
 }
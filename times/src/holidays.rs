@@ -0,0 +1,231 @@
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+use chrono::{Datelike, NaiveDate, Weekday};
+use thiserror::Error;
+
+use crate::Date;
+
+/// The German states, each with its own set of public holidays.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Region {
+    BW,
+    BY,
+    BE,
+    BB,
+    HB,
+    HH,
+    HE,
+    MV,
+    NI,
+    NW,
+    RP,
+    SL,
+    SN,
+    ST,
+    SH,
+    TH,
+}
+
+const REGIONS: [(&str, Region); 16] = [
+    ("BW", Region::BW),
+    ("BY", Region::BY),
+    ("BE", Region::BE),
+    ("BB", Region::BB),
+    ("HB", Region::HB),
+    ("HH", Region::HH),
+    ("HE", Region::HE),
+    ("MV", Region::MV),
+    ("NI", Region::NI),
+    ("NW", Region::NW),
+    ("RP", Region::RP),
+    ("SL", Region::SL),
+    ("SN", Region::SN),
+    ("ST", Region::ST),
+    ("SH", Region::SH),
+    ("TH", Region::TH),
+];
+
+#[derive(Debug, Error, Eq, PartialEq)]
+#[error("Unknown region {0}, expected one of BW, BY, BE, BB, HB, HH, HE, MV, NI, NW, RP, SL, SN, ST, SH or TH")]
+pub struct UnknownRegion(String);
+
+impl FromStr for Region {
+    type Err = UnknownRegion;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        REGIONS
+            .iter()
+            .find(|(code, _)| code.eq_ignore_ascii_case(s.trim()))
+            .map(|(_, region)| *region)
+            .ok_or_else(|| UnknownRegion(s.to_owned()))
+    }
+}
+
+impl Display for Region {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let (code, _) = REGIONS.iter().find(|(_, r)| r == self).unwrap();
+        f.write_str(code)
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Holiday {
+    pub date: Date,
+    pub name: &'static str,
+}
+
+/// Easter Sunday of the gregorian calendar, using the anonymous gregorian algorithm.
+#[allow(clippy::many_single_char_names)]
+fn easter(year: i32) -> NaiveDate {
+    let a = year % 19;
+    let b = year / 100;
+    let c = year % 100;
+    let d = b / 4;
+    let e = b % 4;
+    let f = (b + 8) / 25;
+    let g = (b - f + 1) / 3;
+    let h = (19 * a + b - d - g + 15) % 30;
+    let i = c / 4;
+    let k = c % 4;
+    let l = (32 + 2 * e + 2 * i - h - k) % 7;
+    let m = (a + 11 * h + 22 * l) / 451;
+    let month = (h + l - 7 * m + 114) / 31;
+    let day = (h + l - 7 * m + 114) % 31 + 1;
+    NaiveDate::from_ymd_opt(year, month.unsigned_abs(), day.unsigned_abs()).unwrap()
+}
+
+/// All public holidays of `region` in `year` ordered by date, including the ones on weekends.
+///
+/// # Panics
+///
+/// If `year` is not between 1583, the first full year of the Gregorian calendar, and 9999.
+#[must_use]
+pub fn holidays(region: Region, year: i32) -> Vec<Holiday> {
+    use Region::{BB, BE, BW, BY, HB, HE, HH, MV, NI, NW, RP, SH, SL, SN, ST, TH};
+
+    let fixed = |month, day| NaiveDate::from_ymd_opt(year, month, day).unwrap();
+    let easter = easter(year);
+    let from_easter = |days: i64| {
+        easter
+            .checked_add_signed(chrono::Duration::days(days))
+            .unwrap()
+    };
+    // The Wednesday before the 23rd of November.
+    let repentance = fixed(11, 16)
+        .iter_days()
+        .find(|d| d.weekday() == Weekday::Wed)
+        .unwrap();
+    let reformation = year == 2017
+        || matches!(region, BB | MV | SN | ST | TH)
+        || (year >= 2018 && matches!(region, HB | HH | NI | SH));
+
+    let candidates = [
+        (fixed(1, 1), "Neujahr", true),
+        (
+            fixed(1, 6),
+            "Heilige Drei Könige",
+            matches!(region, BW | BY | ST),
+        ),
+        (
+            fixed(3, 8),
+            "Internationaler Frauentag",
+            (region == BE && year >= 2019) || (region == MV && year >= 2023),
+        ),
+        (from_easter(-2), "Karfreitag", true),
+        (from_easter(1), "Ostermontag", true),
+        (fixed(5, 1), "Tag der Arbeit", true),
+        (from_easter(39), "Christi Himmelfahrt", true),
+        (from_easter(50), "Pfingstmontag", true),
+        (
+            from_easter(60),
+            "Fronleichnam",
+            matches!(region, BW | BY | HE | NW | RP | SL),
+        ),
+        (fixed(8, 15), "Mariä Himmelfahrt", region == SL),
+        (fixed(9, 20), "Weltkindertag", region == TH && year >= 2019),
+        (fixed(10, 3), "Tag der Deutschen Einheit", true),
+        (fixed(10, 31), "Reformationstag", reformation),
+        (
+            fixed(11, 1),
+            "Allerheiligen",
+            matches!(region, BW | BY | NW | RP | SL),
+        ),
+        (repentance, "Buß- und Bettag", region == SN),
+        (fixed(12, 25), "1. Weihnachtstag", true),
+        (fixed(12, 26), "2. Weihnachtstag", true),
+    ];
+    let mut holidays: Vec<_> = candidates
+        .into_iter()
+        .filter(|(_, _, observed)| *observed)
+        .map(|(date, name, _)| Holiday {
+            date: Date::new(date),
+            name,
+        })
+        .collect();
+    holidays.sort_by_key(|h| h.date);
+    holidays
+}
+
+impl Holiday {
+    /// Whether the holiday falls on a working day.
+    #[must_use]
+    pub fn is_weekday(&self) -> bool {
+        !matches!(self.date.weekday(), Weekday::Sat | Weekday::Sun)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dates(region: Region, year: i32) -> Vec<(String, &'static str)> {
+        holidays(region, year)
+            .into_iter()
+            .map(|h| (h.date.into_inner().format("%m-%d").to_string(), h.name))
+            .collect()
+    }
+
+    #[test]
+    fn test_easter() {
+        for (year, month, day) in [(2019, 4, 21), (2024, 3, 31), (2025, 4, 20), (2038, 4, 25)] {
+            assert_eq!(
+                easter(year),
+                NaiveDate::from_ymd_opt(year, month, day).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn test_holidays() {
+        let expected = [
+            ("01-01", "Neujahr"),
+            ("01-06", "Heilige Drei Könige"),
+            ("03-29", "Karfreitag"),
+            ("04-01", "Ostermontag"),
+            ("05-01", "Tag der Arbeit"),
+            ("05-09", "Christi Himmelfahrt"),
+            ("05-20", "Pfingstmontag"),
+            ("05-30", "Fronleichnam"),
+            ("10-03", "Tag der Deutschen Einheit"),
+            ("11-01", "Allerheiligen"),
+            ("12-25", "1. Weihnachtstag"),
+            ("12-26", "2. Weihnachtstag"),
+        ]
+        .map(|(d, n)| (d.to_owned(), n));
+        assert_eq!(dates(Region::BY, 2024), expected);
+
+        let saxony = dates(Region::SN, 2024);
+        assert!(saxony.contains(&("10-31".to_owned(), "Reformationstag")));
+        assert!(saxony.contains(&("11-20".to_owned(), "Buß- und Bettag")));
+        assert_eq!(dates(Region::HB, 2017).len(), 10);
+        assert_eq!(dates(Region::HB, 2016).len(), 9);
+    }
+
+    #[test]
+    fn test_region() {
+        assert_eq!("by".parse(), Ok(Region::BY));
+        assert_eq!(Region::NW.to_string(), "NW");
+        assert_eq!("XX".parse::<Region>(), Err(UnknownRegion("XX".to_owned())));
+    }
+}
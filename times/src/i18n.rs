@@ -0,0 +1,315 @@
+use std::fmt::{Display, Formatter, Result};
+
+use serde::Deserialize;
+
+use crate::convert;
+use crate::diagnostic::Severity;
+use crate::error::Kind;
+use crate::lint::Warning;
+use crate::parse::{self, DateError, EntryError};
+
+/// Language of the reports, the check output and the entries written by templates.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Language {
+    #[default]
+    En,
+    De,
+}
+
+#[derive(Debug, Copy, Clone)]
+pub enum Message<'a> {
+    Total,
+    Subtotal,
+    TotalOfFiles(usize),
+    Expected,
+    UnderHours,
+    BalanceWithoutUnderHours,
+    TravelTime,
+    Billable,
+    DstStarts,
+    DstEnds,
+    /// Identifier of the holiday template.
+    Holiday,
+    /// Identifier of the ill template.
+    Ill,
+    /// Identifier of the public holiday template.
+    PublicHoliday,
+    Warning,
+    NoProblems,
+    Lint(&'a Warning),
+    Parse(&'a parse::Error),
+    Convert(&'a convert::Error),
+    /// An error with its location and severity.
+    Error(&'a crate::Error),
+}
+
+impl Language {
+    #[must_use]
+    pub fn tr(self, message: Message<'_>) -> Text<'_> {
+        Text {
+            language: self,
+            message,
+        }
+    }
+}
+
+/// A [`Message`] in a [`Language`].
+pub struct Text<'a> {
+    language: Language,
+    message: Message<'a>,
+}
+
+impl Display for Text<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        match self.language {
+            Language::En => en(f, self.message),
+            Language::De => de(f, self.message),
+        }
+    }
+}
+
+fn en(f: &mut Formatter<'_>, message: Message<'_>) -> Result {
+    let text = match message {
+        Message::Total => "Total",
+        Message::Subtotal => "Subtotal",
+        Message::TotalOfFiles(count) => return write!(f, "Total of {count} files"),
+        Message::Expected => "Expected",
+        Message::UnderHours => "Under hours",
+        Message::BalanceWithoutUnderHours => "balance without under hours",
+        Message::TravelTime => "Travel time",
+        Message::Billable => "billable",
+        Message::DstStarts => "Daylight saving time starts, 02:00 to 03:00 does not exist",
+        Message::DstEnds => "Daylight saving time ends, 02:00 to 03:00 happens twice",
+        Message::Holiday => "Vacation",
+        Message::Ill => "Sick",
+        Message::PublicHoliday => "PublicHoliday",
+        Message::Warning => "Warning",
+        Message::NoProblems => "No problems found",
+        Message::Lint(warning) => return write!(f, "{warning}"),
+        Message::Parse(error) => return write!(f, "{error}"),
+        Message::Convert(error) => return write!(f, "{error}"),
+        Message::Error(error) => return write!(f, "{error}"),
+    };
+    f.write_str(text)
+}
+
+fn de(f: &mut Formatter<'_>, message: Message<'_>) -> Result {
+    let text = match message {
+        Message::Total => "Summe",
+        Message::Subtotal => "Zwischensumme",
+        Message::TotalOfFiles(count) => return write!(f, "Summe von {count} Dateien"),
+        Message::Expected => "Soll",
+        Message::UnderHours => "Unterstunden",
+        Message::BalanceWithoutUnderHours => "Saldo ohne Unterstunden",
+        Message::TravelTime => "Reisezeit",
+        Message::Billable => "abrechenbar",
+        Message::DstStarts => "Beginn der Sommerzeit, 02:00 bis 03:00 gibt es nicht",
+        Message::DstEnds => "Ende der Sommerzeit, 02:00 bis 03:00 gibt es zweimal",
+        Message::Holiday => "Urlaub",
+        Message::Ill => "Krank",
+        Message::PublicHoliday => "Feiertag",
+        Message::Warning => "Warnung",
+        Message::NoProblems => "Keine Probleme gefunden",
+        Message::Lint(warning) => return lint_de(f, warning),
+        Message::Parse(error) => return parse_de(f, error),
+        Message::Convert(error) => return convert_de(f, error),
+        Message::Error(error) => return error_de(f, error),
+    };
+    f.write_str(text)
+}
+
+fn lint_de(f: &mut Formatter<'_>, warning: &Warning) -> Result {
+    match warning {
+        Warning::NonExistentTime(line) => write!(
+            f,
+            "Zeit in Zeile {line} existiert nicht, die Uhren werden von 02:00 auf 03:00 vorgestellt"
+        ),
+        Warning::AmbiguousTime(line) => write!(
+            f,
+            "Zeit in Zeile {line} ist mehrdeutig, die Uhren werden von 03:00 auf 02:00 zurückgestellt"
+        ),
+        Warning::AcrossDstChange(line) => write!(
+            f,
+            "Zeit in Zeile {line} reicht über die Zeitumstellung, ihre Dauer weicht um eine Stunde ab"
+        ),
+        Warning::UnknownIdentifier(line, identifier, suggestion) => {
+            write!(f, "Unbekannter Bezeichner {identifier} in Zeile {line}")?;
+            match suggestion {
+                Some(suggestion) => write!(f, ", meinten Sie {suggestion}?"),
+                None => Ok(()),
+            }
+        }
+        Warning::MaxDailyWorkTime(line, work, max) => write!(
+            f,
+            "Tag in Zeile {line} hat eine Arbeitszeit von {}, mehr als die erlaubten {}",
+            work.into_duration(),
+            max.into_duration()
+        ),
+        Warning::MissingBreak(line, breaks, required) => write!(
+            f,
+            "Tag in Zeile {line} hat Pausen von {}, mindestens {} sind vorgeschrieben",
+            breaks.into_duration(),
+            required.into_duration()
+        ),
+        Warning::UnterminatedDay(line) => {
+            write!(f, "Tag in Zeile {line} endet nicht mit einer Pause")
+        }
+        Warning::Gap(line, gap) => write!(
+            f,
+            "Zeit in Zeile {line} beginnt {} nach der Zeit davor",
+            gap.into_duration()
+        ),
+        Warning::WeekendWork(line) => write!(f, "Tag in Zeile {line} liegt an einem Wochenende"),
+        // Messages of custom rules come from their plugin.
+        Warning::Custom(..) => write!(f, "{warning}"),
+    }
+}
+
+fn parse_de(f: &mut Formatter<'_>, error: &parse::Error) -> Result {
+    match error {
+        parse::Error::Io(e) => write!(f, "Ein-/Ausgabefehler: {e}"),
+        parse::Error::Many(errors) => {
+            write!(f, "Beim Parsen sind folgende Fehler aufgetreten:")?;
+            for entry in &errors.0 {
+                write!(f, "\nEintrag in Zeile {} ist ungültig: ", entry.line)?;
+                entry_de(f, &entry.value)?;
+            }
+            Ok(())
+        }
+        parse::Error::ExpectedDay(line) => write!(f, "Tag in Zeile {line} erwartet"),
+    }
+}
+
+fn entry_de(f: &mut Formatter<'_>, error: &EntryError) -> Result {
+    match error {
+        EntryError::Time => write!(f, "Ungültiges Zeitformat"),
+        EntryError::MissingTime => write!(f, "Zeit fehlt"),
+        EntryError::Date(error) => {
+            write!(f, "Datum des Tages ist ungültig: ")?;
+            date_de(f, error)
+        }
+        EntryError::Meta(key) => write!(f, "Ungültiger Wert für Metadatum {key:?}"),
+    }
+}
+
+fn date_de(f: &mut Formatter<'_>, error: &DateError) -> Result {
+    f.write_str(match error {
+        DateError::Format => {
+            "Datum im Format <Wochentag>. <Tag>.<Monat>. oder <Jahr>-<Monat>-<Tag> erwartet"
+        }
+        DateError::Date => "Ungültiges Datum",
+        DateError::DayOfWeek => "Ungültiger Wochentag",
+        DateError::UnexpectedDayOfWeek => "Wochentag passt nicht zum angegebenen Datum",
+        DateError::UnexpectedMonth => "Monat passt nicht zum angegebenen Monat",
+        DateError::EntryOutOfOrder => {
+            "Eintrag außer der Reihe, die Daten müssen streng monoton steigen"
+        }
+    })
+}
+
+fn convert_de(f: &mut Formatter<'_>, error: &convert::Error) -> Result {
+    match error {
+        convert::Error::NotTerminated(line) => {
+            write!(f, "Zeitspanne in Zeile {line} wird nie beendet")
+        }
+        convert::Error::TimeNotMultipleOfGranularity(line, granularity) => write!(
+            f,
+            "Minuten der Zeit in Zeile {line} sind kein Vielfaches von {granularity}"
+        ),
+        convert::Error::EndsBeforeItStarts(line) => {
+            write!(f, "Zeit in Zeile {line} endet, bevor sie beginnt")
+        }
+        convert::Error::OverlapWithPrevious(line) => {
+            write!(
+                f,
+                "Zeit in Zeile {line} überschneidet sich mit der Zeit davor"
+            )
+        }
+        convert::Error::AcrossTravelTime(line) => write!(
+            f,
+            "Zeit in Zeile {line} überschreitet Beginn oder Ende einer vorherigen Reisezeit"
+        ),
+    }
+}
+
+fn error_de(f: &mut Formatter<'_>, error: &crate::Error) -> Result {
+    if let Some(path) = &error.path {
+        write!(f, "{}: ", path.display())?;
+    }
+    let severity = match error.severity {
+        Severity::Warning => "Warnung",
+        Severity::Error => "Fehler",
+    };
+    write!(f, "{severity}: ")?;
+    match &*error.kind {
+        Kind::Parse(error) => parse_de(f, error),
+        Kind::Convert(error) => convert_de(f, error),
+        Kind::Lint(warning) => lint_de(f, warning),
+        kind => write!(f, "{kind}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use chrono::NaiveDate;
+
+    use super::*;
+    use crate::{Date, Minutes};
+
+    #[test]
+    fn test_tr() {
+        assert_eq!(
+            Language::En.tr(Message::TotalOfFiles(2)).to_string(),
+            "Total of 2 files"
+        );
+        assert_eq!(
+            Language::De.tr(Message::TotalOfFiles(2)).to_string(),
+            "Summe von 2 Dateien"
+        );
+        let warning = Warning::Gap(3, Minutes::from(90));
+        assert_eq!(
+            Language::En.tr(Message::Lint(&warning)).to_string(),
+            warning.to_string()
+        );
+        assert_eq!(
+            Language::De.tr(Message::Lint(&warning)).to_string(),
+            "Zeit in Zeile 3 beginnt 01:30 nach der Zeit davor"
+        );
+        let warning = Warning::UnknownIdentifier(2, "AB".to_owned(), Some("AA".to_owned()));
+        assert_eq!(
+            Language::De.tr(Message::Lint(&warning)).to_string(),
+            "Unbekannter Bezeichner AB in Zeile 2, meinten Sie AA?"
+        );
+    }
+
+    #[test]
+    fn test_tr_errors() {
+        let error = convert::Error::OverlapWithPrevious(4);
+        assert_eq!(
+            Language::En.tr(Message::Convert(&error)).to_string(),
+            error.to_string()
+        );
+        assert_eq!(
+            Language::De.tr(Message::Convert(&error)).to_string(),
+            "Zeit in Zeile 4 überschneidet sich mit der Zeit davor"
+        );
+        let month = Date(NaiveDate::from_ymd_opt(2024, 4, 1).unwrap());
+        let error = crate::parse::parse("10:17 AA".as_bytes(), month).unwrap_err();
+        assert_eq!(
+            Language::De.tr(Message::Parse(&error)).to_string(),
+            "Tag in Zeile 1 erwartet"
+        );
+        let error = crate::Error::from(error).with_path(Path::new("2024-04.tsh"));
+        assert_eq!(
+            Language::En.tr(Message::Error(&error)).to_string(),
+            error.to_string()
+        );
+        assert_eq!(
+            Language::De.tr(Message::Error(&error)).to_string(),
+            "2024-04.tsh: Fehler: Tag in Zeile 1 erwartet"
+        );
+    }
+}
@@ -0,0 +1,289 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::io::Read;
+
+use chrono::{NaiveDate, NaiveTime, Timelike};
+use csv::StringRecord;
+use thiserror::Error;
+
+use crate::{Date, Entry, Granularity, SignedMinutes, Time, Topic};
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("Failed to read CSV: {0}")]
+    Csv(#[from] csv::Error),
+    #[error("Missing column {0}")]
+    MissingColumn(&'static str),
+    #[error("Row {0}: invalid {1} {2:?}")]
+    Invalid(usize, &'static str, String),
+    #[error("No identifier configured for project {0:?}")]
+    Unmapped(String),
+    #[error("Activity of project {0:?} on {1} ends after midnight")]
+    Overnight(String, NaiveDate),
+}
+
+/// A span of tracked time read from an export.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Activity {
+    pub date: NaiveDate,
+    pub start: Time,
+    pub end: Time,
+    pub project: String,
+    pub description: Option<String>,
+}
+
+/// Reads the activities of an export of another time tracking tool.
+pub trait Importer {
+    fn activities(&self, reader: &mut dyn Read) -> Result<Vec<Activity>, Error>;
+}
+
+/// The detailed CSV report of Clockify.
+pub struct Clockify;
+
+/// The detailed time CSV report of Harvest, entries without timestamps are placed one after
+/// another starting at 09:00.
+pub struct Harvest;
+
+const DATE_FORMATS: [&str; 4] = ["%Y-%m-%d", "%m/%d/%Y", "%d.%m.%Y", "%d/%m/%Y"];
+const TIME_FORMATS: [&str; 6] = [
+    "%H:%M:%S",
+    "%H:%M",
+    "%I:%M:%S %p",
+    "%I:%M %p",
+    "%I:%M%p",
+    "%I%p",
+];
+
+struct Columns(StringRecord);
+
+impl Columns {
+    fn find(&self, name: &str) -> Option<usize> {
+        self.0
+            .iter()
+            .position(|h| h.trim().eq_ignore_ascii_case(name))
+    }
+
+    fn index(&self, name: &'static str) -> Result<usize, Error> {
+        self.find(name).ok_or(Error::MissingColumn(name))
+    }
+}
+
+fn parse_date(row: usize, value: &str) -> Result<NaiveDate, Error> {
+    DATE_FORMATS
+        .iter()
+        .find_map(|f| NaiveDate::parse_from_str(value.trim(), f).ok())
+        .ok_or_else(|| Error::Invalid(row, "date", value.to_owned()))
+}
+
+fn parse_time(row: usize, value: &str) -> Result<Time, Error> {
+    let time = TIME_FORMATS
+        .iter()
+        .find_map(|f| NaiveTime::parse_from_str(value.trim(), f).ok())
+        .ok_or_else(|| Error::Invalid(row, "time", value.to_owned()))?;
+    Ok(Time::new(
+        u8::try_from(time.hour()).unwrap(),
+        u8::try_from(time.minute()).unwrap(),
+    )
+    .unwrap())
+}
+
+fn description(value: &str) -> Option<String> {
+    let value = value.split_whitespace().collect::<Vec<_>>().join(" ");
+    (!value.is_empty()).then_some(value)
+}
+
+impl Importer for Clockify {
+    fn activities(&self, reader: &mut dyn Read) -> Result<Vec<Activity>, Error> {
+        let mut reader = csv::Reader::from_reader(reader);
+        let columns = Columns(reader.headers()?.clone());
+        let project = columns.index("Project")?;
+        let description_column = columns.index("Description")?;
+        let start_date = columns.index("Start Date")?;
+        let start = columns.index("Start Time")?;
+        let end = columns.index("End Time")?;
+        let mut activities = Vec::new();
+        for (row, record) in reader.records().enumerate() {
+            let record = record?;
+            let row = row + 2;
+            activities.push(Activity {
+                date: parse_date(row, &record[start_date])?,
+                start: parse_time(row, &record[start])?,
+                end: parse_time(row, &record[end])?,
+                project: record[project].trim().to_owned(),
+                description: description(&record[description_column]),
+            });
+        }
+        Ok(activities)
+    }
+}
+
+impl Importer for Harvest {
+    fn activities(&self, reader: &mut dyn Read) -> Result<Vec<Activity>, Error> {
+        let mut reader = csv::Reader::from_reader(reader);
+        let columns = Columns(reader.headers()?.clone());
+        let date = columns.index("Date")?;
+        let project = columns.index("Project")?;
+        let notes = columns.index("Notes")?;
+        let hours = columns.index("Hours")?;
+        let timestamps = columns.find("Started At").zip(columns.find("Ended At"));
+        let mut activities: Vec<Activity> = Vec::new();
+        for (row, record) in reader.records().enumerate() {
+            let record = record?;
+            let row = row + 2;
+            let date = parse_date(row, &record[date])?;
+            let (start, end) = match timestamps {
+                Some((start, end)) if !record[start].trim().is_empty() => (
+                    parse_time(row, &record[start])?,
+                    parse_time(row, &record[end])?,
+                ),
+                _ => {
+                    let start = activities
+                        .iter()
+                        .rev()
+                        .find(|a| a.date == date)
+                        .map_or(Time::new(9, 0).unwrap(), |a| a.end);
+                    let value = &record[hours];
+                    let invalid = || Error::Invalid(row, "hours", value.to_owned());
+                    let hours: f64 = value
+                        .trim()
+                        .replace(',', ".")
+                        .parse()
+                        .map_err(|_| invalid())?;
+                    if !(0.0..24.0).contains(&hours) {
+                        return Err(invalid());
+                    }
+                    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+                    let minutes = (hours * 60.0).round() as isize;
+                    let end = start
+                        .checked_add_signed(SignedMinutes::from(minutes))
+                        .ok_or_else(invalid)?;
+                    (start, end)
+                }
+            };
+            activities.push(Activity {
+                date,
+                start,
+                end,
+                project: record[project].trim().to_owned(),
+                description: description(&record[notes]),
+            });
+        }
+        Ok(activities)
+    }
+}
+
+/// Renders one day block per date like the templates do, with a break after every activity that
+/// is not directly followed by the next one. Projects are mapped to identifiers with
+/// `identifiers` and times are rounded to `granularity`. Activities that round to nothing are
+/// skipped, activities that end after midnight are an error.
+pub fn render(
+    activities: &[Activity],
+    identifiers: &BTreeMap<String, String>,
+    granularity: Granularity,
+) -> Result<Vec<(Date, String)>, Error> {
+    let mut activities: Vec<_> = activities.iter().collect();
+    activities.sort_by_key(|a| (a.date, a.start));
+    let mut days: Vec<(Date, String)> = Vec::new();
+    let mut end: Option<Time> = None;
+    for activity in activities {
+        let identifier = identifiers
+            .get(&activity.project)
+            .ok_or_else(|| Error::Unmapped(activity.project.clone()))?;
+        if activity.end < activity.start {
+            return Err(Error::Overnight(activity.project.clone(), activity.date));
+        }
+        let start = activity.start.rounded(granularity.minutes());
+        let stop = activity.end.rounded(granularity.minutes());
+        if stop <= start {
+            continue;
+        }
+        let date = Date::new(activity.date);
+        if days.last().is_none_or(|(d, _)| *d != date) {
+            if let (Some((_, text)), Some(end)) = (days.last_mut(), end.take()) {
+                writeln!(text, "{end}").unwrap();
+            }
+            days.push((date, format!("\n* {date}\n")));
+        }
+        let (_, text) = days.last_mut().unwrap();
+        if let Some(end) = end.filter(|e| *e < start) {
+            writeln!(text, "{end}").unwrap();
+        }
+        let entry = Entry {
+            time: start,
+            topic: Topic::Project {
+                identifier: identifier.clone(),
+                comment: activity.description.clone(),
+            },
+        };
+        writeln!(text, "{entry}").unwrap();
+        end = Some(end.map_or(stop, |e| e.max(stop)));
+    }
+    if let (Some((_, text)), Some(end)) = (days.last_mut(), end) {
+        writeln!(text, "{end}").unwrap();
+    }
+    Ok(days)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn identifiers() -> BTreeMap<String, String> {
+        [("Inference", "AA"), ("Ops", "BB")]
+            .into_iter()
+            .map(|(p, i)| (p.to_owned(), i.to_owned()))
+            .collect()
+    }
+
+    #[test]
+    fn test_clockify() {
+        let csv = "Project,Client,Description,Task,User,Start Date,Start Time,End Date,End Time,Duration (h)
+Ops,ACME,Daily,,me,04/15/2024,09:00:00 AM,04/15/2024,09:16:00 AM,00:16:00
+Inference,ACME,\"Model, review\",,me,04/15/2024,09:16:00 AM,04/15/2024,12:30:00 PM,03:14:00
+Inference,ACME,,,me,04/15/2024,01:00:00 PM,04/15/2024,05:00:00 PM,04:00:00
+Ops,ACME,,,me,04/16/2024,09:00:00 AM,04/16/2024,10:00:00 AM,01:00:00
+";
+        let activities = Clockify.activities(&mut csv.as_bytes()).unwrap();
+        assert_eq!(activities.len(), 4);
+        let days = render(&activities, &identifiers(), Granularity::default()).unwrap();
+        let text: Vec<_> = days.iter().map(|(_, t)| t.as_str()).collect();
+        assert_eq!(
+            text,
+            [
+                "\n* Mo. 15.04.\n09:00 BB Daily\n09:15 AA Model, review\n12:30\n13:00 AA\n17:00\n",
+                "\n* Di. 16.04.\n09:00 BB\n10:00\n"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_harvest() {
+        let csv = "Date,Client,Project,Project Code,Task,Notes,Hours
+2024-04-15,ACME,Ops,,Meeting,Daily,0.25
+2024-04-15,ACME,Inference,,Development,,7.5
+";
+        let activities = Harvest.activities(&mut csv.as_bytes()).unwrap();
+        let days = render(&activities, &identifiers(), Granularity::default()).unwrap();
+        assert_eq!(
+            days[0].1,
+            "\n* Mo. 15.04.\n09:00 BB Daily\n09:15 AA\n16:45\n"
+        );
+
+        let csv = "Date,Project,Notes,Hours\n2024-04-15,Other,,1\n";
+        let activities = Harvest.activities(&mut csv.as_bytes()).unwrap();
+        assert!(matches!(
+            render(&activities, &identifiers(), Granularity::default()),
+            Err(Error::Unmapped(p)) if p == "Other"
+        ));
+        let csv = "Date,Project,Notes,Hours,Started At,Ended At\n2024-04-15,Ops,,2,23:00,01:00\n";
+        let activities = Harvest.activities(&mut csv.as_bytes()).unwrap();
+        assert!(matches!(
+            render(&activities, &identifiers(), Granularity::default()),
+            Err(Error::Overnight(p, _)) if p == "Ops"
+        ));
+        assert!(matches!(
+            Harvest.activities(&mut "Date,Project\n".as_bytes()),
+            Err(Error::MissingColumn("Notes"))
+        ));
+    }
+}
@@ -0,0 +1,91 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::convert::Day;
+
+/// Counts how often identifiers and comments are used, to rank completion candidates.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct Index {
+    identifiers: BTreeMap<String, usize>,
+    comments: BTreeMap<String, BTreeMap<String, usize>>,
+}
+
+fn ranked<'a>(counts: &'a BTreeMap<String, usize>, prefix: &str) -> Vec<&'a str> {
+    let mut candidates: Vec<_> = counts
+        .iter()
+        .filter(|(k, _)| k.starts_with(prefix))
+        .collect();
+    candidates.sort_by(|(_, a), (_, b)| b.cmp(a));
+    candidates.into_iter().map(|(k, _)| k.as_str()).collect()
+}
+
+impl Index {
+    pub fn add_days(&mut self, days: &[Day]) {
+        for entry in days.iter().flat_map(|d| &d.entries) {
+            let identifier = entry.value.identifier.as_str();
+            *self.identifiers.entry(identifier.to_owned()).or_default() += 1;
+            if let Some(comment) = &entry.value.comment {
+                *self
+                    .comments
+                    .entry(identifier.to_owned())
+                    .or_default()
+                    .entry(comment.clone())
+                    .or_default() += 1;
+            }
+        }
+    }
+
+    #[must_use]
+    pub fn count(&self, identifier: &str) -> usize {
+        self.identifiers
+            .get(identifier)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Identifiers starting with `prefix`, most used first.
+    #[must_use]
+    pub fn identifiers(&self, prefix: &str) -> Vec<&str> {
+        ranked(&self.identifiers, prefix)
+    }
+
+    /// Comments used with `identifier` starting with `prefix`, most used first.
+    #[must_use]
+    pub fn comments(&self, identifier: &str, prefix: &str) -> Vec<&str> {
+        self.comments
+            .get(identifier)
+            .map(|c| ranked(c, prefix))
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fixture::days;
+
+    use super::*;
+
+    #[test]
+    fn test_ranking() {
+        let text = r"
+        * Mo. 15.04.
+        09:00 AB Daily
+        09:15 AA Foo
+        10:00 AA Bar
+        11:00 AA Bar
+        12:00 B
+        13:00 AB
+        14:00
+        ";
+        let days = days(4, text);
+        let mut index = Index::default();
+        index.add_days(&days);
+
+        assert_eq!(index.identifiers(""), vec!["AA", "AB", "B"]);
+        assert_eq!(index.identifiers("A"), vec!["AA", "AB"]);
+        assert_eq!(index.comments("AA", ""), vec!["Bar", "Foo"]);
+        assert_eq!(index.comments("B", ""), Vec::<&str>::new());
+        assert_eq!(index.count("AB"), 2);
+    }
+}
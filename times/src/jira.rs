@@ -0,0 +1,158 @@
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::sync::LazyLock;
+
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+use regex::Regex;
+
+use crate::convert::{Entry, Identifier, Month};
+use crate::{Minutes, Positioned, Time};
+
+static TICKET: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\b[A-Z][A-Z0-9]+-[0-9]+\b").unwrap());
+
+/// The time spent on an entry, to be logged on a Jira issue.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Worklog {
+    pub issue: String,
+    pub date: NaiveDate,
+    pub start: Time,
+    pub duration: Minutes,
+    pub comment: Option<String>,
+}
+
+impl Worklog {
+    #[must_use]
+    pub fn started(&self) -> NaiveDateTime {
+        let start = NaiveTime::from_hms_opt(self.start.hour.into(), self.start.minute.into(), 0);
+        self.date.and_time(start.expect("valid time"))
+    }
+
+    /// Identifies the entry in the comment of the worklog, entries whose marker is already in a
+    /// worklog of the issue have been pushed before.
+    #[must_use]
+    pub fn marker(&self) -> String {
+        format!("[timesheet {}T{}]", self.date, self.start)
+    }
+
+    /// The comment of the entry followed by the marker.
+    #[must_use]
+    pub fn text(&self) -> String {
+        match &self.comment {
+            Some(comment) => format!("{comment} {}", self.marker()),
+            None => self.marker(),
+        }
+    }
+}
+
+/// An entry without a ticket key in its comment and without an issue for its identifier.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Unmapped {
+    pub line: usize,
+    pub identifier: Identifier,
+}
+
+impl Display for Unmapped {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "No Jira issue for {} in line {}",
+            self.identifier, self.line
+        )
+    }
+}
+
+/// The first ticket key like `PROJ-123` in `comment`.
+#[must_use]
+pub fn ticket(comment: &str) -> Option<&str> {
+    TICKET.find(comment).map(|m| m.as_str())
+}
+
+fn worklog(
+    date: NaiveDate,
+    entry: &Positioned<Entry>,
+    issues: &BTreeMap<String, String>,
+) -> Result<Worklog, Unmapped> {
+    let value = &entry.value;
+    let issue = value
+        .comment
+        .as_deref()
+        .and_then(ticket)
+        .or_else(|| issues.get(value.identifier.as_str()).map(String::as_str))
+        .ok_or_else(|| Unmapped {
+            line: entry.line,
+            identifier: value.identifier.clone(),
+        })?;
+    Ok(Worklog {
+        issue: issue.to_owned(),
+        date,
+        start: value.start.value,
+        duration: value.duration,
+        comment: value.comment.clone(),
+    })
+}
+
+/// The worklogs of all entries of `month`, the issue is the first ticket key in the comment of
+/// an entry or else the issue of its identifier in `issues`.
+#[must_use]
+pub fn worklogs(
+    month: &Month,
+    issues: &BTreeMap<String, String>,
+) -> Vec<Result<Worklog, Unmapped>> {
+    month
+        .days
+        .iter()
+        .flat_map(|day| {
+            let date = day.date.value.into_inner();
+            day.entries
+                .iter()
+                .filter(|e| e.value.duration > Minutes::default())
+                .map(move |e| worklog(date, e, issues))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::convert::Options;
+    use crate::parse::parse_month;
+    use crate::Date;
+
+    use super::*;
+
+    #[test]
+    fn test_ticket() {
+        assert_eq!(ticket("Review of PROJ-12 and AB-3"), Some("PROJ-12"));
+        assert_eq!(ticket("X2-7"), Some("X2-7"));
+        assert_eq!(ticket("Proj-12"), None);
+        assert_eq!(ticket("PROJ-"), None);
+        assert_eq!(ticket("PROJ-12a"), None);
+    }
+
+    #[test]
+    fn test_worklogs() {
+        let text = "* Mo. 15.04.\n09:00 AA Daily\n09:15 AA Review of OPS-7\n10:00 BB\n11:00\n";
+        let month = Date::new(NaiveDate::from_ymd_opt(2024, 4, 1).unwrap());
+        let month = Month::convert(
+            parse_month(text.as_bytes(), month).unwrap(),
+            &Options::default(),
+        )
+        .unwrap();
+        let issues = BTreeMap::from([("AA".to_owned(), "PROJ-1".to_owned())]);
+        let worklogs = worklogs(&month, &issues);
+        assert_eq!(worklogs.len(), 3);
+
+        let daily = worklogs[0].as_ref().unwrap();
+        assert_eq!(daily.issue, "PROJ-1");
+        assert_eq!(daily.duration, Minutes::from(15));
+        assert_eq!(daily.started().to_string(), "2024-04-15 09:00:00");
+        assert_eq!(daily.text(), "Daily [timesheet 2024-04-15T09:00]");
+
+        let review = worklogs[1].as_ref().unwrap();
+        assert_eq!(review.issue, "OPS-7");
+        assert_eq!(review.marker(), "[timesheet 2024-04-15T09:15]");
+
+        let unmapped = worklogs[2].as_ref().unwrap_err();
+        assert_eq!(unmapped.to_string(), "No Jira issue for BB in line 4");
+    }
+}
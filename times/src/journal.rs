@@ -0,0 +1,152 @@
+use std::fmt::Write;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+use thiserror::Error;
+
+/// Number of appends kept per file.
+const MAX_ENTRIES: usize = 20;
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("Failed to access {path}: {1}", path = .0.display())]
+    Io(PathBuf, std::io::Error),
+    #[error("Journal {path} is corrupt", path = .0.display())]
+    Corrupt(PathBuf),
+    #[error("There is nothing to undo")]
+    Empty,
+    #[error("{path} was changed after the last append", path = .0.display())]
+    Changed(PathBuf),
+}
+
+/// Text appended to the end of a file that was `offset` bytes long.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Append {
+    pub offset: usize,
+    pub text: String,
+}
+
+impl Append {
+    /// `contents` without this append, `None` if they do not end with it.
+    #[must_use]
+    pub fn revert<'a>(&self, contents: &'a str) -> Option<&'a str> {
+        let end = self.offset.checked_add(self.text.len())?;
+        (end == contents.len() && contents.get(self.offset..)? == self.text)
+            .then(|| &contents[..self.offset])
+    }
+}
+
+/// The hidden journal next to `file`, e.g. `.2024-04.tsh.journal`.
+#[must_use]
+pub fn journal_path(file: &Path) -> PathBuf {
+    let name = file.file_name().unwrap_or_default().to_string_lossy();
+    file.with_file_name(format!(".{name}.journal"))
+}
+
+/// Each entry is a line `@<offset> <length>` followed by the appended text.
+fn parse(mut s: &str) -> Option<Vec<Append>> {
+    let mut entries = Vec::new();
+    while !s.is_empty() {
+        let (header, rest) = s.strip_prefix('@')?.split_once('\n')?;
+        let (offset, len) = header.split_once(' ')?;
+        let len: usize = len.parse().ok()?;
+        entries.push(Append {
+            offset: offset.parse().ok()?,
+            text: rest.get(..len)?.to_owned(),
+        });
+        s = &rest[len..];
+    }
+    Some(entries)
+}
+
+fn serialize(entries: &[Append]) -> String {
+    let mut s = String::new();
+    for e in entries {
+        writeln!(s, "@{} {}", e.offset, e.text.len()).unwrap();
+        s.push_str(&e.text);
+    }
+    s
+}
+
+fn read(file: &Path) -> Result<Vec<Append>, Error> {
+    let path = journal_path(file);
+    match fs::read_to_string(&path) {
+        Ok(text) => parse(&text).ok_or(Error::Corrupt(path)),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(Vec::new()),
+        Err(e) => Err(Error::Io(path, e)),
+    }
+}
+
+fn write(file: &Path, entries: &[Append]) -> Result<(), Error> {
+    let path = journal_path(file);
+    let result = if entries.is_empty() {
+        fs::remove_file(&path)
+    } else {
+        crate::atomic::write(&path, serialize(entries))
+    };
+    result.map_err(|e| Error::Io(path, e))
+}
+
+/// Remembers that `append` was written to `file`.
+pub fn record(file: &Path, append: Append) -> Result<(), Error> {
+    let mut entries = read(file)?;
+    entries.push(append);
+    let excess = entries.len().saturating_sub(MAX_ENTRIES);
+    write(file, &entries[excess..])
+}
+
+/// The last append to `file`.
+pub fn last(file: &Path) -> Result<Append, Error> {
+    read(file)?.pop().ok_or(Error::Empty)
+}
+
+/// Removes the last append from `file` if the file still ends with it.
+pub fn undo(file: &Path) -> Result<Append, Error> {
+    let mut entries = read(file)?;
+    let append = entries.pop().ok_or(Error::Empty)?;
+    let contents = fs::read_to_string(file).map_err(|e| Error::Io(file.to_owned(), e))?;
+    let reverted = append
+        .revert(&contents)
+        .ok_or_else(|| Error::Changed(file.to_owned()))?;
+    crate::atomic::write(file, reverted).map_err(|e| Error::Io(file.to_owned(), e))?;
+    write(file, &entries)?;
+    Ok(append)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn append(offset: usize, text: &str) -> Append {
+        Append {
+            offset,
+            text: text.to_owned(),
+        }
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let entries = vec![append(0, "\n* Mo. 15.04.\n@1 2\n"), append(19, "ä\n")];
+        assert_eq!(parse(&serialize(&entries)), Some(entries));
+        assert_eq!(parse("@3 10\nabc"), None);
+        assert_eq!(parse("3 1\na"), None);
+    }
+
+    #[test]
+    fn test_revert() {
+        let append = append(4, "\n* Di. 16.04.\n");
+        assert_eq!(append.revert("text\n* Di. 16.04.\n"), Some("text"));
+        assert_eq!(append.revert("text\n* Di. 16.04.\n09:00\n"), None);
+        assert_eq!(append.revert("text\n* Mi. 17.04.\n"), None);
+        assert_eq!(append.revert(""), None);
+    }
+
+    #[test]
+    fn test_journal_path() {
+        assert_eq!(
+            journal_path(Path::new("a/2024-04.tsh")),
+            PathBuf::from("a/.2024-04.tsh.journal")
+        );
+    }
+}
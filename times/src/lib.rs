@@ -6,23 +6,74 @@
 
 use std::fmt::{Display, Formatter};
 use std::iter::Sum;
-use std::ops::{Add, AddAssign, Sub};
+use std::ops::{Add, AddAssign, Neg, Sub};
 
-use chrono::{Datelike, NaiveDate, Weekday};
+use chrono::{Datelike, Days, IsoWeek, NaiveDate, Weekday};
+use serde::{Deserialize, Serialize};
 
+/// Emits a debug event with the `tracing` feature and nothing without it.
+macro_rules! debug {
+    ($($arg:tt)*) => {
+        #[cfg(feature = "tracing")]
+        tracing::debug!($($arg)*);
+    };
+}
+
+pub mod ast;
+pub mod atomic;
+pub mod backup;
+pub mod cache;
+pub mod calendar;
+#[cfg(any(test, feature = "capi"))]
+pub mod capi;
+pub mod catalog;
+pub mod classify;
+pub mod config;
 pub mod convert;
+pub mod data;
+pub mod diagnostic;
+pub mod diff;
+pub mod edit;
+pub mod error;
+pub mod export;
+#[cfg(test)]
+mod fixture;
 pub mod format;
 pub mod generate;
+pub mod holidays;
+pub mod i18n;
+pub mod import;
+pub mod index;
+pub mod jira;
+pub mod journal;
+pub mod lint;
+pub mod lock;
 pub mod parse;
+pub mod remind;
 pub mod report;
-
-#[derive(Debug, Default, Eq, PartialEq, Copy, Clone, Ord, PartialOrd)]
+pub mod search;
+pub mod stats;
+#[cfg(any(test, feature = "proptest"))]
+pub mod strategy;
+pub mod testing;
+pub mod transform;
+
+pub use error::Error;
+
+#[derive(
+    Debug, Default, Eq, PartialEq, Copy, Clone, Ord, PartialOrd, Hash, Serialize, Deserialize,
+)]
 pub struct Minutes(usize);
 
 impl Minutes {
     #[must_use]
     pub fn from_hours(hours: usize) -> Self {
-        Self(hours.checked_mul(60).unwrap())
+        Self::checked_from_hours(hours).unwrap()
+    }
+
+    #[must_use]
+    pub fn checked_from_hours(hours: usize) -> Option<Self> {
+        hours.checked_mul(60).map(Self)
     }
 
     #[must_use]
@@ -37,6 +88,11 @@ impl Minutes {
     pub fn into_inner(self) -> usize {
         self.0
     }
+
+    #[must_use]
+    pub fn saturating_sub(self, rhs: Minutes) -> Self {
+        Self(self.0.saturating_sub(rhs.0))
+    }
 }
 
 impl AddAssign<Minutes> for Minutes {
@@ -46,13 +102,97 @@ impl AddAssign<Minutes> for Minutes {
 }
 
 impl Sub<Minutes> for Minutes {
-    type Output = Self;
+    type Output = SignedMinutes;
 
     fn sub(self, rhs: Minutes) -> Self::Output {
+        SignedMinutes::from(self) - SignedMinutes::from(rhs)
+    }
+}
+
+/// A duration that can be negative, e.g. the difference to the expected working time.
+#[derive(Debug, Default, Eq, PartialEq, Copy, Clone, Ord, PartialOrd)]
+pub struct SignedMinutes(isize);
+
+impl SignedMinutes {
+    #[must_use]
+    pub fn is_negative(self) -> bool {
+        self.0 < 0
+    }
+
+    #[must_use]
+    pub fn abs(self) -> Minutes {
+        Minutes(self.0.unsigned_abs())
+    }
+
+    #[must_use]
+    pub fn into_inner(self) -> isize {
+        self.0
+    }
+}
+
+impl From<Minutes> for SignedMinutes {
+    fn from(value: Minutes) -> Self {
+        Self(isize::try_from(value.0).expect("minutes fit into isize"))
+    }
+}
+
+impl From<isize> for SignedMinutes {
+    fn from(value: isize) -> Self {
+        Self(value)
+    }
+}
+
+impl TryFrom<SignedMinutes> for Minutes {
+    type Error = std::num::TryFromIntError;
+
+    fn try_from(value: SignedMinutes) -> Result<Self, Self::Error> {
+        usize::try_from(value.0).map(Minutes)
+    }
+}
+
+impl Add<SignedMinutes> for SignedMinutes {
+    type Output = Self;
+
+    fn add(self, rhs: SignedMinutes) -> Self::Output {
+        Self(self.0 + rhs.0)
+    }
+}
+
+impl AddAssign<SignedMinutes> for SignedMinutes {
+    fn add_assign(&mut self, rhs: SignedMinutes) {
+        self.0 += rhs.0;
+    }
+}
+
+impl Sub<SignedMinutes> for SignedMinutes {
+    type Output = Self;
+
+    fn sub(self, rhs: SignedMinutes) -> Self::Output {
         Self(self.0 - rhs.0)
     }
 }
 
+impl Neg for SignedMinutes {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        Self(-self.0)
+    }
+}
+
+impl Sum<SignedMinutes> for SignedMinutes {
+    fn sum<I: Iterator<Item = SignedMinutes>>(iter: I) -> Self {
+        iter.fold(SignedMinutes::default(), |a, b| a + b)
+    }
+}
+
+impl Display for SignedMinutes {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let sign = if self.is_negative() { '-' } else { '+' };
+        write!(f, "{sign}{}", self.abs().into_duration())
+    }
+}
+
 pub struct ClockDuration {
     hours: usize,
     minutes: usize,
@@ -84,7 +224,9 @@ impl From<usize> for Minutes {
     }
 }
 
-#[derive(Debug, Default, Ord, PartialOrd, Eq, PartialEq, Copy, Clone)]
+#[derive(
+    Debug, Default, Ord, PartialOrd, Eq, PartialEq, Hash, Copy, Clone, Serialize, Deserialize,
+)]
 pub struct Time {
     pub hour: u8,
     pub minute: u8,
@@ -100,6 +242,21 @@ impl Time {
         }
     }
 
+    /// Rounds to the nearest multiple of `granularity` minutes that is still on the same day.
+    #[must_use]
+    pub fn rounded(self, granularity: u8) -> Self {
+        let minutes = u16::from(self.hour) * 60 + u16::from(self.minute);
+        let granularity = u16::from(granularity);
+        let mut rounded = (minutes + granularity / 2) / granularity * granularity;
+        if rounded >= 24 * 60 {
+            rounded -= granularity;
+        }
+        Self {
+            hour: u8::try_from(rounded / 60).unwrap(),
+            minute: u8::try_from(rounded % 60).unwrap(),
+        }
+    }
+
     #[must_use]
     pub fn elapsed(self, o: Time) -> Option<Minutes> {
         let hours = self.hour.checked_sub(o.hour)?;
@@ -111,9 +268,58 @@ impl Time {
         };
         Some(minutes.into())
     }
+
+    /// Moves the time by `minutes`, `None` if it would leave the day.
+    #[must_use]
+    pub fn checked_add_signed(self, minutes: SignedMinutes) -> Option<Self> {
+        let current = isize::from(self.hour) * 60 + isize::from(self.minute);
+        let moved = current.checked_add(minutes.into_inner())?;
+        if !(0..24 * 60).contains(&moved) {
+            return None;
+        }
+        Time::new(
+            u8::try_from(moved / 60).ok()?,
+            u8::try_from(moved % 60).ok()?,
+        )
+    }
 }
 
-#[derive(Debug, Eq, PartialEq)]
+/// The minutes of every time have to be a multiple of the granularity.
+#[derive(Debug, Eq, PartialEq, Copy, Clone, Hash)]
+pub struct Granularity(u8);
+
+impl Granularity {
+    pub const ALLOWED: [u8; 5] = [1, 3, 5, 6, 15];
+
+    #[must_use]
+    pub fn new(minutes: u8) -> Option<Self> {
+        Self::ALLOWED.contains(&minutes).then_some(Self(minutes))
+    }
+
+    #[must_use]
+    pub fn minutes(self) -> u8 {
+        self.0
+    }
+
+    #[must_use]
+    pub fn matches(self, time: Time) -> bool {
+        time.minute.is_multiple_of(self.0)
+    }
+}
+
+impl Default for Granularity {
+    fn default() -> Self {
+        Self(3)
+    }
+}
+
+impl Display for Granularity {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
 pub enum Topic {
     Break,
     Project {
@@ -122,7 +328,7 @@ pub enum Topic {
     },
 }
 
-#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+#[derive(Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Copy, Clone, Serialize, Deserialize)]
 pub struct Date(NaiveDate);
 
 impl Date {
@@ -131,6 +337,11 @@ impl Date {
         Self(date)
     }
 
+    #[must_use]
+    pub fn into_inner(self) -> NaiveDate {
+        self.0
+    }
+
     #[must_use]
     pub fn year(&self) -> i32 {
         self.0.year()
@@ -141,6 +352,28 @@ impl Date {
         self.0.month()
     }
 
+    #[must_use]
+    pub fn day(&self) -> u32 {
+        self.0.day()
+    }
+
+    #[must_use]
+    pub fn iso_week(&self) -> IsoWeek {
+        self.0.iso_week()
+    }
+
+    /// All days of an ISO week starting on Monday.
+    #[must_use]
+    pub fn days_of_week(year: i32, week: u32) -> Option<Vec<Self>> {
+        let monday = NaiveDate::from_isoywd_opt(year, week, Weekday::Mon)?;
+        Some(monday.iter_days().take(7).map(Self).collect())
+    }
+
+    #[must_use]
+    pub fn weekday(&self) -> Weekday {
+        self.0.weekday()
+    }
+
     #[must_use]
     pub fn is_weekday(&self) -> bool {
         !matches!(self.0.weekday(), Weekday::Sat | Weekday::Sun)
@@ -154,6 +387,17 @@ impl Date {
             .nth(1)
     }
 
+    /// Daylight saving time transition on this day, following the european rules.
+    #[must_use]
+    pub fn dst_transition(&self) -> Option<DstTransition> {
+        let last_sunday = self.0.weekday() == Weekday::Sun && (self.0 + Days::new(7)).day() < 8;
+        match self.0.month() {
+            3 if last_sunday => Some(DstTransition::Forward),
+            10 if last_sunday => Some(DstTransition::Backward),
+            _ => None,
+        }
+    }
+
     pub fn next_weekday_in_month(&self) -> Option<Self> {
         self.0
             .iter_days()
@@ -163,6 +407,14 @@ impl Date {
     }
 }
 
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum DstTransition {
+    /// Clocks are moved forward from 02:00 to 03:00.
+    Forward,
+    /// Clocks are moved back from 03:00 to 02:00.
+    Backward,
+}
+
 fn weekday_to_str(weekday: Weekday) -> &'static str {
     match weekday {
         Weekday::Mon => "Mo",
@@ -187,7 +439,7 @@ impl Display for Date {
     }
 }
 
-#[derive(Debug, Eq, PartialEq)]
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
 pub struct Entry {
     pub time: Time,
     pub topic: Topic,
@@ -200,7 +452,25 @@ pub struct Day {
     pub entries: Vec<Positioned<Entry>>,
 }
 
-#[derive(Debug, Clone, Default, Eq, PartialEq)]
+#[derive(Debug, Default, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct FileMeta {
+    pub name: Option<String>,
+    pub hours_per_week: Option<Minutes>,
+    /// Days with only half of the usual expected time, defaults to 24.12. and 31.12.
+    pub half_days: Option<Vec<Date>>,
+    /// All values in the order they appear in the file, including the known ones.
+    pub values: Vec<(String, String)>,
+}
+
+#[derive(Debug)]
+pub struct Month {
+    /// The first day of the month.
+    pub month: Date,
+    pub meta: FileMeta,
+    pub days: Vec<Day>,
+}
+
+#[derive(Debug, Clone, Default, Eq, PartialEq, Serialize, Deserialize)]
 pub struct Positioned<T> {
     pub line: usize,
     pub value: T,
@@ -214,7 +484,63 @@ impl<T> Positioned<T> {
 
 #[cfg(test)]
 mod test {
-    use crate::Time;
+    use chrono::NaiveDate;
+
+    use crate::{Date, DstTransition, Minutes, SignedMinutes, Time};
+
+    #[test]
+    fn test_signed_minutes() {
+        let delta = Minutes::from(30) - Minutes::from(95);
+        assert_eq!(delta, SignedMinutes::from(-65));
+        assert_eq!(delta.to_string(), "-01:05");
+        assert_eq!((-delta).to_string(), "+01:05");
+        assert_eq!(SignedMinutes::default().to_string(), "+00:00");
+        assert_eq!(delta.abs(), Minutes::from(65));
+        assert!(Minutes::try_from(delta).is_err());
+        assert_eq!(Minutes::try_from(-delta), Ok(Minutes::from(65)));
+        assert_eq!(
+            Minutes::from(30).saturating_sub(Minutes::from(95)),
+            Minutes::default()
+        );
+    }
+
+    #[test]
+    fn test_dst_transition() {
+        let date = |m, d| Date::new(NaiveDate::from_ymd_opt(2024, m, d).unwrap());
+        assert_eq!(date(3, 31).dst_transition(), Some(DstTransition::Forward));
+        assert_eq!(date(3, 24).dst_transition(), None);
+        assert_eq!(date(10, 27).dst_transition(), Some(DstTransition::Backward));
+        assert_eq!(date(10, 20).dst_transition(), None);
+        assert_eq!(date(4, 28).dst_transition(), None);
+    }
+
+    #[test]
+    fn test_rounded() {
+        let time = |h, m| Time::new(h, m).unwrap();
+        assert_eq!(time(10, 17).rounded(3), time(10, 18));
+        assert_eq!(time(10, 16).rounded(3), time(10, 15));
+        assert_eq!(time(10, 59).rounded(3), time(11, 0));
+        assert_eq!(time(23, 59).rounded(3), time(23, 57));
+        assert_eq!(time(10, 7).rounded(15), time(10, 0));
+    }
+
+    #[test]
+    fn test_checked_add_signed() {
+        let time = Time::new(9, 50).unwrap();
+        assert_eq!(
+            time.checked_add_signed(SignedMinutes::from(15)),
+            Time::new(10, 5)
+        );
+        assert_eq!(
+            time.checked_add_signed(SignedMinutes::from(-590)),
+            Time::new(0, 0)
+        );
+        assert_eq!(time.checked_add_signed(SignedMinutes::from(-591)), None);
+        assert_eq!(
+            time.checked_add_signed(SignedMinutes::from(14 * 60 + 10)),
+            None
+        );
+    }
 
     #[test]
     fn test_elapsed() {
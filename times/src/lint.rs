@@ -0,0 +1,544 @@
+use std::collections::BTreeMap;
+use std::fmt::{Debug, Formatter};
+use std::sync::Arc;
+
+use chrono::Weekday;
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::catalog::Catalog;
+use crate::convert::Day;
+use crate::diagnostic::Severity;
+use crate::{DstTransition, Minutes, Time, Topic};
+
+#[derive(Debug, Error, Eq, PartialEq)]
+pub enum Warning {
+    #[error("Time in line {0} does not exist, clocks are moved forward from 02:00 to 03:00")]
+    NonExistentTime(usize),
+    #[error("Time in line {0} is ambiguous, clocks are moved back from 03:00 to 02:00")]
+    AmbiguousTime(usize),
+    #[error(
+        "Time in line {0} spans the daylight saving time change, its duration is off by one hour"
+    )]
+    AcrossDstChange(usize),
+    #[error("Unknown identifier {1} in line {0}{suggestion}", suggestion = did_you_mean(.2.as_deref()))]
+    UnknownIdentifier(usize, String, Option<String>),
+    #[error("Day in line {0} has a work time of {work}, more than the allowed {max}", work = .1.into_duration(), max = .2.into_duration())]
+    MaxDailyWorkTime(usize, Minutes, Minutes),
+    #[error("Day in line {0} has breaks of {breaks}, at least {required} are required", breaks = .1.into_duration(), required = .2.into_duration())]
+    MissingBreak(usize, Minutes, Minutes),
+    #[error("Day in line {0} is not terminated by a break")]
+    UnterminatedDay(usize),
+    #[error("Time in line {0} starts {gap} after the time before it", gap = .1.into_duration())]
+    Gap(usize, Minutes),
+    #[error("Day in line {0} is on a weekend")]
+    WeekendWork(usize),
+    /// A problem found by a [`Rule`] with its code.
+    #[error("{2}")]
+    Custom(usize, String, String),
+}
+
+/// How the warnings of a rule are reported.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Level {
+    Off,
+    Warning,
+    Error,
+}
+
+impl Level {
+    #[must_use]
+    pub fn severity(self) -> Option<Severity> {
+        match self {
+            Level::Off => None,
+            Level::Warning => Some(Severity::Warning),
+            Level::Error => Some(Severity::Error),
+        }
+    }
+}
+
+/// The codes of all warnings with their default level.
+pub const RULES: [(&str, Level); 9] = [
+    ("non-existent-time", Level::Warning),
+    ("ambiguous-time", Level::Warning),
+    ("across-dst-change", Level::Warning),
+    ("unknown-identifier", Level::Warning),
+    ("max-daily-work", Level::Warning),
+    ("missing-break", Level::Off),
+    ("unterminated-day", Level::Warning),
+    ("gap", Level::Off),
+    ("weekend-work", Level::Off),
+];
+
+/// A check of the converted days in addition to the built-in rules, e.g. a company-specific
+/// validation.
+pub trait Rule: Send + Sync {
+    /// The code of its warnings, containing a `/` like `acme/ticket` to tell it apart from the
+    /// built-in rules. Its level is configured in `[lint.rules]` like theirs.
+    fn code(&self) -> &str;
+
+    /// The level if none is configured.
+    fn default_level(&self) -> Level {
+        Level::Warning
+    }
+
+    /// The line and message of each problem of `day`.
+    fn check(&self, day: &Day) -> Vec<(usize, String)>;
+}
+
+/// The registered [`Rule`]s, equal if they have the same codes.
+#[derive(Clone, Default)]
+pub struct Custom(pub Vec<Arc<dyn Rule>>);
+
+impl Custom {
+    fn codes(&self) -> impl Iterator<Item = &str> {
+        self.0.iter().map(|r| r.code())
+    }
+}
+
+impl Debug for Custom {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self.codes()).finish()
+    }
+}
+
+impl PartialEq for Custom {
+    fn eq(&self, other: &Self) -> bool {
+        self.codes().eq(other.codes())
+    }
+}
+
+impl Eq for Custom {}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Options {
+    pub max_daily_work: Minutes,
+    /// Longest time without an entry between two entries of a day.
+    pub max_gap: Minutes,
+    /// Levels by code replacing the defaults of [`RULES`] and of the custom rules.
+    pub levels: BTreeMap<String, Level>,
+    pub custom: Custom,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            max_daily_work: Minutes::from_hours(10),
+            max_gap: Minutes::from_hours(2),
+            levels: BTreeMap::new(),
+            custom: Custom::default(),
+        }
+    }
+}
+
+impl Options {
+    #[must_use]
+    pub fn level(&self, code: &str) -> Level {
+        self.levels.get(code).copied().unwrap_or_else(|| {
+            let custom = self.custom.0.iter().map(|r| (r.code(), r.default_level()));
+            RULES
+                .into_iter()
+                .chain(custom)
+                .find(|(c, _)| *c == code)
+                .map_or(Level::Warning, |(_, level)| level)
+        })
+    }
+
+    #[must_use]
+    pub fn severity(&self, warning: &Warning) -> Option<Severity> {
+        self.level(warning.code()).severity()
+    }
+}
+
+impl Warning {
+    #[must_use]
+    pub fn line(&self) -> usize {
+        match *self {
+            Warning::NonExistentTime(line)
+            | Warning::AmbiguousTime(line)
+            | Warning::AcrossDstChange(line)
+            | Warning::UnknownIdentifier(line, ..)
+            | Warning::MaxDailyWorkTime(line, ..)
+            | Warning::MissingBreak(line, ..)
+            | Warning::UnterminatedDay(line)
+            | Warning::Gap(line, _)
+            | Warning::WeekendWork(line)
+            | Warning::Custom(line, ..) => line,
+        }
+    }
+
+    #[must_use]
+    pub fn code(&self) -> &str {
+        match self {
+            Warning::NonExistentTime(_) => "non-existent-time",
+            Warning::AmbiguousTime(_) => "ambiguous-time",
+            Warning::AcrossDstChange(_) => "across-dst-change",
+            Warning::UnknownIdentifier(..) => "unknown-identifier",
+            Warning::MaxDailyWorkTime(..) => "max-daily-work",
+            Warning::MissingBreak(..) => "missing-break",
+            Warning::UnterminatedDay(_) => "unterminated-day",
+            Warning::Gap(..) => "gap",
+            Warning::WeekendWork(_) => "weekend-work",
+            Warning::Custom(_, code, _) => code,
+        }
+    }
+}
+
+fn did_you_mean(suggestion: Option<&str>) -> String {
+    suggestion
+        .map(|s| format!(", did you mean {s}?"))
+        .unwrap_or_default()
+}
+
+fn dst(day: &Day, warnings: &mut Vec<Warning>) {
+    let Some(transition) = day.date.value.dst_transition() else {
+        return;
+    };
+    let two = Time::new(2, 0).unwrap();
+    let three = Time::new(3, 0).unwrap();
+    let in_gap = |time: Time| two <= time && time < three;
+    let mut last_line = None;
+    for entry in &day.entries {
+        let entry = &entry.value;
+        for time in [&entry.start, &entry.end] {
+            if in_gap(time.value) && last_line != Some(time.line) {
+                last_line = Some(time.line);
+                warnings.push(match transition {
+                    DstTransition::Forward => Warning::NonExistentTime(time.line),
+                    DstTransition::Backward => Warning::AmbiguousTime(time.line),
+                });
+            }
+        }
+        if entry.start.value < two && three <= entry.end.value {
+            warnings.push(Warning::AcrossDstChange(entry.start.line));
+        }
+    }
+}
+
+fn max_daily_work(day: &Day, options: &Options, warnings: &mut Vec<Warning>) {
+    let work = day.times.billable_time();
+    if options.max_daily_work < work {
+        warnings.push(Warning::MaxDailyWorkTime(
+            day.date.line,
+            work,
+            options.max_daily_work,
+        ));
+    }
+}
+
+/// The times between the entries of `day` with their line, entries within a travel time are
+/// ignored.
+fn breaks(day: &Day) -> impl Iterator<Item = (usize, Minutes)> + '_ {
+    let mut end: Option<Time> = None;
+    day.entries.iter().filter_map(move |entry| {
+        let previous =
+            end.replace(end.map_or(entry.value.end.value, |e| e.max(entry.value.end.value)));
+        let gap = entry.value.start.value.elapsed(previous?)?;
+        (gap > Minutes::default()).then_some((entry.line, gap))
+    })
+}
+
+/// Required breaks of the German working hours act.
+fn missing_break(day: &Day, warnings: &mut Vec<Warning>) {
+    let (Some(first), Some(last)) = (day.entries.first(), day.entries.last()) else {
+        return;
+    };
+    let breaks: Minutes = breaks(day).map(|(_, gap)| gap).sum();
+    let span = last
+        .value
+        .end
+        .value
+        .elapsed(first.value.start.value)
+        .unwrap_or_default();
+    let work = span.saturating_sub(breaks);
+    let required = if work > Minutes::from_hours(9) {
+        Minutes::from(45)
+    } else if work > Minutes::from_hours(6) {
+        Minutes::from(30)
+    } else {
+        return;
+    };
+    if breaks < required {
+        warnings.push(Warning::MissingBreak(day.date.line, breaks, required));
+    }
+}
+
+fn gaps(day: &Day, options: &Options, warnings: &mut Vec<Warning>) {
+    warnings.extend(
+        breaks(day)
+            .filter(|(_, gap)| options.max_gap < *gap)
+            .map(|(line, gap)| Warning::Gap(line, gap)),
+    );
+}
+
+fn weekend_work(day: &Day, warnings: &mut Vec<Warning>) {
+    if !day.entries.is_empty() && matches!(day.date.value.weekday(), Weekday::Sat | Weekday::Sun) {
+        warnings.push(Warning::WeekendWork(day.date.line));
+    }
+}
+
+/// Days of a file that could not be converted because their last entry is not a break.
+#[must_use]
+pub fn unterminated_days(days: &[crate::Day], options: &Options) -> Vec<Warning> {
+    if options.level("unterminated-day") == Level::Off {
+        return Vec::new();
+    }
+    days.iter()
+        .filter(|d| {
+            d.entries
+                .last()
+                .is_some_and(|e| matches!(e.value.topic, Topic::Project { .. }))
+        })
+        .map(|d| Warning::UnterminatedDay(d.date.line))
+        .collect()
+}
+
+#[must_use]
+pub fn unknown_identifiers(days: &[Day], catalog: &Catalog) -> Vec<Warning> {
+    days.iter()
+        .flat_map(|d| &d.entries)
+        .filter(|e| !catalog.contains(e.value.identifier.as_str()))
+        .map(|e| {
+            let identifier = e.value.identifier.as_str();
+            Warning::UnknownIdentifier(
+                e.line,
+                identifier.to_owned(),
+                catalog.suggest(identifier).map(str::to_owned),
+            )
+        })
+        .collect()
+}
+
+#[must_use]
+pub fn lint(days: &[Day], options: &Options) -> Vec<Warning> {
+    let mut warnings = Vec::new();
+    for day in days {
+        max_daily_work(day, options, &mut warnings);
+        missing_break(day, &mut warnings);
+        dst(day, &mut warnings);
+        gaps(day, options, &mut warnings);
+        weekend_work(day, &mut warnings);
+        for rule in &options.custom.0 {
+            let problems = rule.check(day).into_iter();
+            warnings.extend(
+                problems
+                    .map(|(line, message)| Warning::Custom(line, rule.code().to_owned(), message)),
+            );
+        }
+    }
+    warnings.retain(|w| options.level(w.code()) != Level::Off);
+    warnings
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use crate::fixture::days;
+    use crate::parse::parse;
+    use crate::Date;
+
+    use super::*;
+
+    #[test]
+    fn test_dst_forward() {
+        let text = r"
+        * So. 24.03.
+        01:00 AA
+        02:30
+        * So. 31.03.
+        00:30 AA
+        02:30 BB
+        03:00
+        04:00 CC
+        05:00
+        ";
+        assert_eq!(
+            lint(&days(3, text), &Options::default()),
+            vec![Warning::NonExistentTime(7)]
+        );
+    }
+
+    #[test]
+    fn test_unknown() {
+        let text = r"
+        * Mo. 15.04.
+        09:00 AA
+        10:00 ANB
+        11:00 Urlaub
+        12:00
+        ";
+        let catalog: Catalog = ["AA", "AANB"].into_iter().collect();
+        let warnings = unknown_identifiers(&days(4, text), &catalog);
+        assert_eq!(
+            warnings,
+            vec![
+                Warning::UnknownIdentifier(4, "ANB".to_owned(), Some("AANB".to_owned())),
+                Warning::UnknownIdentifier(5, "Urlaub".to_owned(), None),
+            ]
+        );
+        assert_eq!(
+            warnings[0].to_string(),
+            "Unknown identifier ANB in line 4, did you mean AANB?"
+        );
+    }
+
+    #[test]
+    fn test_dst_backward() {
+        let text = r"
+        * So. 27.10.
+        01:00 AA
+        03:30
+        04:00 AA
+        05:00
+        ";
+        assert_eq!(
+            lint(&days(10, text), &Options::default()),
+            vec![Warning::AcrossDstChange(3)]
+        );
+    }
+
+    #[test]
+    fn test_max_daily_work() {
+        let text = r"
+        * Mo. 15.04.
+        07:00 AA
+        12:00
+        12:30 BB
+        17:30
+        * Di. 16.04.
+        07:00 AA
+        12:00
+        12:30 BB
+        18:00
+        ";
+        let warnings = lint(&days(4, text), &Options::default());
+        assert_eq!(
+            warnings,
+            vec![Warning::MaxDailyWorkTime(
+                7,
+                Minutes::from(10 * 60 + 30),
+                Minutes::from_hours(10)
+            )]
+        );
+        assert_eq!(
+            warnings[0].to_string(),
+            "Day in line 7 has a work time of 10:30, more than the allowed 10:00"
+        );
+
+        let options = Options {
+            max_daily_work: Minutes::from(10 * 60 + 30),
+            ..Options::default()
+        };
+        assert_eq!(lint(&days(4, text), &options), vec![]);
+    }
+
+    #[test]
+    fn test_breaks_and_gaps() {
+        let text = r"
+        * Mo. 15.04.
+        08:00 AA
+        12:00 BBFa
+        12:30 CC
+        13:00
+        13:09 AA
+        16:00
+        * Sa. 20.04.
+        08:00 AA
+        09:00
+        12:00 AA
+        13:00
+        ";
+        assert_eq!(lint(&days(4, text), &Options::default()), vec![]);
+        let levels = |level| {
+            ["missing-break", "gap", "weekend-work"]
+                .map(|code| (code.to_owned(), level))
+                .into()
+        };
+        let options = Options {
+            levels: levels(Level::Warning),
+            ..Options::default()
+        };
+        assert_eq!(
+            lint(&days(4, text), &options),
+            vec![
+                Warning::MissingBreak(2, Minutes::from(9), Minutes::from(30)),
+                Warning::Gap(12, Minutes::from_hours(3)),
+                Warning::WeekendWork(9),
+            ]
+        );
+        let options = Options {
+            max_gap: Minutes::from_hours(3),
+            levels: levels(Level::Error),
+            ..Options::default()
+        };
+        let warnings = lint(&days(4, text), &options);
+        assert_eq!(warnings.len(), 2);
+        assert_eq!(options.severity(&warnings[1]), Some(Severity::Error));
+    }
+
+    struct LongComment;
+
+    impl Rule for LongComment {
+        fn code(&self) -> &'static str {
+            "test/long-comment"
+        }
+
+        fn default_level(&self) -> Level {
+            Level::Error
+        }
+
+        fn check(&self, day: &Day) -> Vec<(usize, String)> {
+            day.entries
+                .iter()
+                .filter(|e| e.value.comment.as_ref().is_some_and(|c| c.len() > 10))
+                .map(|e| (e.line, format!("Comment in line {} is too long", e.line)))
+                .collect()
+        }
+    }
+
+    #[test]
+    fn test_custom() {
+        let text = r"
+        * Mo. 15.04.
+        09:00 AA Planning
+        10:00 AA Reviewing all the things
+        11:00
+        ";
+        let mut options = Options {
+            custom: Custom(vec![Arc::new(LongComment)]),
+            ..Options::default()
+        };
+        let warnings = lint(&days(4, text), &options);
+        assert_eq!(
+            warnings,
+            vec![Warning::Custom(
+                4,
+                "test/long-comment".to_owned(),
+                "Comment in line 4 is too long".to_owned()
+            )]
+        );
+        assert_eq!(warnings[0].code(), "test/long-comment");
+        assert_eq!(options.severity(&warnings[0]), Some(Severity::Error));
+        options
+            .levels
+            .insert("test/long-comment".to_owned(), Level::Off);
+        assert_eq!(lint(&days(4, text), &options), vec![]);
+        assert_ne!(options, Options::default());
+    }
+
+    #[test]
+    fn test_unterminated() {
+        let text = "* Mo. 15.04.\n09:00 AA\n12:00\n* Di. 16.04.\n09:00 AA\n";
+        let days = parse(
+            text.as_bytes(),
+            Date::new(NaiveDate::from_ymd_opt(2024, 4, 1).unwrap()),
+        )
+        .unwrap();
+        assert_eq!(
+            unterminated_days(&days, &Options::default()),
+            vec![Warning::UnterminatedDay(4)]
+        );
+    }
+}
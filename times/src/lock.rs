@@ -0,0 +1,76 @@
+use std::fs::{File, TryLockError};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use thiserror::Error;
+
+const RETRY: Duration = Duration::from_millis(50);
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("Failed to lock {path}: {1}", path = .0.display())]
+    Io(PathBuf, std::io::Error),
+    #[error("{path} is being changed by another process", path = .0.display())]
+    Locked(PathBuf),
+}
+
+/// The hidden lock file next to `file`, e.g. `.2024-04.tsh.lock`. The file itself cannot be
+/// locked because writes replace it.
+#[must_use]
+pub fn lock_path(file: &Path) -> PathBuf {
+    let name = file.file_name().unwrap_or_default().to_string_lossy();
+    file.with_file_name(format!(".{name}.lock"))
+}
+
+/// An exclusive advisory lock on a file, released when dropped or when the process exits.
+/// Only processes that lock the file as well are kept out, editors are not.
+#[derive(Debug)]
+pub struct Lock {
+    _file: File,
+}
+
+impl Lock {
+    /// Locks `file`, waiting up to `timeout` for another process holding the lock.
+    pub fn acquire(file: &Path, timeout: Duration) -> Result<Self, Error> {
+        let path = lock_path(file);
+        // The lock file is never removed, a process could still be waiting for the removed one.
+        let lock = File::options()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(&path)
+            .map_err(|e| Error::Io(path.clone(), e))?;
+        let start = Instant::now();
+        loop {
+            match lock.try_lock() {
+                Ok(()) => return Ok(Self { _file: lock }),
+                Err(TryLockError::WouldBlock) if start.elapsed() < timeout => {
+                    std::thread::sleep(RETRY);
+                }
+                Err(TryLockError::WouldBlock) => return Err(Error::Locked(file.to_owned())),
+                Err(TryLockError::Error(e)) => return Err(Error::Io(path, e)),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lock() {
+        let dir = tempfile::tempdir().unwrap();
+        let dir = dir.path();
+        let file = dir.join("2024-04.tsh");
+        assert_eq!(lock_path(&file), dir.join(".2024-04.tsh.lock"));
+        let lock = Lock::acquire(&file, Duration::ZERO).unwrap();
+        assert!(matches!(
+            Lock::acquire(&file, Duration::from_millis(100)),
+            Err(Error::Locked(_))
+        ));
+        assert!(Lock::acquire(&dir.join("2024-05.tsh"), Duration::ZERO).is_ok());
+        drop(lock);
+        assert!(Lock::acquire(&file, Duration::ZERO).is_ok());
+    }
+}
@@ -4,10 +4,15 @@ use std::mem::take;
 use std::str::FromStr;
 
 use chrono::format::{Item, Numeric, Pad, Parsed};
-use chrono::{Datelike, Weekday};
+use chrono::{Datelike, NaiveDate, Weekday};
 use thiserror::Error;
 
-use crate::{Date, Day, Entry, Positioned, Time, Topic};
+use crate::ast::Document;
+use crate::edit::{with_time, Fix};
+use crate::{
+    weekday_to_str, Date, Day, Entry, FileMeta, Granularity, Minutes, Month, Positioned, Time,
+    Topic,
+};
 
 #[derive(Debug, Error, Eq, PartialEq)]
 pub enum EntryError {
@@ -17,11 +22,13 @@ pub enum EntryError {
     MissingTime,
     #[error("Failed to parse date of day: {0}")]
     Date(DateError),
+    #[error("Invalid value for metadata {0:?}")]
+    Meta(String),
 }
 
 #[derive(Debug, Error, Eq, PartialEq)]
 pub enum DateError {
-    #[error("Expected a date in the format <day of week>. <day>.<month>.")]
+    #[error("Expected a date in the format <day of week>. <day>.<month>. or <year>-<month>-<day>")]
     Format,
     #[error("Invalid date")]
     Date,
@@ -58,7 +65,38 @@ impl Display for EntryErrors {
     }
 }
 
-#[derive(Debug, Eq, PartialEq)]
+impl EntryError {
+    /// Suggests a fix for this error in `line` of `document`.
+    #[must_use]
+    pub fn fix(&self, line: usize, document: &Document) -> Option<Fix> {
+        let text = document.lines().get(line.checked_sub(1)?)?.text().trim();
+        match self {
+            EntryError::Time => {
+                let token = text.split_whitespace().next()?;
+                let time = Time::parse_flexible(token).ok()?;
+                Some(Fix::new(
+                    line,
+                    format!("Write {token} as {time}"),
+                    vec![(line, with_time(text, time))],
+                ))
+            }
+            EntryError::Date(DateError::UnexpectedDayOfWeek) => {
+                let (weekday, date) = text.strip_prefix('*')?.split_once('.')?;
+                let expected =
+                    weekday_to_str(parse_day_month(date, document.month()).ok()?.weekday());
+                Some(Fix::new(
+                    line,
+                    format!("Change day of week {} to {expected}", weekday.trim()),
+                    vec![(line, format!("* {expected}.{date}"))],
+                ))
+            }
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Error, Eq, PartialEq)]
+#[error("Expected a time like 09:00")]
 pub struct TimeError;
 
 impl FromStr for Time {
@@ -69,10 +107,91 @@ impl FromStr for Time {
         if hour.len() != 2 || minute.len() != 2 {
             return Err(TimeError);
         }
-        Ok(Time {
-            hour: hour.parse().map_err(|_| TimeError)?,
-            minute: minute.parse().map_err(|_| TimeError)?,
-        })
+        Time::new(
+            hour.parse().map_err(|_| TimeError)?,
+            minute.parse().map_err(|_| TimeError)?,
+        )
+        .ok_or(TimeError)
+    }
+}
+
+impl Time {
+    /// Parses a time like [`FromStr`] does, but also accepts a single digit hour like `9:00`.
+    pub fn parse_flexible(s: &str) -> Result<Self, TimeError> {
+        let (hour, minute) = s.split_once(':').ok_or(TimeError)?;
+        if !(1..=2).contains(&hour.len()) || minute.len() != 2 {
+            return Err(TimeError);
+        }
+        Time::new(
+            hour.parse().map_err(|_| TimeError)?,
+            minute.parse().map_err(|_| TimeError)?,
+        )
+        .ok_or(TimeError)
+    }
+}
+
+#[derive(Debug, Error, Eq, PartialEq)]
+#[error("Expected a duration like 01:30, 90m or 1h30m")]
+pub struct MinutesError;
+
+impl FromStr for Minutes {
+    type Err = MinutesError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        fn number(s: &str) -> Result<usize, MinutesError> {
+            if s.is_empty() || !s.bytes().all(|b| b.is_ascii_digit()) {
+                return Err(MinutesError);
+            }
+            s.parse().map_err(|_| MinutesError)
+        }
+
+        if let Some((hours, minutes)) = s.split_once(':') {
+            if minutes.len() != 2 {
+                return Err(MinutesError);
+            }
+            let minutes = number(minutes)
+                .ok()
+                .filter(|m| *m < 60)
+                .ok_or(MinutesError)?;
+            let hours = Minutes::checked_from_hours(number(hours)?).ok_or(MinutesError)?;
+            return hours
+                .0
+                .checked_add(minutes)
+                .map(Minutes)
+                .ok_or(MinutesError);
+        }
+
+        let (hours, rest) = match s.split_once('h') {
+            Some((hours, rest)) => (Some(number(hours)?), rest),
+            None => (None, s),
+        };
+        let minutes = match (rest.strip_suffix('m'), hours) {
+            (Some(minutes), _) => number(minutes)?,
+            (None, Some(_)) if rest.is_empty() => 0,
+            _ => return Err(MinutesError),
+        };
+        if hours.is_some() && 60 <= minutes {
+            return Err(MinutesError);
+        }
+        Minutes::checked_from_hours(hours.unwrap_or_default())
+            .and_then(|hours| hours.0.checked_add(minutes))
+            .map(Minutes)
+            .ok_or(MinutesError)
+    }
+}
+
+#[derive(Debug, Error, Eq, PartialEq)]
+#[error("Expected a granularity of {allowed:?} minutes", allowed = Granularity::ALLOWED)]
+pub struct GranularityError;
+
+impl FromStr for Granularity {
+    type Err = GranularityError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.parse()
+            .ok()
+            .and_then(Granularity::new)
+            .ok_or(GranularityError)
     }
 }
 
@@ -132,27 +251,65 @@ fn parse_weekday(s: &str) -> Result<Weekday, DateError> {
     }
 }
 
-fn parse_date(line: &str, month: Date, after: u32) -> Result<Date, DateError> {
+fn parse_iso_date(line: &str) -> Option<Result<NaiveDate, DateError>> {
+    const ITEMS: &[Item<'static>] = &[
+        Item::Numeric(Numeric::Year, Pad::Zero),
+        Item::Literal("-"),
+        Item::Numeric(Numeric::Month, Pad::Zero),
+        Item::Literal("-"),
+        Item::Numeric(Numeric::Day, Pad::Zero),
+    ];
+
+    let mut parsed = Parsed::new();
+    chrono::format::parse(&mut parsed, line, ITEMS.iter()).ok()?;
+    Some(parsed.to_naive_date().map_err(|_| DateError::Date))
+}
+
+fn parse_day_month_in_year(date: &str, month: Date) -> Result<NaiveDate, DateError> {
     const ITEMS: &[Item<'static>] = &[
         Item::Numeric(Numeric::Day, Pad::Zero),
         Item::Literal("."),
         Item::Numeric(Numeric::Month, Pad::Zero),
         Item::Literal("."),
     ];
-    let (weekday, date) = line.split_once('.').ok_or(DateError::Format)?;
 
     let mut parsed = Parsed::new();
     chrono::format::parse(&mut parsed, date.trim(), ITEMS.iter()).map_err(|_| DateError::Format)?;
-    parsed.set_year(month.year().into()).unwrap();
-    let date = parsed.to_naive_date().map_err(|_| DateError::Date)?;
+    parsed
+        .set_year(month.year().into())
+        .map_err(|_| DateError::Date)?;
+    parsed.to_naive_date().map_err(|_| DateError::Date)
+}
+
+fn parse_day_month(date: &str, month: Date) -> Result<NaiveDate, DateError> {
+    let date = parse_day_month_in_year(date, month)?;
     if date.month() != month.month() {
         return Err(DateError::UnexpectedMonth);
     }
+    Ok(date)
+}
+
+fn parse_german_date(line: &str, month: Date) -> Result<NaiveDate, DateError> {
+    let (weekday, date) = line.split_once('.').ok_or(DateError::Format)?;
+    let date = parse_day_month(date, month)?;
 
     let weekday = parse_weekday(weekday.trim())?;
     if date.weekday() != weekday {
         return Err(DateError::UnexpectedDayOfWeek);
     }
+    Ok(date)
+}
+
+pub(crate) fn parse_date(line: &str, month: Date, after: u32) -> Result<Date, DateError> {
+    let date = if let Some(date) = parse_iso_date(line.trim()) {
+        let date = date?;
+        if date.year() != month.year() || date.month() != month.month() {
+            return Err(DateError::UnexpectedMonth);
+        }
+        date
+    } else {
+        parse_german_date(line, month)?
+    };
 
     if date.day() <= after {
         return Err(DateError::EntryOutOfOrder);
@@ -161,19 +318,71 @@ fn parse_date(line: &str, month: Date, after: u32) -> Result<Date, DateError> {
     Ok(Date(date))
 }
 
+/// Parses whole hours or a duration, at most the hours of a week.
+pub(crate) fn parse_hours(s: &str) -> Option<Minutes> {
+    match s.parse() {
+        Ok(hours) => Minutes::checked_from_hours(hours),
+        Err(_) => s.parse().ok(),
+    }
+    .filter(|hours| *hours <= Minutes::from_hours(7 * 24))
+}
+
+fn parse_half_days(s: &str, month: Date) -> Option<Vec<Date>> {
+    s.split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(|s| parse_day_month_in_year(s, month).ok().map(Date))
+        .collect()
+}
+
+fn parse_meta(meta: &mut FileMeta, line: &str, month: Date) -> Result<(), EntryError> {
+    let (key, value) = line
+        .split_once(':')
+        .ok_or_else(|| EntryError::Meta(line.trim().to_owned()))?;
+    let (key, value) = (key.trim(), value.trim());
+    match key {
+        "name" => meta.name = Some(value.to_owned()),
+        "hours_per_week" => {
+            meta.hours_per_week =
+                Some(parse_hours(value).ok_or_else(|| EntryError::Meta(key.to_owned()))?);
+        }
+        "half_days" => {
+            meta.half_days = Some(
+                parse_half_days(value, month).ok_or_else(|| EntryError::Meta(key.to_owned()))?,
+            );
+        }
+        _ => {}
+    }
+    meta.values.push((key.to_owned(), value.to_owned()));
+    Ok(())
+}
+
 pub fn parse(r: impl BufRead, month: Date) -> Result<Vec<Day>, Error> {
+    parse_month(r, month).map(|m| m.days)
+}
+
+#[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(r)))]
+pub fn parse_month(mut r: impl BufRead, month: Date) -> Result<Month, Error> {
+    let mut meta = FileMeta::default();
     let mut days = Vec::new();
     let mut current_day: Option<Day> = None;
     let mut comments = Vec::new();
     let mut errors = Vec::new();
-    for (index, line) in r.lines().enumerate() {
-        let index = index + 1;
-        let line = line?;
-        let line = line.trim();
+    let mut buffer = String::new();
+    for index in 1.. {
+        buffer.clear();
+        if r.read_line(&mut buffer)? == 0 {
+            break;
+        }
+        let line = buffer.trim();
         if line.is_empty() {
             continue;
         }
-        if let Some(comment) = line.strip_prefix('#') {
+        if let Some(line) = line.strip_prefix("#!").filter(|_| current_day.is_none()) {
+            if let Err(e) = parse_meta(&mut meta, line, month) {
+                errors.push(Positioned::new(index, e));
+            }
+        } else if let Some(comment) = line.strip_prefix('#') {
             comments.push(comment.to_owned());
         } else if let Some(line) = line.strip_prefix('*') {
             let last_day = current_day.take().map(|day| {
@@ -192,9 +401,7 @@ pub fn parse(r: impl BufRead, month: Date) -> Result<Vec<Day>, Error> {
                 entries: Vec::new(),
             });
         } else {
-            let day = current_day
-                .as_mut()
-                .ok_or_else(|| Error::ExpectedDay(index))?;
+            let day = current_day.as_mut().ok_or(Error::ExpectedDay(index))?;
             match line.parse() {
                 Ok(entry) => {
                     day.entries.push(Positioned::new(index, entry));
@@ -206,13 +413,20 @@ pub fn parse(r: impl BufRead, month: Date) -> Result<Vec<Day>, Error> {
     if let Some(day) = current_day.take() {
         days.push(day);
     }
+    debug!(days = days.len(), errors = errors.len(), "parsed");
     if errors.is_empty() {
-        Ok(days)
+        Ok(Month { month, meta, days })
     } else {
         Err(Error::Many(EntryErrors(errors)))
     }
 }
 
+/// Parses arbitrary bytes as the file of `month`, invalid input of any kind is reported as an
+/// error and never panics. This is the entry point for fuzzing.
+pub fn parse_bytes(bytes: &[u8], month: Date) -> Result<Month, Error> {
+    parse_month(bytes, month)
+}
+
 #[must_use]
 pub fn from_stem(stem: &str) -> Option<Date> {
     const ITEMS: &[Item<'static>] = &[
@@ -231,8 +445,15 @@ pub fn from_stem(stem: &str) -> Option<Date> {
 mod test {
     use chrono::NaiveDate;
 
-    use crate::parse::{parse_date, DateError, EntryError, TimeError};
-    use crate::{Date, Entry, Time, Topic};
+    use crate::ast::Document;
+    use crate::edit::Fix;
+    use proptest::prelude::*;
+
+    use crate::parse::{
+        from_stem, parse_bytes, parse_date, parse_month, DateError, EntryError, GranularityError,
+        MinutesError, TimeError,
+    };
+    use crate::{Date, Entry, FileMeta, Granularity, Minutes, Time, Topic};
 
     #[test]
     fn test_parse_date() {
@@ -264,6 +485,101 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_fix() {
+        let month = Date(NaiveDate::from_ymd_opt(2024, 4, 1).unwrap());
+        let document = Document::parse("* So. 20.04.\n9:00 AA A\n1000 AA\n", month);
+
+        let fix = EntryError::Date(DateError::UnexpectedDayOfWeek).fix(1, &document);
+        assert_eq!(
+            fix,
+            Some(Fix::new(
+                1,
+                "Change day of week So to Sa".to_owned(),
+                vec![(1, "* Sa. 20.04.".to_owned())]
+            ))
+        );
+        let fix = EntryError::Time.fix(2, &document);
+        assert_eq!(
+            fix,
+            Some(Fix::new(
+                2,
+                "Write 9:00 as 09:00".to_owned(),
+                vec![(2, "09:00 AA A".to_owned())]
+            ))
+        );
+        assert_eq!(EntryError::Time.fix(3, &document), None);
+        assert_eq!(EntryError::Time.fix(4, &document), None);
+    }
+
+    #[test]
+    fn test_parse_iso_date() {
+        let month = Date(NaiveDate::from_ymd_opt(2024, 4, 1).unwrap());
+
+        assert_eq!(
+            parse_date(" 2024-04-20", month, 0),
+            Ok(Date(NaiveDate::from_ymd_opt(2024, 4, 20).unwrap()))
+        );
+        assert_eq!(
+            parse_date(" 2024-04-20", month, 20),
+            Err(DateError::EntryOutOfOrder)
+        );
+
+        let tests = [
+            ("2024-04-31", DateError::Date),
+            ("2024-05-20", DateError::UnexpectedMonth),
+            ("2023-04-20", DateError::UnexpectedMonth),
+            ("2024-04-20x", DateError::Format),
+        ];
+
+        for (text, e) in tests {
+            assert_eq!(parse_date(text, month, 0), Err(e), "{text}");
+        }
+    }
+
+    #[test]
+    fn test_parse_meta() {
+        let month = Date(NaiveDate::from_ymd_opt(2024, 4, 1).unwrap());
+        let text = "#! name: J. Doe\n#! hours_per_week: 32:30\n#! half_days: 24.12., 30.04.\n# comment\n#! team: A\n* Sa. 20.04.\n#! not: meta\n* So. 21.04.\n";
+        let parsed = parse_month(text.as_bytes(), month).unwrap();
+        assert_eq!(
+            parsed.meta,
+            FileMeta {
+                name: Some("J. Doe".to_owned()),
+                hours_per_week: Some(Minutes::from(32 * 60 + 30)),
+                half_days: Some(vec![
+                    Date(NaiveDate::from_ymd_opt(2024, 12, 24).unwrap()),
+                    Date(NaiveDate::from_ymd_opt(2024, 4, 30).unwrap()),
+                ]),
+                values: vec![
+                    ("name".to_owned(), "J. Doe".to_owned()),
+                    ("hours_per_week".to_owned(), "32:30".to_owned()),
+                    ("half_days".to_owned(), "24.12., 30.04.".to_owned()),
+                    ("team".to_owned(), "A".to_owned()),
+                ],
+            }
+        );
+        assert_eq!(parsed.days[0].comments, vec![" comment"]);
+        assert_eq!(parsed.days[1].comments, vec!["! not: meta"]);
+
+        let Err(super::Error::Many(errors)) =
+            parse_month("#! hours_per_week: many\n".as_bytes(), month)
+        else {
+            panic!("expected an error");
+        };
+        assert_eq!(
+            errors.0[0].value,
+            EntryError::Meta("hours_per_week".to_owned())
+        );
+
+        let Err(super::Error::Many(errors)) =
+            parse_month("#! half_days: 24.12., 32.12.\n".as_bytes(), month)
+        else {
+            panic!("expected an error");
+        };
+        assert_eq!(errors.0[0].value, EntryError::Meta("half_days".to_owned()));
+    }
+
     #[test]
     fn test_parse_time() {
         assert_eq!("10:02".parse(), Ok(Time::new(10, 2).unwrap()));
@@ -277,6 +593,40 @@ mod test {
         assert_eq!("".parse::<Time>(), Err(TimeError));
     }
 
+    #[test]
+    fn test_parse_time_flexible() {
+        assert_eq!(Time::parse_flexible("9:00"), Ok(Time::new(9, 0).unwrap()));
+        assert_eq!(Time::parse_flexible("09:05"), Ok(Time::new(9, 5).unwrap()));
+        assert_eq!(Time::parse_flexible("24:00"), Err(TimeError));
+        assert_eq!(Time::parse_flexible("9:0"), Err(TimeError));
+        assert_eq!(Time::parse_flexible(":00"), Err(TimeError));
+        assert_eq!(Time::parse_flexible("900"), Err(TimeError));
+    }
+
+    #[test]
+    fn test_parse_minutes() {
+        assert_eq!("01:30".parse(), Ok(Minutes::from(90)));
+        assert_eq!("32:00".parse(), Ok(Minutes::from_hours(32)));
+        assert_eq!("90m".parse(), Ok(Minutes::from(90)));
+        assert_eq!("1h30m".parse(), Ok(Minutes::from(90)));
+        assert_eq!("2h".parse(), Ok(Minutes::from_hours(2)));
+        assert_eq!("1h90m".parse::<Minutes>(), Err(MinutesError));
+        assert_eq!("1:60".parse::<Minutes>(), Err(MinutesError));
+        assert_eq!("90".parse::<Minutes>(), Err(MinutesError));
+        assert_eq!("h".parse::<Minutes>(), Err(MinutesError));
+        assert_eq!("m".parse::<Minutes>(), Err(MinutesError));
+        assert_eq!("-5m".parse::<Minutes>(), Err(MinutesError));
+        assert_eq!("".parse::<Minutes>(), Err(MinutesError));
+    }
+
+    #[test]
+    fn test_parse_granularity() {
+        assert_eq!("15".parse(), Ok(Granularity(15)));
+        assert_eq!("1".parse(), Ok(Granularity(1)));
+        assert_eq!("4".parse::<Granularity>(), Err(GranularityError));
+        assert_eq!("".parse::<Granularity>(), Err(GranularityError));
+    }
+
     #[test]
     fn test_parse_topic() {
         assert_eq!(
@@ -335,4 +685,42 @@ mod test {
         assert_eq!("10".parse::<Entry>(), Err(EntryError::Time));
         assert_eq!("".parse::<Entry>(), Err(EntryError::MissingTime));
     }
+
+    #[test]
+    fn test_out_of_range() {
+        assert_eq!("24:00".parse::<Time>(), Err(TimeError));
+        assert_eq!("09:60".parse::<Time>(), Err(TimeError));
+        assert_eq!(
+            "99999999999999999999h".parse::<Minutes>(),
+            Err(MinutesError)
+        );
+        assert_eq!(
+            "999999999999999999:00".parse::<Minutes>(),
+            Err(MinutesError)
+        );
+        let month = Date(NaiveDate::from_ymd_opt(2024, 4, 1).unwrap());
+        assert!(parse_bytes(b"#! hours_per_week: 9999999999999999999\n", month).is_err());
+        assert!(parse_bytes(b"#! hours_per_week: 169\n", month).is_err());
+    }
+
+    fn text() -> impl Strategy<Value = Vec<u8>> {
+        prop_oneof![
+            proptest::collection::vec(any::<u8>(), 0..256),
+            "(([*] (Mo|Di|Sa|Xx)\\. [0-9]{1,2}\\.[0-9]{1,2}\\.|[*] [0-9]{1,5}-[0-9]{1,2}-[0-9]{1,2}|[0-9]{1,2}:[0-9]{1,3}( [A-Z][a-z]{0,4}( x)?)?|#! ?(hours_per_week|half_days|name): ?[0-9:hm,.]{0,10}|[*#!0-9:., a-zA-Z-]{0,24})\r?\n){0,16}"
+                .prop_map(String::into_bytes),
+        ]
+    }
+
+    proptest! {
+        #[test]
+        fn test_parse_bytes_never_panics(bytes in text(), year in -400..3000i32, month in 1..=12u32) {
+            let month = Date(NaiveDate::from_ymd_opt(year, month, 1).unwrap());
+            if let Ok(parsed) = parse_bytes(&bytes, month) {
+                let _ = crate::convert::Month::convert(parsed, &crate::convert::Options::default());
+            }
+            let document = Document::parse(&String::from_utf8_lossy(&bytes), month);
+            let _ = document.blocks();
+            let _ = from_stem(&String::from_utf8_lossy(&bytes));
+        }
+    }
 }
@@ -0,0 +1,126 @@
+use std::fmt::{Display, Formatter};
+
+use chrono::{Datelike, NaiveDate, Weekday};
+
+use crate::{Date, Minutes, Month, Time, Topic};
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Options {
+    /// Weekdays without entries by this time are reminded of.
+    pub start_by: Time,
+    /// Entries running for longer are reminded of.
+    pub max_running: Minutes,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            start_by: Time::new(10, 0).unwrap(),
+            max_running: Minutes::from_hours(4),
+        }
+    }
+}
+
+/// Something that was probably forgotten to log.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Reminder {
+    NoEntries(Date),
+    Running {
+        identifier: String,
+        start: Time,
+        running: Minutes,
+    },
+}
+
+impl Display for Reminder {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Reminder::NoEntries(date) => write!(f, "Nothing logged on {date} yet"),
+            Reminder::Running {
+                identifier,
+                start,
+                running,
+            } => write!(
+                f,
+                "{identifier} has been running since {start} for {}",
+                running.into_duration()
+            ),
+        }
+    }
+}
+
+/// The reminders for `today` at `now` with the file of its month, `None` if the file does not
+/// exist yet.
+#[must_use]
+pub fn reminders(
+    month: Option<&Month>,
+    today: NaiveDate,
+    now: Time,
+    options: &Options,
+) -> Vec<Reminder> {
+    let date = Date::new(today);
+    let entries = month
+        .and_then(|m| m.days.iter().find(|d| d.date.value == date))
+        .map(|d| d.entries.as_slice())
+        .unwrap_or_default();
+    let mut reminders = Vec::new();
+    let weekend = matches!(today.weekday(), Weekday::Sat | Weekday::Sun);
+    if entries.is_empty() && !weekend && now >= options.start_by {
+        reminders.push(Reminder::NoEntries(date));
+    }
+    if let Some(last) = entries.last() {
+        if let Topic::Project { identifier, .. } = &last.value.topic {
+            let start = last.value.time;
+            let running = now.elapsed(start).unwrap_or_default();
+            if running > options.max_running {
+                reminders.push(Reminder::Running {
+                    identifier: identifier.clone(),
+                    start,
+                    running,
+                });
+            }
+        }
+    }
+    reminders
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parse::parse_month;
+
+    use super::*;
+
+    fn check(text: &str, day: u32, now: (u8, u8)) -> Vec<String> {
+        let month = Date::new(NaiveDate::from_ymd_opt(2024, 4, 1).unwrap());
+        let month = parse_month(text.as_bytes(), month).unwrap();
+        let today = NaiveDate::from_ymd_opt(2024, 4, day).unwrap();
+        let now = Time::new(now.0, now.1).unwrap();
+        reminders(Some(&month), today, now, &Options::default())
+            .iter()
+            .map(ToString::to_string)
+            .collect()
+    }
+
+    #[test]
+    fn test_reminders() {
+        let text = "* Mo. 15.04.\n08:00 AA\n12:00\n* Di. 16.04.\n08:00 AA\n09:00 BB\n";
+        assert!(check(text, 15, (18, 0)).is_empty());
+        assert!(check(text, 17, (9, 59)).is_empty());
+        assert_eq!(
+            check(text, 17, (10, 0)),
+            ["Nothing logged on Mi. 17.04. yet"]
+        );
+        assert!(check(text, 20, (12, 0)).is_empty());
+        assert!(check(text, 16, (13, 0)).is_empty());
+        assert_eq!(
+            check(text, 16, (13, 1)),
+            ["BB has been running since 09:00 for 04:01"]
+        );
+        let today = NaiveDate::from_ymd_opt(2024, 4, 16).unwrap();
+        let now = Time::new(11, 0).unwrap();
+        assert_eq!(
+            reminders(None, today, now, &Options::default()),
+            [Reminder::NoEntries(Date::new(today))]
+        );
+    }
+}
@@ -1,10 +1,12 @@
-use std::fmt::{Display, Formatter, Result};
-use std::ops::Add;
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter, Result, Write};
+use std::path::{Path, PathBuf};
 
 use anstyle::{AnsiColor, Color, Style};
 
-use crate::convert::{AccumulatedTime, Day, Entry};
-use crate::{Minutes, Positioned};
+use crate::convert::{AccumulatedTime, Day, Entry, Month};
+use crate::i18n::{Language, Message};
+use crate::{Date, DstTransition, Minutes, Positioned, SignedMinutes};
 
 const DATE: Style = Style::new().fg_color(Some(Color::Ansi(AnsiColor::BrightYellow)));
 const PROJECT: Style = Style::new().fg_color(Some(Color::Ansi(AnsiColor::BrightGreen)));
@@ -12,91 +14,368 @@ const TIME: Style = Style::new().fg_color(Some(Color::Ansi(AnsiColor::BrightMage
 const POSITIVE: Style = Style::new().fg_color(Some(Color::Ansi(AnsiColor::Green)));
 const NEGATIVE: Style = Style::new().fg_color(Some(Color::Ansi(AnsiColor::Red)));
 const ADDITIONS: Style = Style::new().fg_color(Some(Color::Ansi(AnsiColor::BrightBlack)));
+const HEADING: Style = Style::new().bold();
 
-pub struct Output<'a>(pub &'a [Day]);
+#[derive(Debug, Default, Copy, Clone)]
+pub struct Options {
+    pub entry_durations: bool,
+    /// Show the month-to-date delta after each day.
+    pub running_balance: bool,
+    pub language: Language,
+}
+
+pub struct Output<'a> {
+    month: &'a Month,
+    options: Options,
+    file: Option<&'a Path>,
+}
 
 impl<'a> Output<'a> {
     #[must_use]
-    pub fn new(days: &'a [Day]) -> Self {
-        Self(days)
+    pub fn new(month: &'a Month) -> Self {
+        Self::with_options(month, Options::default())
+    }
+
+    #[must_use]
+    pub fn with_options(month: &'a Month, options: Options) -> Self {
+        Self {
+            month,
+            options,
+            file: None,
+        }
+    }
+
+    /// Links days and entries to their line in the absolute path `file`, for terminals that
+    /// support hyperlinks.
+    #[must_use]
+    pub fn with_file(self, file: &'a Path) -> Self {
+        Self {
+            file: Some(file),
+            ..self
+        }
     }
 }
 
 pub trait Format {
-    fn format(&self, f: &mut Formatter<'_>) -> Result;
+    fn format(&self, f: &mut Formatter<'_>, options: &Options) -> Result;
 }
 
-impl<'a> Display for Output<'a> {
+impl Display for Output<'_> {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result {
-        self.0.format(f)
+        format_month(f, self.month, self.options, self.file)
+    }
+}
+
+/// The `file://` URL of `line` in the absolute path `file`.
+fn file_url(file: &Path, line: usize) -> String {
+    let path = file.to_string_lossy().replace('\\', "/");
+    let mut url = String::from("file://");
+    if !path.starts_with('/') {
+        url.push('/');
+    }
+    for byte in path.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'/' | b'-' | b'_' | b'.' | b'~' | b':' => {
+                url.push(char::from(byte));
+            }
+            _ => write!(url, "%{byte:02X}").unwrap(),
+        }
+    }
+    write!(url, "#{line}").unwrap();
+    url
+}
+
+/// An OSC 8 hyperlink around the text written between [`Link::open`] and [`Link::close`],
+/// nothing without a file.
+#[derive(Copy, Clone)]
+struct Link<'a> {
+    file: Option<&'a Path>,
+    line: usize,
+}
+
+impl Link<'_> {
+    fn open(self, f: &mut Formatter<'_>) -> Result {
+        match self.file {
+            Some(file) => write!(f, "\x1b]8;;{}\x1b\\", file_url(file, self.line)),
+            None => Ok(()),
+        }
+    }
+
+    fn close(self, f: &mut Formatter<'_>) -> Result {
+        match self.file {
+            Some(_) => write!(f, "\x1b]8;;\x1b\\"),
+            None => Ok(()),
+        }
     }
 }
 
 fn output_time_delta(f: &mut Formatter<'_>, lhs: Minutes, rhs: Minutes) -> Result {
-    if lhs < rhs {
-        let delta = rhs - lhs;
-        let duration = delta.into_duration();
-        write!(
-            f,
-            "{}-{duration}{}",
-            NEGATIVE.render(),
-            NEGATIVE.render_reset()
-        )
+    output_signed(f, lhs - rhs)
+}
+
+fn output_signed(f: &mut Formatter<'_>, delta: SignedMinutes) -> Result {
+    let style = if delta.is_negative() {
+        NEGATIVE
     } else {
-        let delta = lhs - rhs;
-        let duration = delta.into_duration();
+        POSITIVE
+    };
+    write!(f, "{}{delta}{}", style.render(), style.render_reset())
+}
+
+/// Writes all days with entries and the file they link to, and returns their accumulated and
+/// expected time.
+fn format_days<'a>(
+    f: &mut Formatter<'_>,
+    days: impl IntoIterator<Item = (&'a Day, Minutes, Option<&'a Path>)>,
+    options: Options,
+) -> std::result::Result<(AccumulatedTime, Minutes), std::fmt::Error> {
+    let mut first = true;
+    let mut time = AccumulatedTime::default();
+    let mut expected_min_work = Minutes::default();
+    let mut balance = SignedMinutes::default();
+    for (day, expected_time, file) in days {
+        if day.entries.is_empty() {
+            continue;
+        }
+        time = time + day.times.clone();
+        expected_min_work += expected_time;
+        balance += day.times.billable_time() - expected_time;
+        if first {
+            first = false;
+        } else {
+            writeln!(f)?;
+        }
+        ReportDay {
+            day,
+            expected_time,
+            balance: options.running_balance.then_some(balance),
+            file,
+        }
+        .format(f, &options)?;
+    }
+    Ok((time, expected_min_work))
+}
+
+fn format_total(
+    f: &mut Formatter<'_>,
+    label: Message<'_>,
+    time: &AccumulatedTime,
+    expected: Minutes,
+    language: Language,
+) -> Result {
+    let minutes = time.billable_time();
+    let duration = minutes.into_duration();
+    writeln!(f)?;
+    write!(
+        f,
+        "{}{}: {duration} (",
+        ADDITIONS.render(),
+        language.tr(label)
+    )?;
+    output_time_delta(f, minutes, expected)?;
+    writeln!(f, "{}){}", ADDITIONS.render(), ADDITIONS.render_reset())?;
+    let under_hours = time.under_hours();
+    if under_hours > Minutes::default() {
         write!(
             f,
-            "{}+{duration}{}",
-            POSITIVE.render(),
-            POSITIVE.render_reset()
+            "{}{}: {} ({}: ",
+            ADDITIONS.render(),
+            language.tr(Message::UnderHours),
+            under_hours.into_duration(),
+            language.tr(Message::BalanceWithoutUnderHours)
+        )?;
+        output_time_delta(f, minutes + under_hours, expected)?;
+        writeln!(f, "{}){}", ADDITIONS.render(), ADDITIONS.render_reset())?;
+    }
+    Ok(())
+}
+
+fn format_month(
+    f: &mut Formatter<'_>,
+    month: &Month,
+    options: Options,
+    file: Option<&Path>,
+) -> Result {
+    let days = month.days.iter().map(|d| (d, month.expected_time(d), file));
+    let (time, expected_min_work) = format_days(f, days, options)?;
+    format_total(
+        f,
+        Message::Total,
+        &time,
+        expected_min_work,
+        options.language,
+    )
+}
+
+impl Format for &Month {
+    fn format(&self, f: &mut Formatter<'_>, options: &Options) -> Result {
+        format_month(f, self, *options, None)
+    }
+}
+
+/// The days of one ISO week, which can span two months.
+pub struct Week<'a> {
+    months: &'a [Month],
+    days: Vec<Date>,
+    options: Options,
+    files: &'a [PathBuf],
+}
+
+impl<'a> Week<'a> {
+    /// `None` if the week does not exist.
+    #[must_use]
+    pub fn new(months: &'a [Month], year: i32, week: u32, options: Options) -> Option<Self> {
+        Some(Self {
+            months,
+            days: Date::days_of_week(year, week)?,
+            options,
+            files: &[],
+        })
+    }
+
+    /// Links days and entries like [`Output::with_file`], `files` are the absolute paths of the
+    /// months in the same order.
+    #[must_use]
+    pub fn with_files(self, files: &'a [PathBuf]) -> Self {
+        Self { files, ..self }
+    }
+
+    fn month(&self, date: Date) -> Option<(&'a Month, Option<&'a Path>)> {
+        let index = self
+            .months
+            .iter()
+            .position(|m| (m.month.year(), m.month.month()) == (date.year(), date.month()))?;
+        Some((
+            &self.months[index],
+            self.files.get(index).map(PathBuf::as_path),
+        ))
+    }
+}
+
+impl Display for Week<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        let days = self.days.iter().filter_map(|&date| {
+            let (month, file) = self.month(date)?;
+            let day = month.days.iter().find(|d| d.date.value == date)?;
+            Some((day, month.expected_time(day), file))
+        });
+        let (time, _) = format_days(f, days, self.options)?;
+        let expected = self
+            .days
+            .iter()
+            .filter_map(|&date| Some(self.month(date)?.0.expected_time_on(date)))
+            .sum();
+        let language = self.options.language;
+        format_total(f, Message::Total, &time, expected, language)?;
+        writeln!(
+            f,
+            "{}{}: {}{}",
+            ADDITIONS.render(),
+            language.tr(Message::Expected),
+            expected.into_duration(),
+            ADDITIONS.render_reset()
         )
     }
 }
 
-impl<'a> Format for &'a [Day] {
-    fn format(&self, f: &mut Formatter<'_>) -> Result {
-        let mut first = true;
-        let mut expected_min_work = Minutes::default();
-        for day in *self {
-            if day.entries.is_empty() {
-                continue;
-            }
-            expected_min_work += day.expected_time();
-            if first {
-                first = false;
-            } else {
+/// Several months each with a subtotal, followed by their combined total.
+pub struct Combined<'a> {
+    months: Vec<(String, &'a Month)>,
+    options: Options,
+    files: &'a [PathBuf],
+}
+
+impl<'a> Combined<'a> {
+    /// `months` are labeled, e.g. with the paths of their files.
+    #[must_use]
+    pub fn new(months: Vec<(String, &'a Month)>, options: Options) -> Self {
+        Self {
+            months,
+            options,
+            files: &[],
+        }
+    }
+
+    /// Links days and entries like [`Output::with_file`], `files` are the absolute paths of the
+    /// months in the same order.
+    #[must_use]
+    pub fn with_files(self, files: &'a [PathBuf]) -> Self {
+        Self { files, ..self }
+    }
+}
+
+impl Display for Combined<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        let mut time = AccumulatedTime::default();
+        let mut expected = Minutes::default();
+        for (index, (label, month)) in self.months.iter().enumerate() {
+            if index > 0 {
                 writeln!(f)?;
             }
-            day.format(f)?;
+            writeln!(f, "{}{label}{}", HEADING.render(), HEADING.render_reset())?;
+            let file = self.files.get(index).map(PathBuf::as_path);
+            let days = month.days.iter().map(|d| (d, month.expected_time(d), file));
+            let (month_time, month_expected) = format_days(f, days, self.options)?;
+            format_total(
+                f,
+                Message::Subtotal,
+                &month_time,
+                month_expected,
+                self.options.language,
+            )?;
+            time = time + month_time;
+            expected += month_expected;
         }
+        let label = Message::TotalOfFiles(self.months.len());
+        format_total(f, label, &time, expected, self.options.language)
+    }
+}
 
-        let time = self
-            .iter()
-            .map(|d| d.times.clone())
-            .fold(AccumulatedTime::default(), AccumulatedTime::add);
-        let minutes = time.billable_time();
-        let duration = minutes.into_duration();
+fn format_entries(
+    f: &mut Formatter<'_>,
+    entries: &[Positioned<Entry>],
+    options: Options,
+    file: Option<&Path>,
+) -> Result {
+    let total: Minutes = entries.iter().map(|e| e.value.duration).sum();
+    for entry in entries {
+        let link = Link {
+            file,
+            line: entry.line,
+        };
+        link.open(f)?;
+        entry.value.format(f, &options)?;
+        link.close(f)?;
+        if options.entry_durations {
+            write!(
+                f,
+                " {}({}",
+                ADDITIONS.render(),
+                entry.value.duration.into_duration()
+            )?;
+            if let Some(share) = share(entry.value.duration, total) {
+                write!(f, ", {share}%")?;
+            }
+            write!(f, "){}", ADDITIONS.render_reset())?;
+        }
         writeln!(f)?;
-        write!(f, "{}Total: {duration} (", ADDITIONS.render())?;
-        output_time_delta(f, minutes, expected_min_work)?;
-        writeln!(f, "{}){}", ADDITIONS.render(), ADDITIONS.render_reset())?;
-
-        Ok(())
     }
+    Ok(())
 }
 
 impl Format for [Positioned<Entry>] {
-    fn format(&self, f: &mut Formatter<'_>) -> Result {
-        for entry in self {
-            entry.value.format(f)?;
-        }
-        Ok(())
+    fn format(&self, f: &mut Formatter<'_>, options: &Options) -> Result {
+        format_entries(f, self, *options, None)
     }
 }
 
+fn share(part: Minutes, total: Minutes) -> Option<usize> {
+    let total = total.into_inner();
+    (part.into_inner() * 100 + total / 2).checked_div(total)
+}
+
 impl Format for Entry {
-    fn format(&self, f: &mut Formatter<'_>) -> Result {
+    fn format(&self, f: &mut Formatter<'_>, _: &Options) -> Result {
         write!(
             f,
             "{}{} - {}{} {}{}{}",
@@ -111,49 +390,288 @@ impl Format for Entry {
         if let Some(comment) = &self.comment {
             write!(f, " {comment}")?;
         }
-        writeln!(f)?;
         Ok(())
     }
 }
 
-impl<'a> Format for &'a Day {
-    fn format(&self, f: &mut Formatter<'_>) -> Result {
+struct ReportDay<'a> {
+    day: &'a Day,
+    expected_time: Minutes,
+    balance: Option<SignedMinutes>,
+    file: Option<&'a Path>,
+}
+
+impl Format for ReportDay<'_> {
+    fn format(&self, f: &mut Formatter<'_>, options: &Options) -> Result {
+        let link = Link {
+            file: self.file,
+            line: self.day.date.line,
+        };
+        link.open(f)?;
         write!(
             f,
             "{}* {}{}",
             DATE.render(),
-            self.date.value,
+            self.day.date.value,
             DATE.render_reset()
         )?;
-        let minutes = self.times.billable_time();
-        if minutes == Minutes::default() {
-            writeln!(f)?;
-        } else {
+        link.close(f)?;
+        let minutes = self.day.times.billable_time();
+        if minutes != Minutes::default() {
             let duration = minutes.into_duration();
             write!(f, "{} -> {duration}", ADDITIONS.render())?;
-            let expected_time = self.expected_time();
+            let expected_time = self.expected_time;
             if minutes == expected_time {
-                writeln!(f, "{}", ADDITIONS.render_reset())?;
+                write!(f, "{}", ADDITIONS.render_reset())?;
             } else {
                 write!(f, " (")?;
                 output_time_delta(f, minutes, expected_time)?;
-                writeln!(f, "{}){}", ADDITIONS.render(), ADDITIONS.render_reset())?;
+                write!(f, "{}){}", ADDITIONS.render(), ADDITIONS.render_reset())?;
             }
         }
+        if let Some(balance) = self.balance {
+            write!(f, "{} Σ ", ADDITIONS.render())?;
+            output_signed(f, balance)?;
+        }
+        writeln!(f)?;
 
-        self.entries.as_slice().format(f)?;
+        format_entries(f, &self.day.entries, *options, self.file)?;
 
-        if self.times.travel_time() > Minutes::default() {
+        if self.day.times.travel_time() > Minutes::default() {
             writeln!(
                 f,
-                "{}Travel time: {} ({} billable){}",
+                "{}{}: {} ({} {}){}",
                 ADDITIONS.render(),
-                self.times.travel_time().into_duration(),
-                self.times.billable_travel_time().into_duration(),
+                options.language.tr(Message::TravelTime),
+                self.day.times.travel_time().into_duration(),
+                self.day.times.billable_travel_time().into_duration(),
+                options.language.tr(Message::Billable),
                 ADDITIONS.render_reset(),
             )?;
         }
 
+        if self.day.times.under_hours() > Minutes::default() {
+            writeln!(
+                f,
+                "{}{}: {}{}",
+                ADDITIONS.render(),
+                options.language.tr(Message::UnderHours),
+                self.day.times.under_hours().into_duration(),
+                ADDITIONS.render_reset(),
+            )?;
+        }
+
+        if let Some(transition) = self.day.date.value.dst_transition() {
+            let note = match transition {
+                DstTransition::Forward => Message::DstStarts,
+                DstTransition::Backward => Message::DstEnds,
+            };
+            writeln!(
+                f,
+                "{}{}{}",
+                ADDITIONS.render(),
+                options.language.tr(note),
+                ADDITIONS.render_reset()
+            )?;
+        }
+
         Ok(())
     }
 }
+
+pub struct Detail<'a> {
+    days: &'a [Day],
+    identifier: &'a str,
+    language: Language,
+}
+
+impl<'a> Detail<'a> {
+    #[must_use]
+    pub fn new(days: &'a [Day], identifier: &'a str, language: Language) -> Self {
+        Self {
+            days,
+            identifier,
+            language,
+        }
+    }
+}
+
+fn normalize_comment(comment: &str) -> String {
+    comment
+        .split_whitespace()
+        .map(str::to_lowercase)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Sums up the durations of all entries with `identifier`, grouped by their normalized comment.
+/// Each group is labeled with the first spelling of the comment, largest groups first.
+#[must_use]
+pub fn comment_totals<'a>(days: &'a [Day], identifier: &str) -> Vec<(Option<&'a str>, Minutes)> {
+    let mut groups: BTreeMap<Option<String>, (Option<&'a str>, Minutes)> = BTreeMap::new();
+    for entry in days
+        .iter()
+        .flat_map(|d| &d.entries)
+        .map(|e| &e.value)
+        .filter(|e| e.identifier.as_str() == identifier)
+    {
+        let comment = entry.comment.as_deref().map(str::trim);
+        let (_, total) = groups
+            .entry(comment.map(normalize_comment))
+            .or_insert((comment, Minutes::default()));
+        *total += entry.duration;
+    }
+    let mut totals: Vec<_> = groups.into_values().collect();
+    totals.sort_by(|(_, a), (_, b)| b.cmp(a));
+    totals
+}
+
+impl Display for Detail<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        writeln!(
+            f,
+            "{}{}{}",
+            PROJECT.render(),
+            self.identifier,
+            PROJECT.render_reset()
+        )?;
+        let totals = comment_totals(self.days, self.identifier);
+        for (comment, minutes) in &totals {
+            writeln!(
+                f,
+                "{}{}{} {}",
+                TIME.render(),
+                minutes.into_duration(),
+                TIME.render_reset(),
+                comment.unwrap_or("-"),
+            )?;
+        }
+        let total: Minutes = totals.iter().map(|(_, m)| *m).sum();
+        writeln!(
+            f,
+            "{}{}: {}{}",
+            ADDITIONS.render(),
+            self.language.tr(Message::Total),
+            total.into_duration(),
+            ADDITIONS.render_reset()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fixture::{days, month};
+
+    use super::*;
+
+    #[test]
+    fn test_comment_totals() {
+        let days = days(
+            4,
+            r"
+            * Mo. 15.04.
+            09:00 AA Ops Daily
+            09:15 AA Ticket 1
+            12:00
+            12:30 AA ops  daily
+            13:00 BB Ticket 1
+            14:00 AA
+            15:00
+            ",
+        );
+        assert_eq!(
+            comment_totals(&days, "AA"),
+            vec![
+                (Some("Ticket 1"), Minutes::from(165)),
+                (None, Minutes::from(60)),
+                (Some("Ops Daily"), Minutes::from(45)),
+            ]
+        );
+        assert_eq!(comment_totals(&days, "CC"), vec![]);
+    }
+
+    #[test]
+    fn test_running_balance() {
+        let month = month(
+            4,
+            r"
+            * Mo. 15.04.
+            09:00 AA
+            18:00
+            * Di. 16.04.
+            09:00 AA
+            16:00
+            ",
+        );
+        let options = Options {
+            running_balance: true,
+            ..Options::default()
+        };
+        let output = Output::with_options(&month, options).to_string();
+        let balances: Vec<_> = output
+            .lines()
+            .filter_map(|l| l.split_once("Σ "))
+            .map(|(_, balance)| &balance[POSITIVE.render().to_string().len()..][..6])
+            .collect();
+        assert_eq!(balances, vec!["+01:00", "+00:00"]);
+        assert!(!Output::new(&month).to_string().contains('Σ'));
+    }
+
+    #[test]
+    fn test_links() {
+        let month = month(4, "* Mo. 15.04.\n09:00 AA\n17:00\n");
+        assert!(!Output::new(&month).to_string().contains("\x1b]8"));
+        let file = Path::new("/home/me/time sheets/2024-04.tsh");
+        let output = Output::new(&month).with_file(file).to_string();
+        let url = "file:///home/me/time%20sheets/2024-04.tsh";
+        assert!(output.starts_with(&format!("\x1b]8;;{url}#1\x1b\\")));
+        assert!(output.contains(&format!("\x1b]8;;{url}#2\x1b\\")));
+        assert_eq!(output.matches("\x1b]8;;\x1b\\").count(), 2);
+    }
+
+    #[test]
+    fn test_week() {
+        let months = [
+            month(
+                4,
+                "* Mo. 22.04.\n09:00 AA\n17:00\n* Di. 30.04.\n09:00 AA\n17:00\n",
+            ),
+            month(5, "* Mi. 01.05.\n09:00 BB\n12:00\n"),
+        ];
+        let output = Week::new(&months, 2024, 18, Options::default())
+            .unwrap()
+            .to_string();
+        assert!(!output.contains("22.04."));
+        assert!(output.contains("30.04.") && output.contains(" 1.05."));
+        assert!(output.contains("Total: 11:00"));
+        assert!(output.contains("Expected: 40:00"));
+        let options = Options {
+            language: Language::De,
+            ..Options::default()
+        };
+        let output = Week::new(&months, 2024, 18, options).unwrap().to_string();
+        assert!(output.contains("Summe: 11:00"));
+        assert!(output.contains("Soll: 40:00"));
+        assert!(Week::new(&months, 2024, 54, Options::default()).is_none());
+    }
+
+    #[test]
+    fn test_combined() {
+        let april = month(4, "* Mo. 22.04.\n09:00 AA\n17:00\n");
+        let may = month(5, "* Do. 02.05.\n09:00 BB\n12:00\n");
+        let output = Combined::new(
+            vec![
+                ("2024-04.tsh".to_owned(), &april),
+                ("2024-05.tsh".to_owned(), &may),
+            ],
+            Options::default(),
+        )
+        .to_string();
+        let april = output.find("2024-04.tsh").unwrap();
+        let may = output.find("2024-05.tsh").unwrap();
+        assert!(april < may);
+        assert!(output[april..may].contains("Subtotal: 08:00"));
+        assert!(output[may..].contains("Subtotal: 03:00"));
+        assert!(output.contains("Total of 2 files: 11:00"));
+    }
+}
@@ -0,0 +1,59 @@
+use regex::Regex;
+
+use crate::{Date, Day, Entry, Positioned, Topic};
+
+/// An entry whose identifier or comment matches a search pattern.
+pub struct Match<'a> {
+    pub date: Date,
+    pub entry: &'a Positioned<Entry>,
+}
+
+fn is_match(topic: &Topic, regex: &Regex) -> bool {
+    match topic {
+        Topic::Break => false,
+        Topic::Project {
+            identifier,
+            comment,
+        } => regex.is_match(identifier) || comment.as_deref().is_some_and(|c| regex.is_match(c)),
+    }
+}
+
+/// All entries of `days` with an identifier or comment matching `regex`, in file order.
+pub fn search<'a>(days: &'a [Day], regex: &'a Regex) -> impl Iterator<Item = Match<'a>> + 'a {
+    days.iter().flat_map(move |day| {
+        day.entries
+            .iter()
+            .filter(|e| is_match(&e.value.topic, regex))
+            .map(|entry| Match {
+                date: day.date.value,
+                entry,
+            })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use crate::parse::parse;
+
+    use super::*;
+
+    #[test]
+    fn test_search() {
+        let days = parse(
+            "* Mo. 15.04.\n09:00 AA TICKET-1\n10:00 TICKET-2 review\n11:00\n* Di. 16.04.\n09:00 BB ticket-1\n10:00\n"
+                .as_bytes(),
+            Date::new(NaiveDate::from_ymd_opt(2024, 4, 1).unwrap()),
+        )
+        .unwrap();
+        let regex = Regex::new("TICKET-1").unwrap();
+        let lines: Vec<_> = search(&days, &regex).map(|m| m.entry.line).collect();
+        assert_eq!(lines, vec![2]);
+        let regex = Regex::new("(?i)ticket").unwrap();
+        let found: Vec<_> = search(&days, &regex)
+            .map(|m| (m.date.day(), m.entry.line))
+            .collect();
+        assert_eq!(found, vec![(15, 2), (15, 3), (16, 6)]);
+    }
+}
@@ -0,0 +1,237 @@
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter, Result};
+
+use chrono::Weekday;
+
+use crate::convert::{AccumulatedTime, Identifier, Month};
+use crate::{weekday_to_str, Date, Minutes, SignedMinutes};
+
+pub struct MonthBalance {
+    pub month: Date,
+    /// Billable time minus the expected time of the month.
+    pub balance: SignedMinutes,
+    /// Sum of the balances up to and including this month.
+    pub cumulative: SignedMinutes,
+}
+
+/// Statistics across several months.
+pub struct Stats {
+    pub times: AccumulatedTime,
+    /// Billable time and number of worked days per weekday, starting on Monday.
+    weekdays: [(Minutes, usize); 7],
+    pub months: Vec<MonthBalance>,
+}
+
+impl Stats {
+    #[must_use]
+    pub fn new<'a>(months: impl IntoIterator<Item = &'a Month>) -> Self {
+        let mut times = AccumulatedTime::default();
+        let mut weekdays = [(Minutes::default(), 0); 7];
+        let mut balances = Vec::new();
+        let mut cumulative = SignedMinutes::default();
+        for month in months {
+            let mut balance = SignedMinutes::default();
+            for day in month.days.iter().filter(|d| !d.entries.is_empty()) {
+                let billable = day.times.billable_time();
+                let weekday =
+                    &mut weekdays[day.date.value.weekday().num_days_from_monday() as usize];
+                weekday.0 += billable;
+                weekday.1 += 1;
+                balance += billable - month.expected_time(day);
+            }
+            times = times + month.times();
+            cumulative += balance;
+            balances.push(MonthBalance {
+                month: month.month,
+                balance,
+                cumulative,
+            });
+        }
+        Self {
+            times,
+            weekdays,
+            months: balances,
+        }
+    }
+
+    #[must_use]
+    pub fn by_project(&self) -> &BTreeMap<Identifier, Minutes> {
+        self.times.by_project()
+    }
+
+    /// Average billable time of the worked days on `weekday`.
+    #[must_use]
+    pub fn weekday_average(&self, weekday: Weekday) -> Option<Minutes> {
+        let (total, days) = self.weekdays[weekday.num_days_from_monday() as usize];
+        total.into_inner().checked_div(days).map(Minutes::from)
+    }
+}
+
+/// How an identifier is used across several months.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ProjectUsage {
+    pub identifier: Identifier,
+    pub first: Date,
+    pub last: Date,
+    pub entries: usize,
+    pub total: Minutes,
+}
+
+/// Every identifier of `months` ordered by identifier, including unbillable ones.
+#[must_use]
+pub fn projects<'a>(months: impl IntoIterator<Item = &'a Month>) -> Vec<ProjectUsage> {
+    let mut projects: BTreeMap<&Identifier, ProjectUsage> = BTreeMap::new();
+    for day in months.into_iter().flat_map(|m| &m.days) {
+        let date = day.date.value;
+        for entry in &day.entries {
+            let entry = &entry.value;
+            let usage = projects
+                .entry(&entry.identifier)
+                .or_insert_with(|| ProjectUsage {
+                    identifier: entry.identifier.clone(),
+                    first: date,
+                    last: date,
+                    entries: 0,
+                    total: Minutes::default(),
+                });
+            usage.first = usage.first.min(date);
+            usage.last = usage.last.max(date);
+            usage.entries += 1;
+            usage.total += entry.duration;
+        }
+    }
+    projects.into_values().collect()
+}
+
+impl Display for Stats {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        writeln!(f, "Projects:")?;
+        for (identifier, minutes) in self.by_project() {
+            writeln!(f, "  {identifier:<12} {}", minutes.into_duration())?;
+        }
+        writeln!(f, "Weekday averages:")?;
+        let mut weekday = Weekday::Mon;
+        for _ in 0..7 {
+            if let Some(average) = self.weekday_average(weekday) {
+                writeln!(
+                    f,
+                    "  {:<12} {}",
+                    weekday_to_str(weekday),
+                    average.into_duration()
+                )?;
+            }
+            weekday = weekday.succ();
+        }
+        writeln!(
+            f,
+            "Travel time: {} ({} billable)",
+            self.times.travel_time().into_duration(),
+            self.times.billable_travel_time().into_duration()
+        )?;
+        writeln!(f, "Overtime:")?;
+        for month in &self.months {
+            writeln!(
+                f,
+                "  {}-{:0>2}      {} (Σ {})",
+                month.month.year(),
+                month.month.month(),
+                month.balance,
+                month.cumulative
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fixture::month;
+
+    use super::*;
+
+    #[test]
+    fn test_stats() {
+        let months = [
+            month(
+                4,
+                "* Mo. 15.04.\n09:00 AA\n17:00\n* Mo. 22.04.\n09:00 BB\n18:00\n",
+            ),
+            month(5, "* Di. 14.05.\n09:00 AA\n16:00\n"),
+        ];
+        let stats = Stats::new(&months);
+        assert_eq!(
+            stats
+                .by_project()
+                .iter()
+                .map(|(i, m)| (i.as_str(), m.into_inner()))
+                .collect::<Vec<_>>(),
+            vec![("AA", 15 * 60), ("BB", 9 * 60)]
+        );
+        assert_eq!(
+            stats.weekday_average(Weekday::Mon),
+            Some(Minutes::from(8 * 60 + 30))
+        );
+        assert_eq!(stats.weekday_average(Weekday::Wed), None);
+        let balances: Vec<_> = stats
+            .months
+            .iter()
+            .map(|m| (m.balance.to_string(), m.cumulative.to_string()))
+            .collect();
+        assert_eq!(
+            balances,
+            vec![
+                ("+01:00".to_owned(), "+01:00".to_owned()),
+                ("-01:00".to_owned(), "+00:00".to_owned())
+            ]
+        );
+    }
+
+    #[test]
+    fn test_projects() {
+        let months = [
+            month(5, "* Di. 14.05.\n09:00 AA\n16:00\n"),
+            month(
+                4,
+                "* Mo. 15.04.\n09:00 AA\n12:00 Urlaub\n17:00\n* Mo. 22.04.\n09:00 BB\n18:00\n",
+            ),
+        ];
+        let projects: Vec<_> = projects(&months)
+            .into_iter()
+            .map(|p| {
+                (
+                    p.identifier.to_string(),
+                    p.first.to_string(),
+                    p.last.to_string(),
+                    p.entries,
+                    p.total.into_inner(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            projects,
+            vec![
+                (
+                    "AA".to_owned(),
+                    "Mo. 15.04.".to_owned(),
+                    "Di. 14.05.".to_owned(),
+                    2,
+                    10 * 60
+                ),
+                (
+                    "BB".to_owned(),
+                    "Mo. 22.04.".to_owned(),
+                    "Mo. 22.04.".to_owned(),
+                    1,
+                    9 * 60
+                ),
+                (
+                    "Urlaub".to_owned(),
+                    "Mo. 15.04.".to_owned(),
+                    "Mo. 15.04.".to_owned(),
+                    1,
+                    5 * 60
+                ),
+            ]
+        );
+    }
+}
@@ -0,0 +1,168 @@
+//! [proptest] strategies for fuzzing timesheets, enabled by the `proptest` feature.
+
+use std::fmt::Write;
+
+use chrono::{Datelike, NaiveDate};
+use proptest::collection::vec;
+use proptest::option;
+use proptest::prelude::*;
+use proptest::sample::subsequence;
+
+use crate::{Date, Entry, Time, Topic};
+
+pub fn time() -> impl Strategy<Value = Time> {
+    (0..24u8, 0..60u8).prop_map(|(hour, minute)| Time { hour, minute })
+}
+
+/// Identifiers like `AA`, `TNG` or `ReiseFa`, the defaults of the classifier included.
+pub fn identifier() -> impl Strategy<Value = String> {
+    prop_oneof![
+        Just("TNG".to_owned()),
+        Just("TNGFa".to_owned()),
+        Just("Ustd".to_owned()),
+        "[A-Z][A-Za-z]{0,7}",
+    ]
+}
+
+pub fn comment() -> impl Strategy<Value = String> {
+    "[a-zA-Z0-9äöü,.-]{1,8}( [a-zA-Z0-9äöü,.-]{1,8}){0,3}"
+}
+
+pub fn topic() -> impl Strategy<Value = Topic> {
+    prop_oneof![
+        1 => Just(Topic::Break),
+        4 => (identifier(), option::of(comment()))
+            .prop_map(|(identifier, comment)| Topic::Project { identifier, comment }),
+    ]
+}
+
+pub fn entry() -> impl Strategy<Value = Entry> {
+    (time(), topic()).prop_map(|(time, topic)| Entry { time, topic })
+}
+
+/// The entries of a valid day, in order and ending with a break.
+pub fn day_entries() -> impl Strategy<Value = Vec<Entry>> {
+    (0..600u16, vec((1..90u16, topic()), 1..8)).prop_map(|(start, entries)| {
+        let time = |minutes: u16| {
+            Time::new(
+                u8::try_from(minutes / 60).unwrap(),
+                u8::try_from(minutes % 60).unwrap(),
+            )
+            .unwrap()
+        };
+        let mut minutes = start;
+        let mut day: Vec<_> = entries
+            .into_iter()
+            .map(|(gap, topic)| {
+                let entry = Entry {
+                    time: time(minutes),
+                    topic,
+                };
+                minutes += gap;
+                entry
+            })
+            .collect();
+        day.push(Entry {
+            time: time(minutes),
+            topic: Topic::Break,
+        });
+        day
+    })
+}
+
+/// The text of a valid file of `month`, with entries on some of its days.
+pub fn month_text(month: Date) -> impl Strategy<Value = String> {
+    let first = month.into_inner();
+    let dates: Vec<NaiveDate> = first
+        .iter_days()
+        .take_while(|d| d.month() == first.month())
+        .collect();
+    let len = dates.len();
+    subsequence(dates, 0..=len)
+        .prop_flat_map(|dates| {
+            let days = dates.len();
+            (Just(dates), vec(day_entries(), days))
+        })
+        .prop_map(|(dates, days)| {
+            let mut text = String::new();
+            for (date, entries) in dates.into_iter().zip(days) {
+                writeln!(text, "* {}", Date::new(date)).unwrap();
+                for entry in entries {
+                    writeln!(text, "{entry}").unwrap();
+                }
+                text.push('\n');
+            }
+            text
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::convert::{Day, Month, Options};
+    use crate::parse::parse_month;
+    use crate::{Granularity, Minutes};
+
+    use super::*;
+
+    fn april() -> Date {
+        Date::new(NaiveDate::from_ymd_opt(2024, 4, 1).unwrap())
+    }
+
+    fn options() -> Options {
+        Options {
+            granularity: Granularity::new(1).unwrap(),
+            ..Options::default()
+        }
+    }
+
+    fn format(month: &crate::Month) -> String {
+        let mut text = String::new();
+        for day in &month.days {
+            writeln!(text, "* {}", day.date.value).unwrap();
+            for entry in &day.entries {
+                writeln!(text, "{}", entry.value).unwrap();
+            }
+        }
+        text
+    }
+
+    proptest! {
+        #[test]
+        fn test_parse_format_roundtrip(text in month_text(april())) {
+            let month = parse_month(text.as_bytes(), april()).unwrap();
+            let again = parse_month(format(&month).as_bytes(), april()).unwrap();
+            prop_assert_eq!(month.days.len(), again.days.len());
+            for (day, other) in month.days.iter().zip(&again.days) {
+                prop_assert_eq!(day.date.value, other.date.value);
+                let entries = |d: &crate::Day| d.entries.iter().map(|e| e.value.clone()).collect::<Vec<_>>();
+                prop_assert_eq!(entries(day), entries(other));
+            }
+        }
+
+        #[test]
+        fn test_valid_months_convert(text in month_text(april())) {
+            let month = parse_month(text.as_bytes(), april()).unwrap();
+            prop_assert!(Month::convert(month, &options()).is_ok());
+        }
+
+        #[test]
+        fn test_travel_never_exceeds_the_day(entries in day_entries()) {
+            let first = entries.first().unwrap().time;
+            let last = entries.last().unwrap().time;
+            let span = last.elapsed(first).unwrap();
+            let day = crate::Day {
+                comments: Vec::new(),
+                date: crate::Positioned::new(1, april()),
+                entries: entries
+                    .into_iter()
+                    .enumerate()
+                    .map(|(line, e)| crate::Positioned::new(line + 2, e))
+                    .collect(),
+            };
+            let day = Day::convert(day, &options()).unwrap();
+            let projects: Minutes = day.by_project().values().copied().sum();
+            prop_assert!(projects <= span);
+            prop_assert!(day.times.billable_time() <= span);
+        }
+    }
+}
@@ -0,0 +1,117 @@
+use std::fmt::Write;
+
+use chrono::{Datelike, NaiveDate, Weekday};
+
+use crate::{Date, Time};
+
+const IDENTIFIERS: [&str; 6] = ["AA", "AANB", "BB", "CC", "TNG", "Ustd"];
+const COMMENTS: [&str; 4] = [
+    "Daily",
+    "Review",
+    "Implementation of the importer",
+    "Planning",
+];
+
+/// Generates synthetic but valid timesheet text, the same seed always gives the same text.
+#[derive(Debug, Clone)]
+pub struct Generator {
+    state: u64,
+}
+
+impl Generator {
+    #[must_use]
+    pub fn new(seed: u64) -> Self {
+        Self {
+            state: seed.wrapping_mul(0x9E37_79B9_7F4A_7C15) | 1,
+        }
+    }
+
+    /// A number in `0..n`, using xorshift.
+    fn below(&mut self, n: usize) -> usize {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 7;
+        self.state ^= self.state << 17;
+        usize::try_from(self.state % n as u64).unwrap()
+    }
+
+    fn day(&mut self, text: &mut String) {
+        let quarters = |q: usize| {
+            Time::new(
+                u8::try_from(q / 4).unwrap(),
+                15 * u8::try_from(q % 4).unwrap(),
+            )
+            .unwrap()
+        };
+        // Starts between 07:00 and 09:45 and ends before 21:00.
+        let mut quarter = 28 + self.below(12);
+        for i in 0..3 + self.below(6) {
+            if i > 0 && self.below(4) == 0 {
+                writeln!(text, "{}", quarters(quarter)).unwrap();
+                quarter += 1 + self.below(4);
+            }
+            let identifier = IDENTIFIERS[self.below(IDENTIFIERS.len())];
+            write!(text, "{} {identifier}", quarters(quarter)).unwrap();
+            if self.below(2) == 0 {
+                write!(text, " {}", COMMENTS[self.below(COMMENTS.len())]).unwrap();
+            }
+            text.push('\n');
+            quarter += 1 + self.below(8);
+            if quarter >= 72 {
+                break;
+            }
+        }
+        writeln!(text, "{}", quarters(quarter)).unwrap();
+    }
+
+    /// A month file with a working day on every weekday.
+    #[must_use]
+    pub fn month(&mut self, month: Date) -> String {
+        let mut text = String::from("#! hours_per_week: 40\n");
+        let first = month.into_inner();
+        for date in first.iter_days().take_while(|d| d.month() == first.month()) {
+            if matches!(date.weekday(), Weekday::Sat | Weekday::Sun) {
+                continue;
+            }
+            text.push('\n');
+            if self.below(8) == 0 {
+                text.push_str("# Comment\n");
+            }
+            writeln!(text, "* {}", Date::new(date)).unwrap();
+            self.day(&mut text);
+        }
+        text
+    }
+
+    /// The month files of `year` with the first days of their months.
+    #[must_use]
+    pub fn year(&mut self, year: i32) -> Vec<(Date, String)> {
+        (1..=12)
+            .map(|month| {
+                let month = Date::new(NaiveDate::from_ymd_opt(year, month, 1).unwrap());
+                (month, self.month(month))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::convert::{Month, Options};
+    use crate::parse::parse_month;
+
+    use super::*;
+
+    #[test]
+    fn test_generator() {
+        let months = Generator::new(7).year(2024);
+        assert_eq!(months.len(), 12);
+        for (month, text) in &months {
+            let parsed = parse_month(text.as_bytes(), *month).unwrap();
+            let converted = Month::convert(parsed, &Options::default()).unwrap();
+            assert!(converted.days.len() >= 20);
+            assert!(converted.days.iter().all(|d| !d.entries.is_empty()));
+        }
+        assert_eq!(Generator::new(7).year(2024), months);
+        assert_ne!(Generator::new(8).year(2024), months);
+    }
+}
@@ -0,0 +1,251 @@
+use thiserror::Error;
+
+use crate::ast::{Document, Node};
+use crate::edit::with_time;
+use crate::{weekday_to_str, Date, Day, Entry, SignedMinutes, Topic};
+
+#[derive(Debug, Error, Eq, PartialEq)]
+pub enum ShiftError {
+    #[error("There is no day {0}")]
+    MissingDay(Date),
+    #[error("Time in line {0} would be moved to another day")]
+    OutOfDay(usize),
+}
+
+fn continues(entry: &Entry, previous: &Entry) -> bool {
+    matches!(entry.topic, Topic::Project { .. }) && entry.topic == previous.topic
+}
+
+/// Joins consecutive entries with the same identifier and comment into a single span.
+/// Returns the number of removed entries.
+pub fn merge_adjacent(day: &mut Day) -> usize {
+    let len = day.entries.len();
+    day.entries
+        .dedup_by(|entry, previous| continues(&entry.value, &previous.value));
+    len - day.entries.len()
+}
+
+/// Like [`merge_adjacent`] for every day of `document`, leaving all other lines untouched.
+pub fn merge_adjacent_in(document: &mut Document) -> usize {
+    let mut removed = Vec::new();
+    for block in document.blocks() {
+        let mut previous: Option<&Entry> = None;
+        for &index in &block.entries {
+            let Some(entry) = document.lines()[index].entry() else {
+                previous = None;
+                continue;
+            };
+            if previous.is_some_and(|previous| continues(entry, previous)) {
+                removed.push(index);
+            } else {
+                previous = Some(entry);
+            }
+        }
+    }
+    for &index in removed.iter().rev() {
+        document.remove(index);
+    }
+    removed.len()
+}
+
+/// Moves all times of `day` by `minutes`. Nothing is changed if any time would leave the day.
+pub fn shift(day: &mut Day, minutes: SignedMinutes) -> Result<(), ShiftError> {
+    let times = day
+        .entries
+        .iter()
+        .map(|e| {
+            e.value
+                .time
+                .checked_add_signed(minutes)
+                .ok_or(ShiftError::OutOfDay(e.line))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    for (entry, time) in day.entries.iter_mut().zip(times) {
+        entry.value.time = time;
+    }
+    Ok(())
+}
+
+/// Like [`shift`] for the day `date` of `document`. Returns the number of changed lines.
+pub fn shift_in(
+    document: &mut Document,
+    date: Date,
+    minutes: SignedMinutes,
+) -> Result<usize, ShiftError> {
+    let block = document.block(date).ok_or(ShiftError::MissingDay(date))?;
+    let mut replacements = Vec::new();
+    for &index in &block.entries {
+        let line = &document.lines()[index];
+        let Some(entry) = line.entry() else {
+            continue;
+        };
+        let time = entry
+            .time
+            .checked_add_signed(minutes)
+            .ok_or(ShiftError::OutOfDay(index + 1))?;
+        replacements.push((index, with_time(line.text(), time)));
+    }
+    for (index, text) in &replacements {
+        document.replace(*index, text);
+    }
+    Ok(replacements.len())
+}
+
+fn header(date: Date) -> String {
+    format!(
+        "* {}. {:0>2}.{:0>2}.",
+        weekday_to_str(date.weekday()),
+        date.day(),
+        date.month()
+    )
+}
+
+/// Rewrites `document` in the canonical style: no indentation, single spaces, zero-padded day
+/// headers and exactly one blank line before every day and the comments directly above it.
+/// Returns whether anything changed.
+pub fn normalize(document: &mut Document) -> bool {
+    let lines = document.lines();
+    let attached_to_day = |index: usize| {
+        lines[index..]
+            .iter()
+            .find(|l| !matches!(l.node(), Node::Comment))
+            .is_some_and(|l| matches!(l.node(), Node::Day(_)))
+    };
+    let mut output: Vec<String> = Vec::new();
+    let mut in_block = false;
+    let mut in_comments = false;
+    let mut blank = false;
+    for (index, line) in lines.iter().enumerate() {
+        let text = match line.node() {
+            Node::Blank => {
+                blank = true;
+                continue;
+            }
+            Node::Day(Ok(date)) => header(*date),
+            Node::Entry(Ok(entry)) => entry.to_string(),
+            Node::Day(Err(_)) | Node::Entry(Err(_)) | Node::Comment => {
+                line.text().trim().to_owned()
+            }
+        };
+        let is_comment = matches!(line.node(), Node::Comment);
+        let starts_day =
+            matches!(line.node(), Node::Day(_)) || (is_comment && attached_to_day(index));
+        let separate = if starts_day {
+            !in_comments
+        } else {
+            blank && !in_block
+        };
+        if separate && !output.is_empty() {
+            output.push(String::new());
+        }
+        in_comments = starts_day && is_comment;
+        in_block |= matches!(line.node(), Node::Day(_));
+        blank = false;
+        output.push(text);
+    }
+    let ending = document.line_ending();
+    let mut text = output.join(ending);
+    if !output.is_empty() {
+        text.push_str(ending);
+    }
+    if text == document.to_string() {
+        return false;
+    }
+    *document = Document::parse(&text, document.month());
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use crate::parse::parse;
+    use crate::Date;
+
+    use super::*;
+
+    const TEXT: &str = "* Mo. 15.04.
+09:00 AA A
+10:00 AA A
+# note
+10:30 AA A
+11:00 AA B
+12:00
+12:30 AA B
+13:00 BB
+";
+
+    fn month() -> Date {
+        Date::new(NaiveDate::from_ymd_opt(2024, 4, 1).unwrap())
+    }
+
+    #[test]
+    fn test_merge_adjacent() {
+        let mut days = parse(TEXT.as_bytes(), month()).unwrap();
+        assert_eq!(merge_adjacent(&mut days[0]), 2);
+        let lines: Vec<_> = days[0].entries.iter().map(|e| e.line).collect();
+        assert_eq!(lines, vec![2, 6, 7, 8, 9]);
+    }
+
+    #[test]
+    fn test_shift() {
+        let mut days = parse(TEXT.as_bytes(), month()).unwrap();
+        shift(&mut days[0], SignedMinutes::from(-30)).unwrap();
+        assert_eq!(
+            days[0].entries[0].value.time,
+            crate::Time::new(8, 30).unwrap()
+        );
+        assert_eq!(
+            shift(&mut days[0], SignedMinutes::from(-9 * 60)),
+            Err(ShiftError::OutOfDay(2))
+        );
+        assert_eq!(
+            days[0].entries[0].value.time,
+            crate::Time::new(8, 30).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_shift_in() {
+        let mut document = Document::parse("* Mo. 15.04.\n  09:00 AA A\n# c\n10:00\n", month());
+        assert_eq!(
+            shift_in(&mut document, month(), SignedMinutes::from(6)),
+            Err(ShiftError::MissingDay(month()))
+        );
+        let date = Date::new(NaiveDate::from_ymd_opt(2024, 4, 15).unwrap());
+        assert_eq!(shift_in(&mut document, date, SignedMinutes::from(6)), Ok(2));
+        assert_eq!(
+            document.to_string(),
+            "* Mo. 15.04.\n  09:06 AA A\n# c\n10:06\n"
+        );
+    }
+
+    #[test]
+    fn test_normalize() {
+        let text = "\n#! hours_per_week: 40\n\n\n  * Mo. 1.04.\n  09:00   AA  A\n\n12:00\n# note\n* Di. 2.04.\n09:00 BB\n\n\n10:00\n\n* Mi. 03.04.\n09:00 AA\n10:00\n* Do. 4.4.\n";
+        let mut document = Document::parse(text, month());
+        assert!(normalize(&mut document));
+        let normalized = document.to_string();
+        assert_eq!(
+            normalized,
+            "#! hours_per_week: 40\n\n* Mo. 01.04.\n09:00 AA A\n12:00\n\n# note\n* Di. 02.04.\n09:00 BB\n10:00\n\n* Mi. 03.04.\n09:00 AA\n10:00\n\n* Do. 04.04.\n"
+        );
+        assert!(!normalize(&mut document));
+        let mut document = Document::parse("* Mo. 01.04.\r\n09:00 AA\r\n10:00", month());
+        assert!(normalize(&mut document));
+        assert_eq!(
+            document.to_string(),
+            "* Mo. 01.04.\r\n09:00 AA\r\n10:00\r\n"
+        );
+    }
+
+    #[test]
+    fn test_merge_adjacent_in() {
+        let mut document = Document::parse(TEXT, month());
+        assert_eq!(merge_adjacent_in(&mut document), 2);
+        assert_eq!(
+            document.to_string(),
+            "* Mo. 15.04.\n09:00 AA A\n# note\n11:00 AA B\n12:00\n12:30 AA B\n13:00 BB\n"
+        );
+    }
+}